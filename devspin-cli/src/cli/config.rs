@@ -1,17 +1,112 @@
+use crate::cli::env::EnvOverrides;
+use crate::generate::write_output;
+use crate::parser::document::Document;
+use crate::parser::format::Format;
+use crate::parser::migrate;
 use crate::prelude::*;
-// use clap::{Parser, Subcommand};
+use crate::utils::{
+    config_loader::{default_project_name, load_resolved_config, resolve_root},
+    devspin_finder::find_devspin_yml_parallel,
+    diff,
+    prompt::confirm,
+    stack_detector::{detect, Stack},
+};
+use colored::*;
 
 #[derive(clap::Args, Debug)]
 pub struct ConfigArgs {
-    /*
-    Add fields as needed for the config command
-    */
+    /// Apply the changes without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Show the changes without writing them
+    #[arg(long, conflicts_with = "yes")]
+    dry_run: bool,
+
+    /// Print the config with its `${VAR}` references expanded, without detecting the stack
+    #[arg(long, conflicts_with_all = ["yes", "dry_run"])]
+    resolved: bool,
+
+    #[command(flatten)]
+    overrides: EnvOverrides,
+
+    #[arg(short, long)]
+    root: Option<String>,
 }
 
-#[allow(dead_code)]
 impl ConfigArgs {
     pub async fn execute(&self) -> Result<(), ProcessError> {
-        println!("Configuration management coming soon!");
+        let root = resolve_root(self.root.as_deref())?;
+        if self.resolved {
+            let (_, config, secrets) = load_resolved_config(&root, &self.overrides.vars)?;
+            print!("{}", secrets.redact(&config.write()));
+            return Ok(());
+        }
+        let detection = detect(&root);
+        if detection.stacks.is_empty() {
+            let indicators: Vec<&str> = Stack::ALL
+                .iter()
+                .flat_map(|stack| stack.indicators().iter().copied())
+                .collect();
+            println!(
+                "No known project files in {} (looked for {})",
+                root.display(),
+                indicators.join(", ")
+            );
+            return Ok(());
+        }
+        let stacks: Vec<&str> = detection.stacks.iter().map(Stack::name).collect();
+        println!(
+            "{} {}",
+            "▶ Detected".cyan().bold(),
+            stacks.join(", ").bold()
+        );
+
+        let path = find_devspin_yml_parallel(&root).unwrap_or_else(|_| root.join("devspin.yml"));
+        // The changes are edits to the YAML document, keeping its comments
+        if Format::from_path(&path).is_some_and(|format| format != Format::Yaml) {
+            return Err(ProcessError::InvalidConfig {
+                error_msg: format!(
+                    "{}: only YAML configs can be updated, convert it with `devspin convert --to yaml`",
+                    path.display()
+                ),
+                exit_status: 422,
+            });
+        }
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        // An empty file (as left by `devspin init`) is filled from scratch
+        let updated = if current.trim().is_empty() {
+            detection.to_config(&default_project_name(&root)).write()
+        } else {
+            let mut document = Document::parse(&current, &path.display().to_string())?;
+            // Edits are made in the current format, an older file is migrated first
+            migrate::migrate(&mut document)?;
+            let config = document.config()?;
+            document.update(&detection.merge_into(&config))?;
+            document.to_string()
+        };
+
+        let label = path
+            .strip_prefix(&root)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let changes = diff::unified(&current, &updated, &label);
+        if changes.is_empty() {
+            println!("{} {} is up to date", "✔".green(), label);
+            return Ok(());
+        }
+        print!("{}", diff::paint(&changes));
+
+        if self.dry_run {
+            return Ok(());
+        }
+        if !self.yes && !confirm(&format!("Apply these changes to {}?", label)) {
+            println!("No changes written");
+            return Ok(());
+        }
+        write_output(&path, &updated)?;
+        println!("{} {}", "✔ Updated".green().bold(), path.display());
         Ok(())
     }
 }
@@ -0,0 +1,88 @@
+use crate::generate::write_output;
+use crate::parser::format::Format;
+use crate::prelude::*;
+use crate::utils::{config_loader::load_config, config_loader::resolve_root, devspin_finder};
+use clap::Args;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// Format to write the config in
+    #[arg(long, value_enum)]
+    to: Format,
+
+    /// Write to this path instead of devspin.<format> next to the current config
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Print to stdout instead of writing a file
+    #[arg(long, conflicts_with = "output")]
+    stdout: bool,
+
+    /// Overwrite the output file if it exists
+    #[arg(short, long)]
+    force: bool,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl ConvertArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let (path, config) = load_config(&root)?;
+        let content = self.to.write(&config);
+
+        if self.stdout {
+            print!("{}", content);
+            return Ok(());
+        }
+        let target = self
+            .output
+            .clone()
+            .unwrap_or_else(|| path.with_file_name(self.to.file_name()));
+        if target == path {
+            return Err(ProcessError::CreateFileFailed {
+                error_msg: format!(
+                    "{} is already the config file (use --output to write a copy)",
+                    path.display()
+                ),
+                exit_status: 409,
+            });
+        }
+        if target.exists() && !self.force {
+            return Err(ProcessError::CreateFileFailed {
+                error_msg: format!(
+                    "{} already exists (use --force to overwrite it)",
+                    target.display()
+                ),
+                exit_status: 409,
+            });
+        }
+
+        write_output(&target, &content)?;
+        println!(
+            "{} {} to {}",
+            "✔ Converted".green().bold(),
+            path.display(),
+            target.display()
+        );
+        if target.parent() == path.parent() && rank(&path) < rank(&target) {
+            println!(
+                "{} is still read first, remove it to switch to {}",
+                path.display(),
+                target.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Position of the file name in the finder's order of preference
+fn rank(path: &Path) -> usize {
+    devspin_finder::CONFIG_FILE_NAMES
+        .iter()
+        .position(|name| path.file_name().is_some_and(|file| file == *name))
+        .unwrap_or(usize::MAX)
+}
@@ -0,0 +1,174 @@
+use crate::parser::devspin_generator::DevSpinConfig;
+use crate::parser::interpolate::Layer;
+use crate::prelude::*;
+use crate::runtime::connection_env;
+use crate::secrets::Secrets;
+use crate::utils::config_loader::{
+    env_file_layers, env_layers, load_config, load_resolved_config, resolve_root,
+};
+use crate::utils::export::{self, ExportFormat};
+use clap::Args;
+use colored::*;
+use indexmap::IndexMap;
+
+#[derive(Args, Debug)]
+pub struct EnvArgs {
+    /// How to print the variables
+    #[arg(short, long, value_enum, default_value = "sh")]
+    format: ExportFormat,
+
+    /// Show the variables whose value differs from the current environment
+    #[arg(long, conflicts_with = "explain")]
+    diff: bool,
+
+    /// Show which source the value of KEY comes from and which sources it overrides
+    #[arg(long, value_name = "KEY")]
+    explain: Option<String>,
+
+    #[command(flatten)]
+    overrides: EnvOverrides,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+/// `--env KEY=VALUE`, shared by the commands that use the project environment
+#[derive(Args, Debug, Default)]
+pub struct EnvOverrides {
+    /// Set a variable above every other source (repeatable)
+    #[arg(
+        short = 'e',
+        long = "env",
+        value_name = "KEY=VALUE",
+        value_parser = parse_override,
+        global = true
+    )]
+    pub vars: Vec<(String, String)>,
+}
+
+fn parse_override(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got `{}`", arg)),
+    }
+}
+
+/// The variables of the project: the `<SERVICE>_HOST` / `<SERVICE>_PORT` of its
+/// services (as reached from the project network when `from_network` is set),
+/// then `env`, which wins over them
+pub(crate) fn project_env(config: &DevSpinConfig, from_network: bool) -> IndexMap<String, String> {
+    let mut env = connection_env(&config.content.services, from_network);
+    env.extend(config.content.env.clone());
+    env
+}
+
+impl EnvArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let (config_path, resolved, secrets) = load_resolved_config(&root, &self.overrides.vars)?;
+        let raw = project_env(&resolved, false);
+        if self.diff {
+            print_diff(&raw, &secrets);
+            return Ok(());
+        }
+        let Some(key) = &self.explain else {
+            print!("{}", export::render(&exported(raw, &secrets), self.format)?);
+            return Ok(());
+        };
+
+        // Every source, highest priority first
+        let (_, config) = load_config(&root)?;
+        let mut sources = env_layers(&root, &self.overrides.vars)?;
+        let file_name = config_path
+            .file_name()
+            .map_or("devspin.yml".into(), |name| name.to_string_lossy());
+        sources.push(Layer::new(
+            format!("{} env", file_name),
+            config.content.env.clone(),
+        ));
+        let mut files = env_file_layers(&config_path, &config.content.env_file)?;
+        files.reverse();
+        sources.extend(files);
+
+        let defining: Vec<&Layer> = sources
+            .iter()
+            .filter(|layer| layer.vars.contains_key(key))
+            .collect();
+        let Some(value) = resolved.content.env.get(key) else {
+            if let Some(value) = raw.get(key) {
+                println!("{}={}", key.bold(), secrets.redact(value));
+                println!("  {} address of a service, from its `ports`", "✔".green());
+                return Ok(());
+            }
+            match defining.first() {
+                Some(layer) => println!(
+                    "{} is only set in the {}, which does not add variables to the project",
+                    key.bold(),
+                    layer.name
+                ),
+                None => println!("{} is not set by any source", key.bold()),
+            }
+            return Ok(());
+        };
+
+        println!("{}={}", key.bold(), secrets.redact(value));
+        let width = defining
+            .iter()
+            .map(|layer| layer.name.len())
+            .max()
+            .unwrap_or(0);
+        for (index, layer) in defining.iter().enumerate() {
+            let raw = secrets.redact(&layer.vars[key]);
+            let line = format!("{:<width$}  {}", layer.name, raw);
+            if index == 0 {
+                println!("  {} {}", "✔".green(), line);
+            } else {
+                println!("    {}", format!("{}  (overridden)", line).dimmed());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `vars` without the ones holding a secret, which are named on stderr: the
+/// output is evaluated or written to files, the values only reach tasks
+fn exported(vars: IndexMap<String, String>, secrets: &Secrets) -> IndexMap<String, String> {
+    let mut exported = IndexMap::new();
+    for (key, value) in vars {
+        if secrets.find(&value).is_some() {
+            eprintln!(
+                "{} {} holds a secret and is not exported, use `devspin shell` or `devspin run`",
+                "warning:".yellow().bold(),
+                key
+            );
+            continue;
+        }
+        exported.insert(key, value);
+    }
+    exported
+}
+
+/// The variables of `vars` missing from the current environment or set to another value
+fn print_diff(vars: &IndexMap<String, String>, secrets: &Secrets) {
+    let mut differ = 0;
+    for (key, value) in vars {
+        match std::env::var(key) {
+            Ok(current) if &current == value => continue,
+            Ok(current) => println!(
+                "{}",
+                format!(
+                    "~ {}: {} → {}",
+                    key,
+                    secrets.redact(&current),
+                    secrets.redact(value)
+                )
+                .yellow()
+            ),
+            Err(_) => println!("{}", format!("+ {}={}", key, secrets.redact(value)).green()),
+        }
+        differ += 1;
+    }
+    if differ == 0 {
+        println!("The current environment already matches the project");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::cli::env::EnvOverrides;
+use crate::generate::{compose, dockerfile, write_output};
+use crate::prelude::*;
+use crate::utils::config_loader::{load_generate_config, resolve_root};
+use clap::{Args, Subcommand};
+use colored::*;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    #[command(subcommand)]
+    target: GenerateTarget,
+
+    /// Write to this path instead of the default file in the project root
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+
+    /// Print to stdout instead of writing a file
+    #[arg(long, global = true, conflicts_with = "output")]
+    stdout: bool,
+
+    #[command(flatten)]
+    overrides: EnvOverrides,
+
+    #[arg(short, long, global = true)]
+    root: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum GenerateTarget {
+    /// Dockerfile built from `base`, `system` and `env`
+    Dockerfile,
+    /// docker-compose.yml with the declared services and the app container
+    Compose,
+}
+
+impl GenerateArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        // Generated files are committed: neither .env files nor the shell environment go in
+        let (_, mut config, secrets) = load_generate_config(&root, &self.overrides.vars)?;
+        // Generated files get the references, the values are passed at run time
+        secrets.withhold(&mut config)?;
+
+        let (file_name, content) = match self.target {
+            GenerateTarget::Dockerfile => ("Dockerfile", dockerfile::render(&config)),
+            GenerateTarget::Compose => ("docker-compose.yml", compose::render(&config)),
+        };
+        if let Some(name) = secrets.find(&content) {
+            return Err(ProcessError::InvalidConfig {
+                error_msg: format!(
+                    "{} would contain the value of secret `{}`, secrets can only be used in `env` and service `environment`",
+                    file_name, name
+                ),
+                exit_status: 422,
+            });
+        }
+
+        if self.stdout {
+            print!("{}", content);
+            return Ok(());
+        }
+        let path = self.output.clone().unwrap_or_else(|| root.join(file_name));
+        write_output(&path, &content)?;
+        println!("{} {}", "✔ Generated".green().bold(), path.display());
+        Ok(())
+    }
+}
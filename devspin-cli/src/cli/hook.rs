@@ -0,0 +1,200 @@
+use crate::cli::env::project_env;
+use crate::parser::devspin_generator::DevSpinGenerator;
+use crate::parser::validator::is_valid_env_name;
+use crate::prelude::*;
+use crate::utils::config_loader::resolve_config;
+use crate::utils::devspin_finder::find_devspin_yml_upward;
+use crate::utils::hook::{self, CacheEntry, HookShell, HookState};
+use clap::Args;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct HookArgs {
+    /// Shell to print the hook for, e.g. `eval "$(devspin hook bash)"` in ~/.bashrc
+    #[arg(value_enum)]
+    shell: HookShell,
+
+    /// Print the commands loading or unloading the env of the current directory (run by the hook)
+    #[arg(long, hide = true)]
+    apply: bool,
+}
+
+impl HookArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        if !self.apply {
+            let devspin = std::env::current_exe().map_err(|e| ProcessError::RootNotFound {
+                error_msg: format!("Could not find the devspin executable: {}", e),
+                exit_status: 500,
+            })?;
+            print!("{}", self.shell.script(&devspin));
+            return Ok(());
+        }
+
+        let state = HookState::from_env();
+        let format = self.shell.export_format();
+        let cwd = std::env::current_dir().map_err(|e| ProcessError::RootNotFound {
+            error_msg: format!("Could not read the current directory: {}", e),
+            exit_status: 404,
+        })?;
+        let Some(config_path) = find_devspin_yml_upward(&cwd) else {
+            if let Some(root) = &state.root {
+                eprintln!("devspin: unloading {}", root.display());
+                print!("{}", state.unload(format)?);
+            }
+            return Ok(());
+        };
+        let root = config_path.parent().unwrap_or(Path::new("."));
+
+        // A cloned repository must not run anything before its config was read
+        let allowed = hook::allow_dir().is_some_and(|dir| hook::is_allowed(&dir, &config_path));
+        if !allowed {
+            eprintln!(
+                "devspin: {} is not allowed, run `devspin allow` to load it",
+                config_path.display()
+            );
+            if state.root.is_some() {
+                print!("{}", state.unload(format)?);
+            }
+            return Ok(());
+        }
+
+        let cache_path = hook::cache_dir().map(|dir| hook::cache_path(&dir, &config_path));
+        let cached = cache_path
+            .as_deref()
+            .and_then(hook::read_cache)
+            .filter(|entry| entry.config == config_path && entry.is_fresh());
+        if let Some(entry) = &cached {
+            if state.root.as_deref() == Some(root) && state.stamp == entry.stamp() {
+                return Ok(());
+            }
+        }
+
+        let entry = match cached {
+            Some(entry) => entry,
+            None => {
+                let entry = resolve(&config_path, root, &state)?;
+                // Without a cache the hook still works, only slower
+                if let Some(cache_path) = &cache_path {
+                    let _ = hook::write_cache(cache_path, &entry);
+                }
+                entry
+            }
+        };
+        eprintln!("devspin: loading {}", config_path.display());
+        for name in &entry.invalid {
+            eprintln!(
+                "devspin: `{}` is not a valid variable name and is not exported",
+                name.escape_debug()
+            );
+        }
+        for name in &entry.withheld {
+            eprintln!(
+                "devspin: {} holds a secret and is not exported, use `devspin shell` or `devspin run`",
+                name
+            );
+        }
+        print!(
+            "{}",
+            state.load(&entry, format, |key| std::env::var(key).ok())?
+        );
+        Ok(())
+    }
+}
+
+/// Variables of the project of `config_path`, resolved against the shell
+/// environment as it was before the hook loaded anything
+fn resolve(config_path: &Path, root: &Path, state: &HookState) -> Result<CacheEntry, ProcessError> {
+    for (key, previous) in &state.backup {
+        match previous {
+            Some(value) => std::env::set_var(key, value),
+            None => std::env::remove_var(key),
+        }
+    }
+
+    let config =
+        DevSpinGenerator::parse_file(config_path).map_err(|e| ProcessError::InvalidConfig {
+            error_msg: e.to_string(),
+            exit_status: 422,
+        })?;
+    let dir = config_path.parent().unwrap_or(Path::new("."));
+    let sources: Vec<_> = [
+        config_path.to_path_buf(),
+        root.join(".env"),
+        root.join(".env.local"),
+    ]
+    .into_iter()
+    .chain(config.content.env_file.iter().map(|file| dir.join(file)))
+    .collect();
+    let mut entry = CacheEntry::new(config_path, sources);
+
+    let (config, secrets) = resolve_config(config_path, config, root, &[])?;
+    for (key, value) in project_env(&config, false) {
+        // The hook output is evaluated by the shell, a name like `A; cmd` would run
+        if !is_valid_env_name(&key) {
+            entry.invalid.push(key);
+            continue;
+        }
+        // Secrets stay out of the shell environment and of the cache file
+        match secrets.find(&value) {
+            Some(_) => entry.withheld.push(key),
+            None => {
+                entry.vars.insert(key, value);
+            }
+        }
+    }
+    Ok(entry)
+}
+
+#[derive(Args, Debug)]
+pub struct AllowArgs {
+    /// Stop the hook from loading the config instead
+    #[arg(long)]
+    revoke: bool,
+
+    /// Directory to look for the config from, upward (defaults to the current directory)
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl AllowArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let dir = match &self.root {
+            Some(root) => PathBuf::from(root),
+            None => std::env::current_dir().map_err(|e| ProcessError::RootNotFound {
+                error_msg: format!("Could not read the current directory: {}", e),
+                exit_status: 404,
+            })?,
+        };
+        let config_path =
+            find_devspin_yml_upward(&dir).ok_or_else(|| ProcessError::DevspinYmlNotFound {
+                error_msg: format!("No devspin.yml in {} or above", dir.display()),
+                exit_status: 404,
+            })?;
+        let allow_dir = hook::allow_dir().ok_or_else(|| ProcessError::CreateFileFailed {
+            error_msg: "Could not find the user data directory".to_string(),
+            exit_status: 500,
+        })?;
+        let failed = |e: std::io::Error| ProcessError::CreateFileFailed {
+            error_msg: format!("{}: {}", allow_dir.display(), e),
+            exit_status: 500,
+        };
+
+        if self.revoke {
+            hook::revoke(&allow_dir, &config_path).map_err(failed)?;
+            println!(
+                "{} The hook no longer loads {}",
+                "✔".green(),
+                config_path.display()
+            );
+        } else {
+            hook::allow(&allow_dir, &config_path).map_err(failed)?;
+            println!(
+                "{} The hook loads {} until it changes",
+                "✔".green(),
+                config_path.display()
+            );
+        }
+        Ok(())
+    }
+}
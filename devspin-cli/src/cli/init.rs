@@ -1,46 +1,54 @@
 use crate::utils::{
-    create_devspin_file::create_cfg_file, devspin_finder::find_devspin_yml_parallel,
-    root_finder::get_root_no_param,
+    config_loader::{default_project_name, resolve_root},
+    create_devspin_file::{backup_cfg_file, write_cfg_file},
+    devspin_finder::CONFIG_FILE_NAMES,
+    prompt::ask,
+    templates::{render, ServicePreset, Template, SERVICE_PRESETS},
 };
 
 use crate::prelude::*;
-use clap::Args;
-use std::path::PathBuf;
+use clap::{Args, ValueEnum};
+use colored::*;
+use std::io::IsTerminal;
 
 #[derive(Args, Debug)]
 pub struct InitArgs {
+    /// Stack the generated config starts from
+    #[arg(short, long, value_enum, default_value = "default")]
+    template: Template,
+
+    /// Overwrite an existing devspin.yml, keeping a `.bak` copy of it
+    #[arg(short, long)]
+    force: bool,
+
+    /// Ask for the name, base image and services even when stdin is not a terminal
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// Accept the template defaults without asking
+    #[arg(short, long, conflicts_with = "interactive")]
+    yes: bool,
+
     #[arg(short, long)]
     root: Option<String>,
 }
 
 impl InitArgs {
     pub async fn execute(&self) -> Result<(), ProcessError> {
-        let root = if let Some(root_path) = &self.root {
-            let root_buf = PathBuf::from(root_path);
-            if !root_buf.exists() {
-                return Err(ProcessError::RootNotFound {
-                    error_msg: format!("Specified root directory does not exist: {}", root_path),
-                    exit_status: 404,
-                });
-            }
-            if !root_buf.is_dir() {
-                return Err(ProcessError::RootNotFound {
-                    error_msg: format!("Specified root path is not a directory: {}", root_path),
-                    exit_status: 404,
-                });
-            }
-            root_buf
-        } else {
-            get_root_no_param().map_err(|e| ProcessError::RootNotFound {
-                error_msg: format!("Could not find project root: {}", e),
-                exit_status: 404,
-            })?
-        };
-
-        // Check if devspin.yml already exists (don't overwrite)
-        if find_devspin_yml_parallel(&root).is_ok() {
+        let root = resolve_root(self.root.as_deref())?;
+
+        // Only configs of this directory are replaced, never one of a nested project
+        let existing: Vec<_> = CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| root.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+        if let Some(first) = existing.first().filter(|_| !self.force) {
             return Err(ProcessError::CreateFileFailed {
-                error_msg: "devspin.yml already exists in this directory".to_string(),
+                error_msg: format!(
+                    "{} already exists in this directory (use --force to overwrite it)",
+                    first.file_name().unwrap_or_default().to_string_lossy()
+                ),
                 exit_status: 409,
             });
         }
@@ -52,11 +60,74 @@ impl InitArgs {
                 exit_status: 400,
             })?;
 
-        create_cfg_file(root_str).map_err(|e| ProcessError::CreateFileFailed {
-            error_msg: e.to_string(),
-            exit_status: 400,
+        let mut name = default_project_name(&root);
+        let mut base = self.template.base().to_string();
+        let mut services: Vec<&ServicePreset> = self
+            .template
+            .services()
+            .iter()
+            .filter_map(|name| ServicePreset::find(name))
+            .collect();
+        if self.interactive || (!self.yes && std::io::stdin().is_terminal()) {
+            name = ask("Project name", &name);
+            base = ask("Base image", &base);
+            let defaults: Vec<&str> = services.iter().map(|preset| preset.name).collect();
+            let available: Vec<&str> = SERVICE_PRESETS.iter().map(|preset| preset.name).collect();
+            let answer = ask(
+                &format!("Services ({}, or none)", available.join(", ")),
+                &defaults.join(", "),
+            );
+            services = parse_services(&answer);
+        }
+
+        let content = render(self.template, &name, Some(&base), &services).map_err(|e| {
+            ProcessError::CreateFileFailed {
+                error_msg: e.to_string(),
+                exit_status: 400,
+            }
         })?;
 
+        for existing in existing {
+            let backup = backup_cfg_file(&existing)?;
+            std::fs::remove_file(&existing).map_err(|e| ProcessError::CreateFileFailed {
+                error_msg: format!("Failed to replace {}: {}", existing.display(), e),
+                exit_status: 400,
+            })?;
+            println!("Previous config saved to {}", backup.display());
+        }
+        let path = write_cfg_file(root_str, &content)?;
+        let template = self
+            .template
+            .to_possible_value()
+            .map(|v| v.get_name().to_string());
+        println!(
+            "{} {} from the {} template",
+            "✔ Created".green().bold(),
+            path.display(),
+            template.unwrap_or_default()
+        );
         Ok(())
     }
 }
+
+/// Presets named in a comma or space separated answer; unknown names are skipped with a warning
+fn parse_services(answer: &str) -> Vec<&'static ServicePreset> {
+    let mut services: Vec<&ServicePreset> = Vec::new();
+    for name in answer.split([',', ' ']).map(str::trim) {
+        if name.is_empty() || name == "none" {
+            continue;
+        }
+        match ServicePreset::find(name) {
+            Some(preset) if !services.iter().any(|s| s.name == preset.name) => {
+                services.push(preset)
+            }
+            Some(_) => {}
+            None => eprintln!(
+                "{} no service preset named '{}', skipping it",
+                "warning:".yellow().bold(),
+                name
+            ),
+        }
+    }
+    services
+}
@@ -0,0 +1,124 @@
+use crate::prelude::*;
+use crate::spin_features::executor::{self, Operation, Target};
+use crate::utils::config_loader::{load_config, resolve_root};
+use clap::Args;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct LintArgs {
+    /// File (currFile) or directory (currDir) to lint; the whole project (all) when omitted
+    path: Option<PathBuf>,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl LintArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        run_feature(
+            Operation::Lint,
+            self.path.as_deref(),
+            false,
+            self.root.as_deref(),
+        )
+        .await
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct FormatArgs {
+    /// File (currFile) or directory (currDir) to format; the whole project (all) when omitted
+    path: Option<PathBuf>,
+
+    /// Only report the files that are not formatted, and fail if there are any
+    #[arg(short, long)]
+    check: bool,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl FormatArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        run_feature(
+            Operation::Format,
+            self.path.as_deref(),
+            self.check,
+            self.root.as_deref(),
+        )
+        .await
+    }
+}
+
+/// Run the tools of the `operation` spin feature on `path`, when devspin.yml
+/// enables the feature for that mode
+async fn run_feature(
+    operation: Operation,
+    path: Option<&Path>,
+    check: bool,
+    root: Option<&str>,
+) -> Result<(), ProcessError> {
+    let root = resolve_root(root)?;
+    let (_, config) = load_config(&root)?;
+    let name = operation.feature();
+    let feature = config
+        .content
+        .spin_features
+        .get(name)
+        .filter(|feature| feature.enabled);
+    let Some(feature) = feature else {
+        println!(
+            "`{}` is not enabled in the spin-features of devspin.yml",
+            name
+        );
+        return Ok(());
+    };
+
+    let target = Target::resolve(path, &root)?;
+    if !target.allowed_by(feature) {
+        println!(
+            "`{}` does not run on {} ({} is not in its modes: {})",
+            name,
+            target,
+            target.mode(),
+            feature.modes.join(", ")
+        );
+        return Ok(());
+    }
+
+    let commands = executor::plan(operation, &target, &root, check)?;
+    let action = match (operation, check) {
+        (Operation::Lint, _) => "▶ Linting",
+        (Operation::Format, true) => "▶ Checking the format of",
+        (Operation::Format, false) => "▶ Formatting",
+    };
+    println!("{} {}", action.cyan().bold(), target.to_string().bold());
+
+    let mut failures = Vec::new();
+    for command in &commands {
+        println!("{} {}", "$".dimmed(), command);
+        let code = executor::run(command, &root).await?;
+        if code != 0 {
+            failures.push((command, code));
+        }
+    }
+
+    let Some((_, code)) = failures.first() else {
+        let done = match (operation, check) {
+            (Operation::Lint, _) => "No lint errors in",
+            (Operation::Format, true) => "Already formatted:",
+            (Operation::Format, false) => "Formatted",
+        };
+        println!("{} {} {}", "✔".green(), done, target);
+        return Ok(());
+    };
+    let failed: Vec<&str> = failures
+        .iter()
+        .map(|(command, _)| command.program.as_str())
+        .collect();
+    Err(ProcessError::TaskFailed {
+        error_msg: format!("{} failed: {}", name, failed.join(", ")),
+        exit_status: *code,
+    })
+}
@@ -12,23 +12,74 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     TestCmd(test_cmd::TestCmd),
-    Config,
+    /// Detect the project stack and fill in devspin.yml
+    Config(config::ConfigArgs),
     Init(init::InitArgs),
+    /// Check devspin.yml for mistakes beyond syntax errors
+    Validate(validate::ValidateArgs),
+    /// Print the JSON Schema of devspin.yml, for editor completion and validation
+    Schema(schema::SchemaArgs),
+    /// Rewrite the config as devspin.yml, devspin.json or devspin.toml
+    Convert(convert::ConvertArgs),
+    /// Run a task declared in devspin.yml
+    Run(run::RunArgs),
+    /// Generate container files from devspin.yml
+    Generate(generate::GenerateArgs),
+    /// Start the services declared in devspin.yml
+    Up(up::UpArgs),
+    /// Stop and remove the containers started by `up`
+    Down(up::DownArgs),
+    /// Print the project environment or explain where a variable comes from
+    Env(env::EnvArgs),
+    /// Start a shell in the project root with its env and service addresses
+    Shell(shell::ShellArgs),
+    /// Print a shell hook loading the project env on `cd` (bash, zsh, fish)
+    Hook(hook::HookArgs),
+    /// Let the shell hook load the devspin.yml of this directory, after reading it
+    Allow(hook::AllowArgs),
+    /// Run the linters of the project (the `lint` spin feature)
+    Lint(lint::LintArgs),
+    /// Run the formatters of the project (the `format` spin feature)
+    Format(lint::FormatArgs),
+    /// Manage the encrypted secrets devspin.yml refers to as `secret:NAME`
+    Secret(secret::SecretArgs),
 }
 
 impl Cli {
     pub async fn execute(self) -> Result<(), ProcessError> {
         match self.commands {
             Commands::TestCmd(args) => args.execute().await,
-            Commands::Config => {
-                println!("Configuration management coming soon!");
-                Ok(())
-            }
+            Commands::Config(args) => args.execute().await,
             Commands::Init(args) => args.execute().await,
+            Commands::Validate(args) => args.execute().await,
+            Commands::Schema(args) => args.execute().await,
+            Commands::Convert(args) => args.execute().await,
+            Commands::Run(args) => args.execute().await,
+            Commands::Generate(args) => args.execute().await,
+            Commands::Up(args) => args.execute().await,
+            Commands::Down(args) => args.execute().await,
+            Commands::Env(args) => args.execute().await,
+            Commands::Shell(args) => args.execute().await,
+            Commands::Hook(args) => args.execute().await,
+            Commands::Allow(args) => args.execute().await,
+            Commands::Lint(args) => args.execute().await,
+            Commands::Format(args) => args.execute().await,
+            Commands::Secret(args) => args.execute().await,
         }
     }
 }
 
 mod config;
+mod convert;
+mod env;
+mod generate;
+mod hook;
 mod init;
+mod lint;
+mod run;
+mod schema;
+mod secret;
+mod shell;
 mod test_cmd;
+mod up;
+mod validate;
@@ -0,0 +1,112 @@
+use crate::cli::env::EnvOverrides;
+use crate::parser::devspin_generator::DevSpinConfig;
+use crate::prelude::*;
+use crate::runtime::{detect_runtime, Environment};
+use crate::tasks::scheduler::{Scheduler, TaskStatus};
+use crate::utils::config_loader::{load_resolved_config, resolve_root};
+use clap::Args;
+use colored::*;
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Tasks to run; their `depends_on` tasks run first
+    #[arg(required = true)]
+    tasks: Vec<String>,
+
+    /// Maximum number of tasks running at the same time (defaults to the CPU count)
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    /// Run tasks even when their inputs are unchanged
+    #[arg(short, long)]
+    force: bool,
+
+    #[command(flatten)]
+    overrides: EnvOverrides,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl RunArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let (_, config, secrets) = load_resolved_config(&root, &self.overrides.vars)?;
+
+        for name in &self.tasks {
+            if !config.content.tasks.iter().any(|t| &t.name == name) {
+                let available: Vec<&str> = config
+                    .content
+                    .tasks
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect();
+                return Err(ProcessError::TaskNotFound {
+                    error_msg: format!(
+                        "No task named '{}' (available: {})",
+                        name,
+                        available.join(", ")
+                    ),
+                    exit_status: 404,
+                });
+            }
+        }
+
+        wait_for_services(&config)
+            .await
+            .map_err(|e| secrets.redact_error(e))?;
+
+        let mut scheduler = Scheduler::new(config.content.tasks, root, config.content.env)
+            .with_force(self.force)
+            .with_secrets(secrets.clone());
+        if let Some(jobs) = self.jobs {
+            scheduler = scheduler.with_jobs(jobs);
+        }
+
+        let plan = scheduler.plan(&self.tasks)?;
+        println!("{} {}", "▶ Running".cyan().bold(), plan.join(" → ").bold());
+        let summary = scheduler
+            .run(&self.tasks)
+            .await
+            .map_err(|e| secrets.redact_error(e))?;
+
+        if summary.reports.len() > 1 {
+            println!("\n{}", summary.table());
+        } else if let [report] = summary.reports.as_slice() {
+            if report.status == TaskStatus::UpToDate {
+                println!("{} {} is up to date", "✔".green(), report.name);
+            }
+        }
+        if let Some((name, code)) = summary.first_failure() {
+            return Err(ProcessError::TaskFailed {
+                error_msg: format!("Task '{}' exited with code {}", name, code),
+                exit_status: code,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Block until the services with a healthcheck are healthy, so tasks do not race
+/// their startup. Without a container runtime there is nothing to wait for.
+async fn wait_for_services(config: &DevSpinConfig) -> Result<(), ProcessError> {
+    let services = &config.content.services;
+    if !services.iter().any(|service| service.healthcheck.is_some()) {
+        return Ok(());
+    }
+    let Ok(runtime) = detect_runtime() else {
+        return Ok(());
+    };
+
+    let not_running = Environment::new(runtime.as_ref(), &config.header.name)
+        .wait_ready(services)
+        .await?;
+    for name in not_running {
+        eprintln!(
+            "{} service '{}' is not running, start it with `devspin up`",
+            "warning:".yellow().bold(),
+            name
+        );
+    }
+    Ok(())
+}
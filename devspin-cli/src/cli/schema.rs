@@ -0,0 +1,27 @@
+use crate::generate::write_output;
+use crate::parser::schema::schema_json;
+use crate::prelude::*;
+use clap::Args;
+use colored::*;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    /// Write the schema to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl SchemaArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let json = schema_json();
+        match &self.output {
+            Some(path) => {
+                write_output(path, &json)?;
+                println!("{} {}", "✔ Generated".green().bold(), path.display());
+            }
+            None => print!("{}", json),
+        }
+        Ok(())
+    }
+}
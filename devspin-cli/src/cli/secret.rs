@@ -0,0 +1,108 @@
+use crate::error::DevSpinError;
+use crate::prelude::*;
+use crate::secrets::{self, open_store};
+use clap::{Args, Subcommand};
+use colored::*;
+use std::io::{IsTerminal, Read};
+
+#[derive(Args, Debug)]
+pub struct SecretArgs {
+    #[command(subcommand)]
+    action: SecretAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum SecretAction {
+    /// Store a secret, read from stdin (typed without echo in a terminal)
+    Set {
+        /// Name used in devspin.yml as `secret:NAME`
+        name: String,
+    },
+    /// List the names of the stored secrets
+    List,
+    /// Remove a stored secret
+    Remove { name: String },
+}
+
+impl SecretArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let store = open_store()?;
+        match &self.action {
+            SecretAction::Set { name } => {
+                check_name(name)?;
+                let value = read_value(name)?;
+                store.set(name, &value).map_err(store_failed)?;
+                println!(
+                    "{} Stored secret {} in {}",
+                    "✔".green(),
+                    name.bold(),
+                    store.location()
+                );
+            }
+            SecretAction::List => {
+                let stored = store.load().map_err(store_failed)?;
+                if stored.is_empty() {
+                    println!("No secrets in {}", store.location());
+                }
+                for name in stored.keys() {
+                    println!("{}", name);
+                }
+            }
+            SecretAction::Remove { name } => {
+                if !store.remove(name).map_err(store_failed)? {
+                    return Err(ProcessError::SecretStoreFailed {
+                        error_msg: format!("No secret named '{}' in {}", name, store.location()),
+                        exit_status: 404,
+                    });
+                }
+                println!("{} Removed secret {}", "✔".green(), name.bold());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_name(name: &str) -> Result<(), ProcessError> {
+    if secrets::is_valid_name(name) {
+        return Ok(());
+    }
+    Err(ProcessError::SecretStoreFailed {
+        error_msg: format!(
+            "Invalid secret name '{}' (use letters, digits, `_`, `-` and `.`)",
+            name
+        ),
+        exit_status: 400,
+    })
+}
+
+/// The value from a hidden prompt, or all of stdin without its final newline
+fn read_value(name: &str) -> Result<String, ProcessError> {
+    let read_failed = |e: std::io::Error| ProcessError::SecretStoreFailed {
+        error_msg: format!("Failed to read the value of '{}': {}", name, e),
+        exit_status: 400,
+    };
+    let value = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password(format!("Value of {}: ", name)).map_err(read_failed)?
+    } else {
+        let mut value = String::new();
+        std::io::stdin()
+            .read_to_string(&mut value)
+            .map_err(read_failed)?;
+        let trimmed = value.strip_suffix('\n').unwrap_or(&value);
+        trimmed.strip_suffix('\r').unwrap_or(trimmed).to_string()
+    };
+    if value.is_empty() {
+        return Err(ProcessError::SecretStoreFailed {
+            error_msg: format!("Empty value for '{}', nothing stored", name),
+            exit_status: 400,
+        });
+    }
+    Ok(value)
+}
+
+fn store_failed(e: DevSpinError) -> ProcessError {
+    ProcessError::SecretStoreFailed {
+        error_msg: e.to_string(),
+        exit_status: 500,
+    }
+}
@@ -0,0 +1,155 @@
+use crate::cli::env::{project_env, EnvOverrides};
+use crate::generate::dockerfile;
+use crate::prelude::*;
+use crate::runtime::{detect_runtime, Environment};
+use crate::tasks::runner::exit_code;
+use crate::utils::config_loader::{load_generate_config, load_resolved_config, resolve_root};
+use crate::utils::shell::ShellKind;
+use clap::Args;
+use colored::*;
+use indexmap::IndexMap;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Set in devspin shells to the project name
+pub const PROJECT_VAR: &str = "DEVSPIN_PROJECT";
+/// Set in devspin shells to the project root
+pub const ROOT_VAR: &str = "DEVSPIN_ROOT";
+
+/// Shell started in containers when `--shell` is not given
+const CONTAINER_SHELL: &str = "/bin/sh";
+
+#[derive(Args, Debug)]
+pub struct ShellArgs {
+    /// Shell to start instead of $SHELL (/bin/sh with --container)
+    #[arg(short, long)]
+    shell: Option<String>,
+
+    /// Start the shell in a container built from `base` and `system`, with the project mounted
+    #[arg(short, long)]
+    container: bool,
+
+    #[command(flatten)]
+    overrides: EnvOverrides,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl ShellArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let (_, config, secrets) = load_resolved_config(&root, &self.overrides.vars)?;
+        let name = &config.header.name;
+        if let Ok(current) = std::env::var(PROJECT_VAR) {
+            eprintln!(
+                "{} already in the devspin shell of {}, this one starts inside it",
+                "warning:".yellow().bold(),
+                current
+            );
+        }
+
+        let mut env = project_env(&config, self.container);
+        env.insert(PROJECT_VAR.to_string(), name.clone());
+
+        if self.container {
+            // The image is built like `generate dockerfile`, the local values come in with `env`
+            let (_, mut image_config, image_secrets) =
+                load_generate_config(&root, &self.overrides.vars)?;
+            image_secrets.withhold(&mut image_config)?;
+            let dockerfile = dockerfile::render(&image_config);
+            let shell = self.shell.as_deref().unwrap_or(CONTAINER_SHELL);
+            env.insert(
+                ROOT_VAR.to_string(),
+                crate::runtime::SHELL_WORKDIR.to_string(),
+            );
+            env.insert("PS1".to_string(), format!("({}) $ ", name));
+
+            let runtime = detect_runtime()?;
+            let code = Environment::new(runtime.as_ref(), name)
+                .with_root(&root)
+                .shell(&dockerfile, env, vec![shell.to_string()])
+                .await
+                .map_err(|e| secrets.redact_error(e))?;
+            exited(name, u16::try_from(code).unwrap_or(1))?;
+        } else {
+            env.insert(ROOT_VAR.to_string(), root.display().to_string());
+            let shell = self.shell.clone().unwrap_or_else(user_shell);
+            println!(
+                "{} {} shell ({}), exit to leave",
+                "▶ Entering".cyan().bold(),
+                name.bold(),
+                shell
+            );
+            let code = run_shell(&shell, name, &root, &env).await?;
+            exited(name, code)?;
+        }
+
+        println!("{} Left the {} shell", "✔".green(), name.bold());
+        Ok(())
+    }
+}
+
+/// The exit code of the shell of `project`, passed on as devspin's own
+fn exited(project: &str, code: u16) -> Result<(), ProcessError> {
+    if code == 0 {
+        return Ok(());
+    }
+    Err(ProcessError::TaskFailed {
+        error_msg: format!("The {} shell exited with code {}", project, code),
+        exit_status: code,
+    })
+}
+
+/// $SHELL, or the platform's default shell
+fn user_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .or_else(|| {
+            cfg!(windows)
+                .then(|| std::env::var("COMSPEC").ok())
+                .flatten()
+        })
+        .unwrap_or_else(|| CONTAINER_SHELL.to_string())
+}
+
+/// Start `program` in `root` with `env` and the prompt marker, until it
+/// exits, and return its exit code
+async fn run_shell(
+    program: &str,
+    marker: &str,
+    root: &Path,
+    env: &IndexMap<String, String>,
+) -> Result<u16, ProcessError> {
+    let failed = |e: std::io::Error| ProcessError::TaskFailed {
+        error_msg: format!("Failed to start {}: {}", program, e),
+        exit_status: 127,
+    };
+    // A new directory only the user can read, removed when it is dropped: the
+    // shell runs the init files in it
+    let dir = tempfile::Builder::new()
+        .prefix("devspin-shell-")
+        .tempdir()
+        .map_err(failed)?;
+    let launch = ShellKind::detect(program).launch(marker, dir.path());
+
+    let status = async {
+        launch.write_files()?;
+        let mut child = Command::new(program)
+            .args(&launch.args)
+            .envs(env)
+            .envs(&launch.env)
+            .current_dir(root)
+            .spawn()?;
+        // Ctrl-C belongs to the shell, devspin waits for it to exit
+        let ignore_interrupts =
+            tokio::spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+        let status = child.wait().await;
+        ignore_interrupts.abort();
+        status
+    }
+    .await;
+    drop(dir);
+    status.map(|status| exit_code(&status)).map_err(failed)
+}
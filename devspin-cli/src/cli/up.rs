@@ -0,0 +1,86 @@
+use crate::cli::env::EnvOverrides;
+use crate::prelude::*;
+use crate::runtime::{detect_runtime, Environment, UpOptions};
+use crate::utils::config_loader::{load_config, load_resolved_config, resolve_root};
+use clap::Args;
+use colored::*;
+use std::time::Duration;
+
+#[derive(Args, Debug)]
+pub struct UpArgs {
+    /// Use local images instead of pulling them first
+    #[arg(long)]
+    no_pull: bool,
+
+    /// Seconds each service may take to become ready
+    #[arg(short, long, default_value_t = 60)]
+    timeout: u64,
+
+    #[command(flatten)]
+    overrides: EnvOverrides,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl UpArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let (_, config, secrets) = load_resolved_config(&root, &self.overrides.vars)?;
+        if config.content.services.is_empty() {
+            println!("No services declared in devspin.yml");
+            return Ok(());
+        }
+
+        let runtime = detect_runtime()?;
+        let options = UpOptions {
+            pull: !self.no_pull,
+            timeout: Duration::from_secs(self.timeout),
+            ..UpOptions::default()
+        };
+        Environment::new(runtime.as_ref(), &config.header.name)
+            .with_root(&root)
+            .up(&config.content.services, &options)
+            .await
+            .map_err(|e| secrets.redact_error(e))?;
+
+        println!("{} {} is up", "✔".green().bold(), config.header.name.bold());
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DownArgs {
+    /// Also remove the volumes of the project
+    #[arg(short, long)]
+    volumes: bool,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl DownArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let (_, config) = load_config(&root)?;
+
+        let runtime = detect_runtime()?;
+        let removed = Environment::new(runtime.as_ref(), &config.header.name)
+            .down(self.volumes)
+            .await?;
+
+        if removed.containers.is_empty() {
+            println!("No containers running for {}", config.header.name.bold());
+        }
+        for name in removed.containers {
+            println!("{} Removed {}", "✔".green(), name);
+        }
+        for name in removed.networks {
+            println!("{} Removed network {}", "✔".green(), name);
+        }
+        for name in removed.volumes {
+            println!("{} Removed volume {}", "✔".green(), name);
+        }
+        Ok(())
+    }
+}
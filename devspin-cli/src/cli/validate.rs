@@ -0,0 +1,103 @@
+use crate::error::DevSpinError;
+use crate::parser::devspin_generator::DevSpinGenerator;
+use crate::parser::format::Format;
+use crate::parser::validator::{validate_schema, validate_str, Severity};
+use crate::prelude::*;
+use crate::utils::{config_loader::resolve_root, devspin_finder::find_devspin_yml_parallel};
+use clap::Args;
+use colored::*;
+
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// Fail on warnings too
+    #[arg(long)]
+    strict: bool,
+
+    /// Check against the JSON Schema first, reporting every key or type
+    /// mistake at once instead of stopping at the first one
+    #[arg(long)]
+    schema: bool,
+
+    #[arg(short, long)]
+    root: Option<String>,
+}
+
+impl ValidateArgs {
+    pub async fn execute(&self) -> Result<(), ProcessError> {
+        let root = resolve_root(self.root.as_deref())?;
+        let path =
+            find_devspin_yml_parallel(&root).map_err(|e| ProcessError::DevspinYmlNotFound {
+                error_msg: e,
+                exit_status: 404,
+            })?;
+        // JSON and TOML are read with serde, then checked in their YAML form,
+        // which the locations of the diagnostics refer to
+        let (source, label) = match Format::from_path(&path) {
+            Some(Format::Json | Format::Toml) => {
+                let config = DevSpinGenerator::parse_file(&path)?;
+                (config.write(), format!("{} (as YAML)", path.display()))
+            }
+            _ => {
+                let label = path.display().to_string();
+                let source = std::fs::read_to_string(&path).map_err(|source| {
+                    ProcessError::from(DevSpinError::Io {
+                        path: label.clone(),
+                        source,
+                    })
+                })?;
+                (source, label)
+            }
+        };
+        let mut diagnostics = Vec::new();
+        if self.schema {
+            diagnostics = validate_schema(&source, &label)?;
+        }
+        // The semantic checks need a config that matches the schema
+        if diagnostics.is_empty() {
+            diagnostics = validate_str(&source, &label)?;
+        }
+
+        for diagnostic in &diagnostics {
+            let severity = match diagnostic.severity {
+                Severity::Error => format!("error[{}]", diagnostic.code()).red().bold(),
+                Severity::Warning => format!("warning[{}]", diagnostic.code()).yellow().bold(),
+            };
+            println!(
+                "{}:{}:{}: {} {}\n{}\n",
+                diagnostic.path,
+                diagnostic.line,
+                diagnostic.column,
+                severity,
+                diagnostic.message,
+                diagnostic.snippet
+            );
+        }
+
+        let count = |severity| {
+            diagnostics
+                .iter()
+                .filter(|d| d.severity == severity)
+                .count()
+        };
+        let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+        if errors == 0 && warnings == 0 {
+            println!("{} {} is valid", "✔".green(), path.display());
+            return Ok(());
+        }
+        let summary = format!(
+            "{} error{}, {} warning{}",
+            errors,
+            if errors == 1 { "" } else { "s" },
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        );
+        if errors > 0 || self.strict {
+            return Err(ProcessError::InvalidConfig {
+                error_msg: format!("{}: {}", path.display(), summary),
+                exit_status: 422,
+            });
+        }
+        println!("{} {}: {}", "✔".green(), path.display(), summary);
+        Ok(())
+    }
+}
@@ -10,6 +10,45 @@ pub enum ProcessError {
 
     #[error("Failed to create file (error: {error_msg}  status: {exit_status})")]
     CreateFileFailed { error_msg: String, exit_status: u16 },
+
+    #[error("Invalid devspin.yml (status: {exit_status})\n{error_msg}")]
+    InvalidConfig { error_msg: String, exit_status: u16 },
+
+    #[error("Task not found (error: {error_msg}  status: {exit_status})")]
+    TaskNotFound { error_msg: String, exit_status: u16 },
+
+    #[error("Task failed (error: {error_msg}  status: {exit_status})")]
+    TaskFailed { error_msg: String, exit_status: u16 },
+
+    #[error("Container runtime failed (error: {error_msg}  status: {exit_status})")]
+    RuntimeFailed { error_msg: String, exit_status: u16 },
+
+    #[error("Secret store failed (error: {error_msg}  status: {exit_status})")]
+    SecretStoreFailed { error_msg: String, exit_status: u16 },
+}
+
+impl ProcessError {
+    /// Exit code for the CLI process. Failed tasks forward their own exit code.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ProcessError::TaskFailed { exit_status, .. } => i32::from(*exit_status),
+            _ => 1,
+        }
+    }
+
+    /// The message of the error, to rewrite it before it is shown
+    pub fn message_mut(&mut self) -> &mut String {
+        match self {
+            ProcessError::DevspinYmlNotFound { error_msg, .. }
+            | ProcessError::RootNotFound { error_msg, .. }
+            | ProcessError::CreateFileFailed { error_msg, .. }
+            | ProcessError::InvalidConfig { error_msg, .. }
+            | ProcessError::TaskNotFound { error_msg, .. }
+            | ProcessError::TaskFailed { error_msg, .. }
+            | ProcessError::RuntimeFailed { error_msg, .. }
+            | ProcessError::SecretStoreFailed { error_msg, .. } => error_msg,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -18,4 +57,69 @@ pub enum DevSpinError {
     MissingField(String),
     #[error("Process error: {0}")]
     Process(#[from] ProcessError),
+    #[error("{path}:{line}:{column}: {message}\n{snippet}")]
+    Parse {
+        path: String,
+        line: usize,
+        column: usize,
+        message: String,
+        snippet: String,
+    },
+    /// A mistake found after parsing, with no location in the file.
+    #[error("{path}: {message}")]
+    Invalid { path: String, message: String },
+    #[error("Invalid edit: {0}")]
+    InvalidEdit(String),
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// A config that cannot be read or used is an invalid config (422); a
+/// process error carried through the parser is returned as it was
+impl From<DevSpinError> for ProcessError {
+    fn from(e: DevSpinError) -> Self {
+        match e {
+            DevSpinError::Process(e) => e,
+            e => ProcessError::InvalidConfig {
+                error_msg: e.to_string(),
+                exit_status: 422,
+            },
+        }
+    }
+}
+
+impl DevSpinError {
+    /// Builds a [`DevSpinError::Parse`] pointing at `byte_column` (1-based) of `source_line`,
+    /// which is line `line` (1-based) of the file at `path`.
+    pub fn parse(
+        path: impl Into<String>,
+        source_line: &str,
+        line: usize,
+        byte_column: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        let prefix_end = byte_column.saturating_sub(1).min(source_line.len());
+        let column = source_line
+            .get(..prefix_end)
+            .map_or(prefix_end, |prefix| prefix.chars().count())
+            + 1;
+
+        let gutter = " ".repeat(line.to_string().len());
+        let snippet = format!(
+            "{gutter} |\n{line} | {source_line}\n{gutter} | {}^",
+            " ".repeat(column - 1)
+        );
+
+        DevSpinError::Parse {
+            path: path.into(),
+            line,
+            column,
+            message: message.into(),
+            snippet,
+        }
+    }
 }
@@ -0,0 +1,181 @@
+use crate::generate::dockerfile::WORKDIR;
+use crate::parser::devspin_generator::{DevSpinConfig, VolumeMount};
+use crate::parser::yaml::{format_double_quoted, format_value};
+use crate::secrets;
+use indexmap::IndexMap;
+
+/// Name of the service built from the generated Dockerfile
+pub const APP_SERVICE: &str = "app";
+
+/// Render the compose file of `config`: every declared service plus an app
+/// service built from the generated Dockerfile with the project root mounted.
+pub fn render(config: &DevSpinConfig) -> String {
+    let mut output = format!(
+        "# Generated by devspin from devspin.yml ({}). Do not edit by hand.\nname: {}\n\nservices:\n",
+        config.header.name,
+        project_name(&config.header.name)
+    );
+
+    let app = app_service_name(config);
+    output.push_str(&format!(
+        "  {}:\n    build:\n      context: .\n      dockerfile: Dockerfile\n",
+        app
+    ));
+    push_environment(&mut output, &config.content.env);
+    output.push_str(&format!("    volumes:\n      - .:{}\n", WORKDIR));
+    let services: Vec<String> = config
+        .content
+        .services
+        .iter()
+        .map(|s| s.name.clone())
+        .collect();
+    push_list(&mut output, "depends_on", &services);
+    // The app has to reach every service, whichever networks they joined
+    let mut networks = declared_networks(config);
+    if !networks.is_empty() {
+        networks.insert(0, "default".to_string());
+    }
+    push_list(&mut output, "networks", &networks);
+
+    for service in &config.content.services {
+        output.push_str(&format!(
+            "\n  {}:\n    image: {}\n",
+            format_value(&service.name, 2),
+            quote(&service.image)
+        ));
+        if !service.ports.is_empty() {
+            output.push_str("    ports:\n");
+            for port in &service.ports {
+                output.push_str(&format!("      - {}\n", quote(port)));
+            }
+        }
+        push_environment(&mut output, &service.environment);
+        push_list(&mut output, "depends_on", &service.depends_on);
+        push_quoted_list(&mut output, "volumes", &service.volumes);
+        push_quoted_list(&mut output, "command", &service.command);
+        push_quoted_list(&mut output, "entrypoint", &service.entrypoint);
+        if let Some(restart) = &service.restart {
+            output.push_str(&format!("    restart: {}\n", quote(restart)));
+        }
+        push_list(&mut output, "networks", &service.networks);
+    }
+
+    let volumes = named_volumes(config);
+    if !volumes.is_empty() {
+        output.push_str("\nvolumes:\n");
+        for volume in volumes {
+            output.push_str(&format!("  {}: {{}}\n", volume));
+        }
+    }
+    let networks = declared_networks(config);
+    if !networks.is_empty() {
+        output.push_str("\nnetworks:\n");
+        for network in networks {
+            output.push_str(&format!("  {}: {{}}\n", format_value(&network, 2)));
+        }
+    }
+
+    output
+}
+
+/// Named volumes of every service, in declaration order
+fn named_volumes(config: &DevSpinConfig) -> Vec<String> {
+    let mut names = Vec::new();
+    for volume in config.content.services.iter().flat_map(|s| &s.volumes) {
+        if let Ok(VolumeMount::Named { name, .. }) = VolumeMount::parse(volume) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Networks declared by any service, in declaration order
+fn declared_networks(config: &DevSpinConfig) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for network in config.content.services.iter().flat_map(|s| &s.networks) {
+        if !names.contains(network) {
+            names.push(network.clone());
+        }
+    }
+    names
+}
+
+fn push_list(output: &mut String, key: &str, values: &[String]) {
+    if values.is_empty() {
+        return;
+    }
+    output.push_str(&format!("    {}:\n", key));
+    for value in values {
+        output.push_str(&format!("      - {}\n", format_value(value, 6)));
+    }
+}
+
+fn push_quoted_list(output: &mut String, key: &str, values: &[String]) {
+    if values.is_empty() {
+        return;
+    }
+    output.push_str(&format!("    {}:\n", key));
+    for value in values {
+        output.push_str(&format!("      - {}\n", quote(value)));
+    }
+}
+
+/// `app`, or `<project>-app` when a declared service already uses that name
+pub fn app_service_name(config: &DevSpinConfig) -> String {
+    let taken = |name: &str| config.content.services.iter().any(|s| s.name == name);
+    if !taken(APP_SERVICE) {
+        return APP_SERVICE.to_string();
+    }
+    format!("{}-{}", project_name(&config.header.name), APP_SERVICE)
+}
+
+/// Compose project names only allow lowercase letters, digits, `-` and `_`
+pub fn project_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9' | '-' | '_') => c,
+            _ => '-',
+        })
+        .collect();
+    let sanitized = sanitized.trim_start_matches(['-', '_']);
+    if sanitized.is_empty() {
+        "devspin".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+fn push_environment(output: &mut String, env: &IndexMap<String, String>) {
+    if env.is_empty() {
+        return;
+    }
+    output.push_str("    environment:\n");
+    for (key, value) in env {
+        let key = format_value(key, 6);
+        match secrets::reference(value) {
+            // Without a value compose takes the variable from its own environment
+            Some(name) => output.push_str(&format!(
+                "      {}: # holds secret `{}`, read from the environment\n",
+                key, name
+            )),
+            None => match secrets::template(value) {
+                // Compose fills in the `${VAR}` from its environment, where the secret is
+                Some(template) => output.push_str(&format!(
+                    "      {}: {}\n",
+                    key,
+                    format_double_quoted(template)
+                )),
+                None => output.push_str(&format!("      {}: {}\n", key, quote(value))),
+            },
+        }
+    }
+}
+
+/// Values are always quoted (compose would read `8080:80` or `true` as other
+/// types) and `$` is doubled so compose does not interpolate it.
+fn quote(value: &str) -> String {
+    format_double_quoted(&value.replace('$', "$$"))
+}
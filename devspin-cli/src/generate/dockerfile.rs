@@ -0,0 +1,118 @@
+use crate::parser::devspin_generator::DevSpinConfig;
+use crate::secrets;
+
+/// Directory the project is copied to inside the image
+pub const WORKDIR: &str = "/app";
+
+/// Package manager of a base image's distro
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Apt,
+    Apk,
+    Dnf,
+}
+
+impl PackageManager {
+    /// Guess the package manager from an image reference such as `node:20-alpine`.
+    /// Unknown images fall back to apt, as most official images are Debian based.
+    pub fn detect(image: &str) -> Self {
+        // Drop the registry/namespace, keep `name:tag`
+        let image = image.rsplit('/').next().unwrap_or(image).to_lowercase();
+        const DNF_DISTROS: &[&str] = &[
+            "fedora",
+            "centos",
+            "rockylinux",
+            "almalinux",
+            "rhel",
+            "ubi",
+            "amazonlinux",
+            "oraclelinux",
+        ];
+
+        if image.contains("alpine") {
+            PackageManager::Apk
+        } else if DNF_DISTROS.iter().any(|distro| image.starts_with(distro)) {
+            PackageManager::Dnf
+        } else {
+            PackageManager::Apt
+        }
+    }
+
+    /// `RUN` instruction installing `packages`
+    pub fn install(&self, packages: &[&str]) -> String {
+        let list: String = packages
+            .iter()
+            .map(|package| format!(" \\\n        {}", package))
+            .collect();
+        match self {
+            PackageManager::Apt => format!(
+                "RUN apt-get update \\\n    && DEBIAN_FRONTEND=noninteractive apt-get install -y --no-install-recommends{} \\\n    && rm -rf /var/lib/apt/lists/*\n",
+                list
+            ),
+            PackageManager::Apk => format!("RUN apk add --no-cache{}\n", list),
+            PackageManager::Dnf => {
+                format!("RUN dnf install -y{} \\\n    && dnf clean all\n", list)
+            }
+        }
+    }
+}
+
+/// Render the Dockerfile of `config`. The output only depends on the config,
+/// so it can be committed and snapshot-tested.
+pub fn render(config: &DevSpinConfig) -> String {
+    let mut output = format!(
+        "# Generated by devspin from devspin.yml ({}). Do not edit by hand.\nFROM {}\n",
+        config.header.name, config.header.base
+    );
+
+    let mut packages: Vec<&str> = Vec::new();
+    for package in &config.content.system {
+        if !packages.contains(&package.as_str()) {
+            packages.push(package);
+        }
+    }
+    if !packages.is_empty() {
+        output.push('\n');
+        output.push_str(&PackageManager::detect(&config.header.base).install(&packages));
+    }
+
+    if !config.content.env.is_empty() {
+        output.push('\n');
+        for (key, value) in &config.content.env {
+            // Anything in an ENV ends up in the image layers
+            match secrets::reference(value) {
+                Some(name) => output.push_str(&format!(
+                    "# {} holds secret `{}`, pass it when running the container\n",
+                    key, name
+                )),
+                None => match secrets::template(value) {
+                    Some(template) => output.push_str(&format!(
+                        "# {} is built from secrets ({}), pass it when running the container\n",
+                        key, template
+                    )),
+                    None => output.push_str(&format!("ENV {}={}\n", key, quote(value))),
+                },
+            }
+        }
+    }
+
+    output.push_str(&format!("\nWORKDIR {}\nCOPY . .\n", WORKDIR));
+    output
+}
+
+/// Double-quote an `ENV` value so spaces and `$` are kept literally
+fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,13 @@
+pub mod compose;
+pub mod dockerfile;
+
+use crate::prelude::*;
+use std::path::Path;
+
+/// Write generated `content` to `path`, replacing any previous version
+pub fn write_output(path: &Path, content: &str) -> Result<(), ProcessError> {
+    std::fs::write(path, content).map_err(|e| ProcessError::CreateFileFailed {
+        error_msg: format!("Failed to write {}: {}", path.display(), e),
+        exit_status: 400,
+    })
+}
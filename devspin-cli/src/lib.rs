@@ -4,6 +4,11 @@
 
 pub mod cli;
 pub mod error;
+pub mod generate;
 pub mod parser;
 pub mod prelude;
+pub mod runtime;
+pub mod secrets;
+pub mod spin_features;
+pub mod tasks;
 pub mod utils;
@@ -1,13 +1,14 @@
 //! DEVSPIN
 
 use clap::Parser;
+use colored::*;
 use devspin_cli::cli::Cli;
 
 #[tokio::main]
 async fn main() {
-    println!("Hello, Devspin!");
-
     let cli = Cli::parse();
-    #[allow(unused_must_use)]
-    cli.execute().await;
+    if let Err(e) = cli.execute().await {
+        eprintln!("{} {}", "error:".red().bold(), e);
+        std::process::exit(e.exit_code());
+    }
 }
@@ -1,5 +1,12 @@
 use crate::error::DevSpinError;
-use std::collections::HashMap;
+use crate::parser::document::Document;
+use crate::parser::yaml::{self, Entry, Node, Span, Value};
+use crate::parser::{format, interpolate, migrate, schema};
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
 
 /// Default configuration template used for initialization.
 pub const DEFAULT_DEVSPIN_CONFIG: &[&str] = &[
@@ -18,45 +25,512 @@ pub const DEFAULT_DEVSPIN_CONFIG: &[&str] = &[
     "    command: cargo build",
 ];
 
-#[derive(Debug, Clone, PartialEq)]
+// The doc comments of the config structs and their fields are the
+// descriptions of the JSON Schema exported by `devspin schema`. Serde reads
+// and writes the same layout as devspin.yml, in any of the `Format`s.
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct DevSpinConfigHeader {
+    /// Version of the devspin.yml format.
+    #[serde(deserialize_with = "format::scalar")]
+    #[schemars(schema_with = "schema::version")]
     pub version: String,
+    /// Project name.
     pub name: String,
+    /// Image the development container is built from, such as `rust:1`.
     pub base: String,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct SpinFeature {
+    #[serde(default)]
     pub enabled: bool,
+    /// Files the feature applies to: the current file, its directory or the whole project.
+    #[serde(default)]
+    #[schemars(schema_with = "schema::spin_feature_modes")]
     pub modes: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Service {
     pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub image: String,
+    /// Published ports written as `[ip:][host:]container[/protocol]`.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "format::scalars"
+    )]
+    #[schemars(schema_with = "schema::ports")]
     pub ports: Vec<String>,
-    pub environment: HashMap<String, String>,
+    #[serde(
+        default,
+        skip_serializing_if = "IndexMap::is_empty",
+        deserialize_with = "format::scalar_map"
+    )]
+    #[schemars(schema_with = "schema::scalar_map")]
+    pub environment: IndexMap<String, String>,
+    /// Files of `KEY=VALUE` lines added to `environment`, relative to the config file.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "format::one_or_many"
+    )]
+    #[schemars(schema_with = "schema::one_or_many")]
+    pub env_file: Vec<String>,
+    /// How to tell that the service is ready; `None` means "once running".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<Healthcheck>,
+    /// Services that must be ready before this one starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Mounts written as `name:/target`, `./host/path:/target[:ro]` or `/target`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<String>,
+    /// Arguments replacing the image's default command.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "format::command"
+    )]
+    #[schemars(schema_with = "schema::command")]
+    pub command: Vec<String>,
+    /// Replaces the image's entrypoint; the first word is the executable.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "format::command"
+    )]
+    #[schemars(schema_with = "schema::command")]
+    pub entrypoint: Vec<String>,
+    /// `no`, `always`, `unless-stopped`, `on-failure` or `on-failure:N`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "format::restart"
+    )]
+    #[schemars(schema_with = "schema::restart")]
+    pub restart: Option<String>,
+    /// Networks to join instead of the project's default network.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<String>,
+}
+
+impl Service {
+    /// Host port publishing `container_port`, from `ports` entries such as
+    /// `5433:5432` or `127.0.0.1:5433:5432`.
+    pub fn host_port(&self, container_port: u16) -> Option<u16> {
+        self.ports.iter().find_map(|spec| {
+            let (host, container) = split_port(spec)?;
+            (container == container_port).then_some(host?)
+        })
+    }
+
+    /// Container port of the first `ports` entry.
+    pub fn first_port(&self) -> Option<u16> {
+        self.ports.iter().find_map(|spec| Some(split_port(spec)?.1))
+    }
+}
+
+/// Splits a TCP port mapping into its host port (if fixed) and container port.
+fn split_port(spec: &str) -> Option<(Option<u16>, u16)> {
+    let spec = match spec.split_once('/') {
+        Some((spec, "tcp")) => spec,
+        Some(_) => return None,
+        None => spec,
+    };
+    let parts: Vec<&str> = spec.split(':').collect();
+    let container = parts.last()?.parse().ok()?;
+    let host = match parts.len() {
+        1 => None,
+        n => Some(parts[n - 2].parse().ok()?),
+    };
+    Some((host, container))
+}
+
+/// A parsed entry of `Service::ports`: `[ip:][host:]container[/protocol]`,
+/// where both ports may be ranges such as `8000-8010`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortMapping {
+    pub ip: Option<String>,
+    /// First and last host port, `None` when the runtime picks one.
+    pub host: Option<(u16, u16)>,
+    pub container: (u16, u16),
+    pub protocol: String,
+}
+
+impl PortMapping {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (mapping, protocol) = match spec.split_once('/') {
+            Some((mapping, protocol)) => (mapping, protocol),
+            None => (spec, "tcp"),
+        };
+        if !["tcp", "udp", "sctp"].contains(&protocol) {
+            return Err(format!(
+                "unknown protocol `{}` (expected tcp, udp or sctp)",
+                protocol
+            ));
+        }
+
+        // An IPv6 address is bracketed: `[::1]:8080:80`.
+        let (ip, ports) = match mapping.strip_prefix('[') {
+            Some(rest) => {
+                let (ip, ports) = rest
+                    .split_once("]:")
+                    .ok_or_else(|| format!("invalid port mapping `{}`", spec))?;
+                (Some(ip.to_string()), ports)
+            }
+            None => (None, mapping),
+        };
+        let parts: Vec<&str> = ports.split(':').collect();
+        let (ip, host, container) = match (ip, &parts[..]) {
+            (None, [container]) => (None, None, *container),
+            (None, [host, container]) => (None, Some(*host), *container),
+            (None, [ip, host, container]) => (Some(ip.to_string()), Some(*host), *container),
+            (Some(ip), [host, container]) => (Some(ip), Some(*host), *container),
+            _ => {
+                return Err(format!(
+                    "invalid port mapping `{}`, expected `[ip:][host:]container[/protocol]`",
+                    spec
+                ))
+            }
+        };
+
+        let container = port_range(container)?;
+        // `127.0.0.1::80` publishes on a random host port.
+        let host = match host {
+            None | Some("") => None,
+            Some(host) => Some(port_range(host)?),
+        };
+        if let Some(host) = host {
+            if host.1 - host.0 != container.1 - container.0 && host.0 != host.1 {
+                return Err(format!(
+                    "host ports `{}-{}` and container ports `{}-{}` differ in size",
+                    host.0, host.1, container.0, container.1
+                ));
+            }
+        }
+        Ok(PortMapping {
+            ip,
+            host,
+            container,
+            protocol: protocol.to_string(),
+        })
+    }
+}
+
+/// Parses `80` or `8000-8010` into its first and last port.
+fn port_range(text: &str) -> Result<(u16, u16), String> {
+    let port = |text: &str| match text.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("`{}` is not a port number (1-65535)", text)),
+    };
+    match text.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (port(first)?, port(last)?);
+            if first > last {
+                return Err(format!("port range `{}` is reversed", text));
+            }
+            Ok((first, last))
+        }
+        None => port(text).map(|port| (port, port)),
+    }
+}
+
+/// A parsed entry of `Service::volumes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VolumeMount {
+    /// Volume managed by the runtime, kept across restarts.
+    Named {
+        name: String,
+        target: String,
+        mode: Option<String>,
+    },
+    /// Host path, relative paths being relative to the project root.
+    Bind {
+        source: String,
+        target: String,
+        mode: Option<String>,
+    },
+    /// Volume created with the container and removed with it.
+    Anonymous { target: String },
+}
+
+impl VolumeMount {
+    /// Parses `source:target[:mode]` or `target`. Sources starting with `.`, `/`
+    /// or `~` are host paths, anything else names a volume.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let (source, target, mode) = match parts[..] {
+            [target] => {
+                return match target.starts_with('/') {
+                    true => Ok(VolumeMount::Anonymous {
+                        target: target.to_string(),
+                    }),
+                    false => Err(format!(
+                        "volume target `{}` must be an absolute path",
+                        target
+                    )),
+                }
+            }
+            [source, target] => (source, target, None),
+            [source, target, mode] => (source, target, Some(mode.to_string())),
+            _ => return Err("expected a volume as `source:target[:mode]`".to_string()),
+        };
+        if !target.starts_with('/') {
+            return Err(format!(
+                "volume target `{}` must be an absolute path",
+                target
+            ));
+        }
+        if let Some(mode) = &mode {
+            if !["ro", "rw", "z", "Z"].contains(&mode.as_str()) {
+                return Err(format!(
+                    "unknown volume mode `{}` (expected `ro` or `rw`)",
+                    mode
+                ));
+            }
+        }
+
+        let (target, source) = (target.to_string(), source.to_string());
+        if source.starts_with(['.', '/', '~']) {
+            return Ok(VolumeMount::Bind {
+                source,
+                target,
+                mode,
+            });
+        }
+        let valid_name = source.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && source
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+        if !valid_name {
+            return Err(format!(
+                "invalid volume name `{}`, host paths must start with `./` or `/`",
+                source
+            ));
+        }
+        Ok(VolumeMount::Named {
+            name: source,
+            target,
+            mode,
+        })
+    }
+}
+
+/// Restart policies accepted by docker and podman.
+pub const RESTART_POLICIES: &[&str] = &["no", "always", "unless-stopped", "on-failure"];
+
+/// Checks a `restart` value: one of [`RESTART_POLICIES`] or `on-failure:N`.
+pub fn check_restart_policy(policy: &str) -> Result<(), String> {
+    let (name, retries) = match policy.split_once(':') {
+        Some((name, retries)) => (name, Some(retries)),
+        None => (policy, None),
+    };
+    let valid = match retries {
+        None => RESTART_POLICIES.contains(&name),
+        Some(retries) => name == "on-failure" && retries.parse::<u32>().is_ok(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "unknown restart policy `{}` (expected one of {})",
+            policy,
+            RESTART_POLICIES.join(", ")
+        ))
+    }
 }
 
+/// Splits a command line into words, honouring single and double quotes.
+pub fn split_command(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                let escaped = chars.next().ok_or("unterminated escape")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// The check telling whether a service is ready.
 #[derive(Debug, Clone, PartialEq)]
+pub enum Probe {
+    /// Shell command run inside the container, healthy when it exits with 0.
+    Command(String),
+    /// Container port accepting TCP connections.
+    Tcp(u16),
+    /// HTTP `GET` of `path` answering 2xx or 3xx. `port` is a container port and
+    /// defaults to the service's first published port.
+    Http { path: String, port: Option<u16> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Healthcheck {
+    pub probe: Probe,
+    /// Delay between two attempts.
+    pub interval: Duration,
+    /// How long a single attempt may take.
+    pub timeout: Duration,
+    /// Attempts before the service is reported unhealthy.
+    pub retries: u32,
+}
+
+impl Healthcheck {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+    pub const DEFAULT_RETRIES: u32 = 30;
+
+    /// A healthcheck with the default interval, timeout and retries.
+    pub fn new(probe: Probe) -> Self {
+        Self {
+            probe,
+            interval: Self::DEFAULT_INTERVAL,
+            timeout: Self::DEFAULT_TIMEOUT,
+            retries: Self::DEFAULT_RETRIES,
+        }
+    }
+
+    /// The keys of the healthcheck block, omitting default values.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = match &self.probe {
+            Probe::Command(command) => vec![("command", command.clone())],
+            Probe::Tcp(port) => vec![("tcp", port.to_string())],
+            Probe::Http { path, port } => {
+                let mut fields = vec![("http", path.clone())];
+                if let Some(port) = port {
+                    fields.push(("port", port.to_string()));
+                }
+                fields
+            }
+        };
+        if self.interval != Self::DEFAULT_INTERVAL {
+            fields.push(("interval", format_duration(self.interval)));
+        }
+        if self.timeout != Self::DEFAULT_TIMEOUT {
+            fields.push(("timeout", format_duration(self.timeout)));
+        }
+        if self.retries != Self::DEFAULT_RETRIES {
+            fields.push(("retries", self.retries.to_string()));
+        }
+        fields
+    }
+}
+
+/// Parses durations written as `500ms`, `2s`, `1m` or `1h`; a bare number is in seconds.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(amount)),
+        "" | "s" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_secs(amount * 60)),
+        "h" => Some(Duration::from_secs(amount * 3600)),
+        _ => None,
+    }
+}
+
+/// Formats a duration in the largest unit of [`parse_duration`] that keeps it exact.
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if !millis.is_multiple_of(1000) {
+        return format!("{}ms", millis);
+    }
+    match millis / 1000 {
+        s if s > 0 && s.is_multiple_of(3600) => format!("{}h", s / 3600),
+        s if s > 0 && s.is_multiple_of(60) => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Task {
     pub name: String,
+    /// Shell command run from the project root.
     pub command: String,
+    /// Tasks that must succeed before this one starts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Globs of files the task reads; when they are unchanged the task is skipped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// Paths the task produces; a task is only skipped while they all exist.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct DevSpinConfigContent {
-    pub spin_features: HashMap<String, SpinFeature>,
+    /// Editor features such as `lint` and `format`.
+    #[serde(
+        rename = "spin-features",
+        default,
+        skip_serializing_if = "IndexMap::is_empty"
+    )]
+    pub spin_features: IndexMap<String, SpinFeature>,
+    /// System packages installed in the development container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub system: Vec<String>,
-    pub env: HashMap<String, String>,
+    /// Environment variables of tasks and of the development container.
+    #[serde(
+        default,
+        skip_serializing_if = "IndexMap::is_empty",
+        deserialize_with = "format::scalar_map"
+    )]
+    #[schemars(schema_with = "schema::scalar_map")]
+    pub env: IndexMap<String, String>,
+    /// Files of `KEY=VALUE` lines added to `env`, relative to the config file.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "format::one_or_many"
+    )]
+    #[schemars(schema_with = "schema::one_or_many")]
+    pub env_file: Vec<String>,
+    /// Containers started by `devspin up`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub services: Vec<Service>,
+    /// Commands run by `devspin run`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tasks: Vec<Task>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A whole config file. `Deserialize` is implemented in `format.rs`, as
+/// serde cannot deny unknown keys across flattened structs.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct DevSpinConfig {
+    #[serde(flatten)]
     pub header: DevSpinConfigHeader,
+    #[serde(flatten)]
     pub content: DevSpinConfigContent,
 }
 
@@ -82,7 +556,9 @@ impl DevSpinConfig {
     pub fn write(&self) -> String {
         let mut output = format!(
             "version: {}\nname: {}\nbase: {}\n\n",
-            self.header.version, self.header.name, self.header.base
+            yaml::format_value(&self.header.version, 0),
+            yaml::format_value(&self.header.name, 0),
+            yaml::format_value(&self.header.base, 0)
         );
 
         if !self.content.spin_features.is_empty() {
@@ -98,7 +574,7 @@ impl DevSpinConfig {
         if !self.content.system.is_empty() {
             output.push_str("system:\n");
             for sys in &self.content.system {
-                output.push_str(&format!("  - {}\n", sys));
+                output.push_str(&format!("  - {}\n", yaml::format_value(sys, 2)));
             }
             output.push('\n');
         }
@@ -106,16 +582,38 @@ impl DevSpinConfig {
         if !self.content.env.is_empty() {
             output.push_str("env:\n");
             for (key, val) in &self.content.env {
-                output.push_str(&format!("  {}: {}\n", key, val));
+                output.push_str(&format!(
+                    "  {}: {}\n",
+                    yaml::format_value(key, 2),
+                    yaml::format_value(val, 2)
+                ));
             }
             output.push('\n');
         }
 
+        if !self.content.env_file.is_empty() {
+            output.push_str(&format!(
+                "env_file: [{}]\n\n",
+                self.content
+                    .env_file
+                    .iter()
+                    .map(|path| yaml::format_flow_value(path))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
         if !self.content.services.is_empty() {
             output.push_str("services:\n");
             for service in &self.content.services {
-                output.push_str(&format!("  - name: {}\n", service.name));
-                output.push_str(&format!("    image: {}\n", service.image));
+                output.push_str(&format!(
+                    "  - name: {}\n",
+                    yaml::format_value(&service.name, 4)
+                ));
+                output.push_str(&format!(
+                    "    image: {}\n",
+                    yaml::format_value(&service.image, 4)
+                ));
                 if !service.ports.is_empty() {
                     output.push_str("    ports:\n");
                     for port in &service.ports {
@@ -125,7 +623,52 @@ impl DevSpinConfig {
                 if !service.environment.is_empty() {
                     output.push_str("    environment:\n");
                     for (k, v) in &service.environment {
-                        output.push_str(&format!("      {}: {}\n", k, v));
+                        output.push_str(&format!(
+                            "      {}: {}\n",
+                            yaml::format_value(k, 6),
+                            yaml::format_value(v, 6)
+                        ));
+                    }
+                }
+                if let Some(restart) = &service.restart {
+                    output.push_str(&format!(
+                        "    restart: {}\n",
+                        yaml::format_value(restart, 4)
+                    ));
+                }
+                for (key, values) in [
+                    ("env_file", &service.env_file),
+                    ("depends_on", &service.depends_on),
+                    ("command", &service.command),
+                    ("entrypoint", &service.entrypoint),
+                    ("networks", &service.networks),
+                ] {
+                    if !values.is_empty() {
+                        output.push_str(&format!(
+                            "    {}: [{}]\n",
+                            key,
+                            values
+                                .iter()
+                                .map(|value| yaml::format_flow_value(value))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                }
+                if !service.volumes.is_empty() {
+                    output.push_str("    volumes:\n");
+                    for volume in &service.volumes {
+                        output.push_str(&format!("      - {}\n", yaml::format_value(volume, 6)));
+                    }
+                }
+                if let Some(healthcheck) = &service.healthcheck {
+                    output.push_str("    healthcheck:\n");
+                    for (key, value) in healthcheck.fields() {
+                        output.push_str(&format!(
+                            "      {}: {}\n",
+                            key,
+                            yaml::format_value(&value, 6)
+                        ));
                     }
                 }
             }
@@ -135,10 +678,29 @@ impl DevSpinConfig {
         if !self.content.tasks.is_empty() {
             output.push_str("tasks:\n");
             for task in &self.content.tasks {
+                // Multi-line commands are written back as `|` block scalars.
                 output.push_str(&format!(
                     "  - name: {}\n    command: {}\n",
-                    task.name, task.command
+                    yaml::format_value(&task.name, 4),
+                    yaml::format_value(&task.command, 4)
                 ));
+                for (key, values) in [
+                    ("depends_on", &task.depends_on),
+                    ("inputs", &task.inputs),
+                    ("outputs", &task.outputs),
+                ] {
+                    if !values.is_empty() {
+                        output.push_str(&format!(
+                            "    {}: [{}]\n",
+                            key,
+                            values
+                                .iter()
+                                .map(|value| yaml::format_flow_value(value))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                }
             }
         }
 
@@ -148,214 +710,528 @@ impl DevSpinConfig {
 
 pub struct DevSpinGenerator;
 
+/// Keys accepted at the top level of devspin.yml.
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "version",
+    "name",
+    "base",
+    "spin-features",
+    "system",
+    "env",
+    "env_file",
+    "services",
+    "tasks",
+];
+const SPIN_FEATURE_KEYS: &[&str] = &["enabled", "modes"];
+const SERVICE_KEYS: &[&str] = &[
+    "name",
+    "image",
+    "ports",
+    "environment",
+    "env_file",
+    "healthcheck",
+    "depends_on",
+    "volumes",
+    "command",
+    "entrypoint",
+    "restart",
+    "networks",
+];
+const HEALTHCHECK_KEYS: &[&str] = &[
+    "command", "tcp", "http", "port", "interval", "timeout", "retries",
+];
+const TASK_KEYS: &[&str] = &["name", "command", "depends_on", "inputs", "outputs"];
+
 impl DevSpinGenerator {
     /// Generates the configuration object from an array of strings (template).
     pub fn generate(input: &[&str]) -> Result<DevSpinConfig, DevSpinError> {
-        let mut header_lines = Vec::new();
-        let mut content_lines = Vec::new();
-        let mut in_header = true;
-        let mut version_found = false;
-        let mut name_found = false;
-        let mut base_found = false;
-
-        for &line in input {
-            if in_header {
-                if line.trim().is_empty() && version_found && name_found && base_found {
-                    in_header = false;
-                    continue;
-                }
+        Self::parse_str(&input.join("\n"), "<input>")
+    }
 
-                if line.trim().starts_with("version:") {
-                    version_found = true;
-                    header_lines.push(line);
-                } else if line.trim().starts_with("name:") {
-                    name_found = true;
-                    header_lines.push(line);
-                } else if line.trim().starts_with("base:") {
-                    base_found = true;
-                    header_lines.push(line);
-                } else if !version_found && !name_found && !base_found && line.trim().is_empty() {
-                    // skip initial empty lines
-                } else if version_found && name_found && base_found {
-                    // If we have all fields and hit something else, switch to content
-                    in_header = false;
-                    content_lines.push(line);
-                } else {
-                    // Still in header potential area
-                    header_lines.push(line);
+    /// Parses the content of a devspin.yml. `path` is only used in error messages.
+    /// A config in an older version is migrated in memory first, so errors
+    /// in it point at the migrated text.
+    pub fn parse_str(source: &str, path: &str) -> Result<DevSpinConfig, DevSpinError> {
+        let root = yaml::parse(source, path)?;
+        let reader = ConfigReader {
+            path,
+            lines: source.lines().collect(),
+        };
+        if let Some(node) = root.get("version") {
+            let version = node.as_str().unwrap_or_default();
+            match migrate::support(version) {
+                migrate::Support::Newer | migrate::Support::Unknown => {
+                    return Err(reader.error(node.span, migrate::unsupported(version)));
                 }
-            } else {
-                content_lines.push(line);
+                migrate::Support::Outdated => {
+                    let mut document = Document::parse(source, path)?;
+                    migrate::migrate(&mut document)?;
+                    return Self::parse_str(&document.to_string(), path);
+                }
+                migrate::Support::Current => {}
             }
         }
+        reader.read_config(&root)
+    }
 
-        let header = Self::parse_header(&header_lines)?;
-        let content = Self::parse_content(&content_lines)?;
+    /// Reads and parses a config file from disk, in the format of its extension
+    /// (YAML when it has none).
+    pub fn parse_file(path: impl AsRef<Path>) -> Result<DevSpinConfig, DevSpinError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let source = std::fs::read_to_string(path).map_err(|source| DevSpinError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        let format = format::Format::from_path(path).unwrap_or(format::Format::Yaml);
+        format.parse(&source, &display)
+    }
+}
 
-        Ok(DevSpinConfig { header, content })
+/// Maps the YAML tree onto the config structs, reporting misplaced or
+/// mistyped keys at their location.
+struct ConfigReader<'a> {
+    path: &'a str,
+    lines: Vec<&'a str>,
+}
+
+impl ConfigReader<'_> {
+    fn error(&self, span: Span, message: impl Into<String>) -> DevSpinError {
+        let line = self.lines.get(span.line - 1).copied().unwrap_or_default();
+        DevSpinError::parse(self.path, line, span.line, span.column, message)
+    }
+
+    fn mapping<'n>(&self, node: &'n Node, what: &str) -> Result<&'n [Entry], DevSpinError> {
+        match &node.value {
+            Value::Null => Ok(&[]),
+            Value::Mapping(entries) => Ok(entries),
+            _ => Err(self.error(
+                node.span,
+                format!("expected `{}` to be a mapping, found {}", what, node.kind()),
+            )),
+        }
+    }
+
+    fn sequence<'n>(&self, node: &'n Node, what: &str) -> Result<&'n [Node], DevSpinError> {
+        match &node.value {
+            Value::Null => Ok(&[]),
+            Value::Sequence(items) => Ok(items),
+            _ => Err(self.error(
+                node.span,
+                format!(
+                    "expected `{}` to be a sequence, found {}",
+                    what,
+                    node.kind()
+                ),
+            )),
+        }
+    }
+
+    fn string(&self, node: &Node, what: &str) -> Result<String, DevSpinError> {
+        match &node.value {
+            Value::Null => Ok(String::new()),
+            Value::Scalar(text, _) => Ok(text.clone()),
+            _ => Err(self.error(
+                node.span,
+                format!("expected `{}` to be a scalar, found {}", what, node.kind()),
+            )),
+        }
+    }
+
+    fn strings(&self, node: &Node, what: &str) -> Result<Vec<String>, DevSpinError> {
+        self.sequence(node, what)?
+            .iter()
+            .map(|item| self.string(item, what))
+            .collect()
     }
 
-    fn parse_header(lines: &[&str]) -> Result<DevSpinConfigHeader, DevSpinError> {
-        let mut version = None;
-        let mut name = None;
-        let mut base = None;
+    fn string_map(
+        &self,
+        node: &Node,
+        what: &str,
+    ) -> Result<IndexMap<String, String>, DevSpinError> {
+        self.mapping(node, what)?
+            .iter()
+            .map(|entry| Ok((entry.key.clone(), self.string(&entry.value, &entry.key)?)))
+            .collect()
+    }
 
-        for line in lines {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
+    fn check_keys(
+        &self,
+        entries: &[Entry],
+        allowed: &[&str],
+        context: &str,
+    ) -> Result<(), DevSpinError> {
+        for entry in entries {
+            if allowed.contains(&entry.key.as_str()) {
                 continue;
             }
-
-            if let Some(v) = trimmed.strip_prefix("version:") {
-                version = Some(v.trim().to_string());
-            } else if let Some(n) = trimmed.strip_prefix("name:") {
-                name = Some(n.trim().to_string());
-            } else if let Some(b) = trimmed.strip_prefix("base:") {
-                base = Some(b.trim().to_string());
+            let mut message = format!("unknown key `{}` in {}", entry.key, context);
+            if let Some(suggestion) = closest_match(&entry.key, allowed) {
+                message.push_str(&format!(", did you mean `{}`?", suggestion));
             }
+            return Err(self.error(entry.key_span, message));
         }
+        Ok(())
+    }
 
-        Ok(DevSpinConfigHeader {
-            version: version.ok_or(DevSpinError::MissingField("version".to_string()))?,
-            name: name.ok_or(DevSpinError::MissingField("name".to_string()))?,
-            base: base.ok_or(DevSpinError::MissingField("base".to_string()))?,
+    fn required<'n>(
+        &self,
+        node: &'n Node,
+        key: &str,
+        context: &str,
+    ) -> Result<&'n Node, DevSpinError> {
+        node.get(key).ok_or_else(|| {
+            self.error(
+                node.span,
+                format!("{} is missing the `{}` key", context, key),
+            )
         })
     }
 
-    fn parse_content(lines: &[&str]) -> Result<DevSpinConfigContent, DevSpinError> {
-        let mut spin_features = HashMap::new();
-        let mut system = Vec::new();
-        let mut env = HashMap::new();
-        let mut services = Vec::new();
-        let mut tasks = Vec::new();
+    fn read_config(&self, root: &Node) -> Result<DevSpinConfig, DevSpinError> {
+        let entries = self.mapping(root, "the document")?;
+        self.check_keys(entries, TOP_LEVEL_KEYS, "the document")?;
 
-        let mut current_section = "";
+        let header_field = |key: &str| -> Result<String, DevSpinError> {
+            match root.get(key) {
+                Some(node) if !node.is_null() => self.string(node, key),
+                _ => Err(DevSpinError::MissingField(key.to_string())),
+            }
+        };
+        let header = DevSpinConfigHeader {
+            version: header_field("version")?,
+            name: header_field("name")?,
+            base: header_field("base")?,
+        };
 
-        // State for nested parsing
-        let mut current_feature_name = None;
-        let mut current_service: Option<Service> = None;
-        let mut current_task_name = None;
+        let mut content = DevSpinConfigContent {
+            spin_features: IndexMap::new(),
+            system: Vec::new(),
+            env: IndexMap::new(),
+            env_file: Vec::new(),
+            services: Vec::new(),
+            tasks: Vec::new(),
+        };
 
-        for line in lines {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
+        if let Some(node) = root.get("spin-features") {
+            for entry in self.mapping(node, "spin-features")? {
+                content
+                    .spin_features
+                    .insert(entry.key.clone(), self.read_spin_feature(entry)?);
             }
-
-            if trimmed == "spin-features:" {
-                current_section = "spin-features";
-                continue;
-            } else if trimmed == "system:" {
-                current_section = "system";
-                continue;
-            } else if trimmed == "env:" {
-                current_section = "env";
-                continue;
-            } else if trimmed == "services:" {
-                current_section = "services";
-                continue;
-            } else if trimmed == "tasks:" {
-                current_section = "tasks";
-                continue;
+        }
+        if let Some(node) = root.get("system") {
+            content.system = self.strings(node, "system")?;
+        }
+        if let Some(node) = root.get("env") {
+            content.env = self.string_map(node, "env")?;
+        }
+        if let Some(node) = root.get("env_file") {
+            content.env_file = self.one_or_many(node, "env_file")?;
+        }
+        if let Some(node) = root.get("services") {
+            for item in self.sequence(node, "services")? {
+                content.services.push(self.read_service(item)?);
+            }
+            let names: Vec<&str> = content.services.iter().map(|s| s.name.as_str()).collect();
+            self.check_dependencies(node, &names, "service")?;
+        }
+        if let Some(node) = root.get("tasks") {
+            for item in self.sequence(node, "tasks")? {
+                content.tasks.push(self.read_task(item)?);
             }
+            let names: Vec<&str> = content.tasks.iter().map(|t| t.name.as_str()).collect();
+            self.check_dependencies(node, &names, "task")?;
+        }
 
-            match current_section {
-                "spin-features" => {
-                    if trimmed.ends_with(':') {
-                        // New feature block
-                        let feature_name = trimmed.trim_end_matches(':');
-                        current_feature_name = Some(feature_name.to_string());
-                        spin_features.insert(
-                            feature_name.to_string(),
-                            SpinFeature {
-                                enabled: false,
-                                modes: vec![],
-                            },
-                        );
-                    } else if let Some(name) = &current_feature_name {
-                        if let Some(val) = trimmed.strip_prefix("enabled:") {
-                            if let Some(feature) = spin_features.get_mut(name) {
-                                feature.enabled = val.trim() == "true";
-                            }
-                        } else if let Some(val) = trimmed.strip_prefix("modes:") {
-                            if let Some(feature) = spin_features.get_mut(name) {
-                                let modes_str =
-                                    val.trim().trim_start_matches('[').trim_end_matches(']');
-                                feature.modes =
-                                    modes_str.split(',').map(|s| s.trim().to_string()).collect();
-                            }
-                        }
-                    }
-                }
-                "system" => {
-                    if let Some(sys) = trimmed.strip_prefix("- ") {
-                        system.push(sys.trim().to_string());
-                    }
+        Ok(DevSpinConfig { header, content })
+    }
+
+    fn read_spin_feature(&self, entry: &Entry) -> Result<SpinFeature, DevSpinError> {
+        let context = format!("spin feature `{}`", entry.key);
+        let entries = self.mapping(&entry.value, &entry.key)?;
+        self.check_keys(entries, SPIN_FEATURE_KEYS, &context)?;
+
+        let enabled = match entry.value.get("enabled") {
+            None => false,
+            Some(node) => match self.string(node, "enabled")?.as_str() {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(self.error(node.span, "expected `enabled` to be `true` or `false`"))
                 }
-                "env" => {
-                    if let Some((key, val)) = trimmed.split_once(':') {
-                        env.insert(key.trim().to_string(), val.trim().to_string());
-                    }
+            },
+        };
+        let modes = match entry.value.get("modes") {
+            Some(node) => self.strings(node, "modes")?,
+            None => Vec::new(),
+        };
+
+        Ok(SpinFeature { enabled, modes })
+    }
+
+    fn read_service(&self, node: &Node) -> Result<Service, DevSpinError> {
+        let entries = self.mapping(node, "services")?;
+        self.check_keys(entries, SERVICE_KEYS, "a service")?;
+
+        let mut service = Service {
+            name: self.string(self.required(node, "name", "service")?, "name")?,
+            image: match node.get("image") {
+                Some(image) => self.string(image, "image")?,
+                None => String::new(),
+            },
+            ports: match node.get("ports") {
+                Some(ports) => self.strings(ports, "ports")?,
+                None => Vec::new(),
+            },
+            environment: match node.get("environment") {
+                Some(environment) => self.string_map(environment, "environment")?,
+                None => IndexMap::new(),
+            },
+            env_file: match node.get("env_file") {
+                Some(env_file) => self.one_or_many(env_file, "env_file")?,
+                None => Vec::new(),
+            },
+            healthcheck: None,
+            depends_on: self.optional_strings(node, "depends_on")?,
+            volumes: self.optional_strings(node, "volumes")?,
+            command: self.command(node, "command")?,
+            entrypoint: self.command(node, "entrypoint")?,
+            restart: None,
+            networks: self.optional_strings(node, "networks")?,
+        };
+        if let Some(volumes) = node.get("volumes").and_then(Node::as_sequence) {
+            for (volume, spec) in volumes.iter().zip(&service.volumes) {
+                VolumeMount::parse(spec).map_err(|message| self.error(volume.span, message))?;
+            }
+        }
+        if let Some(restart) = node.get("restart") {
+            let policy = self.string(restart, "restart")?;
+            check_restart_policy(&policy).map_err(|message| self.error(restart.span, message))?;
+            service.restart = Some(policy);
+        }
+        // Read last: port probes are checked against the service's `ports`.
+        if let Some(healthcheck) = node.get("healthcheck") {
+            service.healthcheck = Some(self.read_healthcheck(healthcheck, &service)?);
+        }
+        Ok(service)
+    }
+
+    fn read_healthcheck(
+        &self,
+        node: &Node,
+        service: &Service,
+    ) -> Result<Healthcheck, DevSpinError> {
+        let entries = self.mapping(node, "healthcheck")?;
+        self.check_keys(entries, HEALTHCHECK_KEYS, "a healthcheck")?;
+
+        let probes: Vec<&Entry> = entries
+            .iter()
+            .filter(|entry| ["command", "tcp", "http"].contains(&entry.key.as_str()))
+            .collect();
+        let [probe] = probes[..] else {
+            let span = probes.get(1).map_or(node.span, |entry| entry.key_span);
+            return Err(self.error(
+                span,
+                "a healthcheck needs exactly one of `command`, `tcp` or `http`",
+            ));
+        };
+        if probe.key != "http" {
+            if let Some(entry) = entries.iter().find(|entry| entry.key == "port") {
+                return Err(
+                    self.error(entry.key_span, "`port` only applies to `http` healthchecks")
+                );
+            }
+        }
+
+        let probe = match probe.key.as_str() {
+            "command" => Probe::Command(self.string(&probe.value, "command")?),
+            "tcp" => {
+                let port = self.port(&probe.value, "tcp")?;
+                self.check_published(&probe.value, service, port)?;
+                Probe::Tcp(port)
+            }
+            _ => {
+                let path = self.string(&probe.value, "http")?;
+                if !path.starts_with('/') {
+                    return Err(self.error(
+                        probe.value.span,
+                        "expected `http` to be a path such as `/health`",
+                    ));
                 }
-                "services" => {
-                    if let Some(name) = trimmed.strip_prefix("- name:") {
-                        // Push previous service if exists
-                        if let Some(svc) = current_service.take() {
-                            services.push(svc);
-                        }
-                        current_service = Some(Service {
-                            name: name.trim().to_string(),
-                            image: String::new(),
-                            ports: Vec::new(),
-                            environment: HashMap::new(),
-                        });
-                    } else if let Some(svc) = &mut current_service {
-                        if let Some(img) = trimmed.strip_prefix("image:") {
-                            svc.image = img.trim().to_string();
-                        } else if trimmed == "ports:" {
-                            // Prepare for ports
-                        } else if trimmed.starts_with("- \"") && trimmed.contains(':') {
-                            // Port mapping like - "8080:8080"
-                            let port = trimmed.trim_start_matches("- \"").trim_end_matches('"');
-                            svc.ports.push(port.to_string());
-                        } else if trimmed == "environment:" {
-                            // Prepare for env
-                        } else if let Some((k, v)) = trimmed.split_once(':') {
-                            // Environment var for service
-                            // Note: This simple parser might confuse this with top-level keys if indentation isn't tracked.
-                            // For MVP, we assume correct structure.
-                            svc.environment
-                                .insert(k.trim().to_string(), v.trim().to_string());
-                        }
+                let port = match node.get("port") {
+                    Some(port_node) => {
+                        let port = self.port(port_node, "port")?;
+                        self.check_published(port_node, service, port)?;
+                        Some(port)
                     }
-                }
-                "tasks" => {
-                    if let Some(name) = trimmed.strip_prefix("- name:") {
-                        current_task_name = Some(name.trim().to_string());
-                    } else if let Some(cmd) = trimmed.strip_prefix("command:") {
-                        if let Some(name) = current_task_name.take() {
-                            tasks.push(Task {
-                                name,
-                                command: cmd.trim().to_string(),
-                            });
-                        }
+                    None => {
+                        let first = service.first_port().ok_or_else(|| {
+                            self.error(
+                                probe.key_span,
+                                "an `http` healthcheck needs a `port` or a published port in `ports`",
+                            )
+                        })?;
+                        self.check_published(&probe.value, service, first)?;
+                        None
                     }
-                }
-                _ => {}
+                };
+                Probe::Http { path, port }
+            }
+        };
+
+        let mut healthcheck = Healthcheck::new(probe);
+        for (key, target) in [
+            ("interval", &mut healthcheck.interval),
+            ("timeout", &mut healthcheck.timeout),
+        ] {
+            if let Some(value) = node.get(key) {
+                *target = parse_duration(&self.string(value, key)?).ok_or_else(|| {
+                    self.error(
+                        value.span,
+                        format!(
+                            "expected `{}` to be a duration such as `2s` or `500ms`",
+                            key
+                        ),
+                    )
+                })?;
             }
         }
+        if let Some(value) = node.get("retries") {
+            healthcheck.retries = self.string(value, "retries")?.parse().map_err(|_| {
+                self.error(value.span, "expected `retries` to be a positive number")
+            })?;
+        }
+        Ok(healthcheck)
+    }
+
+    fn port(&self, node: &Node, what: &str) -> Result<u16, DevSpinError> {
+        self.string(node, what)?.parse().map_err(|_| {
+            self.error(
+                node.span,
+                format!("expected `{}` to be a port number", what),
+            )
+        })
+    }
 
-        // Push last service
-        if let Some(svc) = current_service {
-            services.push(svc);
+    /// Probes run from the host, so the port must be published on a fixed host port.
+    fn check_published(
+        &self,
+        node: &Node,
+        service: &Service,
+        port: u16,
+    ) -> Result<(), DevSpinError> {
+        // Published ports are known once the references are expanded.
+        if service
+            .ports
+            .iter()
+            .any(|spec| interpolate::has_references(spec))
+        {
+            return Ok(());
         }
+        match service.host_port(port) {
+            Some(_) => Ok(()),
+            None => Err(self.error(
+                node.span,
+                format!(
+                    "port {} is not published on a host port, add `\"{}:{}\"` to `ports`",
+                    port, port, port
+                ),
+            )),
+        }
+    }
 
-        Ok(DevSpinConfigContent {
-            spin_features,
-            system,
-            env,
-            services,
-            tasks,
+    fn read_task(&self, node: &Node) -> Result<Task, DevSpinError> {
+        let entries = self.mapping(node, "tasks")?;
+        self.check_keys(entries, TASK_KEYS, "a task")?;
+
+        Ok(Task {
+            name: self.string(self.required(node, "name", "task")?, "name")?,
+            command: self.string(self.required(node, "command", "task")?, "command")?,
+            depends_on: self.optional_strings(node, "depends_on")?,
+            inputs: self.optional_strings(node, "inputs")?,
+            outputs: self.optional_strings(node, "outputs")?,
         })
     }
+
+    /// A command given as a list of words or as a single line split like a shell would.
+    fn command(&self, node: &Node, key: &str) -> Result<Vec<String>, DevSpinError> {
+        match node.get(key) {
+            Some(
+                value @ Node {
+                    value: Value::Scalar(..),
+                    ..
+                },
+            ) => split_command(&self.string(value, key)?).map_err(|message| {
+                self.error(value.span, format!("invalid `{}`: {}", key, message))
+            }),
+            Some(value) => self.strings(value, key),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// A single scalar or a sequence of them.
+    fn one_or_many(&self, node: &Node, what: &str) -> Result<Vec<String>, DevSpinError> {
+        match &node.value {
+            Value::Scalar(text, _) => Ok(vec![text.clone()]),
+            _ => self.strings(node, what),
+        }
+    }
+
+    fn optional_strings(&self, node: &Node, key: &str) -> Result<Vec<String>, DevSpinError> {
+        match node.get(key) {
+            Some(values) => self.strings(values, key),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Points at `depends_on` entries naming a task or service (`what`) that does not exist.
+    fn check_dependencies(
+        &self,
+        node: &Node,
+        names: &[&str],
+        what: &str,
+    ) -> Result<(), DevSpinError> {
+        for item in node.as_sequence().unwrap_or_default() {
+            let Some(deps) = item.get("depends_on").and_then(Node::as_sequence) else {
+                continue;
+            };
+            for dep in deps {
+                let name = dep.as_str().unwrap_or_default();
+                if !names.contains(&name) {
+                    let mut message = format!("unknown {} `{}` in depends_on", what, name);
+                    if let Some(suggestion) = closest_match(name, names) {
+                        message.push_str(&format!(", did you mean `{}`?", suggestion));
+                    }
+                    return Err(self.error(dep.span, message));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the candidate closest to `key` when it looks like a typo of it.
+pub(crate) fn closest_match<'c>(key: &str, candidates: &[&'c str]) -> Option<&'c str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 4))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
 }
@@ -0,0 +1,849 @@
+//! Lossless editing of devspin.yml.
+//!
+//! A [`Document`] keeps the original text next to the parsed tree. Edits
+//! rewrite only the lines of the nodes they touch, so comments, blank lines,
+//! quoting and key order elsewhere in the file survive untouched.
+
+use crate::error::DevSpinError;
+use crate::parser::devspin_generator::{
+    DevSpinConfig, DevSpinGenerator, Healthcheck, Service, SpinFeature, Task,
+};
+use crate::parser::yaml::{self, Node, ScalarStyle, Span, Value};
+use indexmap::IndexMap;
+use std::fmt;
+use std::path::Path;
+
+/// A value to write into a [`Document`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Scalar(String),
+    Sequence(Vec<Item>),
+    Mapping(Vec<(String, Item)>),
+}
+
+impl From<&str> for Item {
+    fn from(value: &str) -> Self {
+        Item::Scalar(value.to_string())
+    }
+}
+
+impl From<String> for Item {
+    fn from(value: String) -> Self {
+        Item::Scalar(value)
+    }
+}
+
+impl From<&[String]> for Item {
+    fn from(values: &[String]) -> Self {
+        Item::Sequence(
+            values
+                .iter()
+                .map(|value| Item::from(value.as_str()))
+                .collect(),
+        )
+    }
+}
+
+impl From<&IndexMap<String, String>> for Item {
+    fn from(values: &IndexMap<String, String>) -> Self {
+        Item::Mapping(
+            values
+                .iter()
+                .map(|(key, value)| (key.clone(), Item::from(value.as_str())))
+                .collect(),
+        )
+    }
+}
+
+impl From<&SpinFeature> for Item {
+    fn from(feature: &SpinFeature) -> Self {
+        Item::Mapping(vec![
+            (
+                "enabled".to_string(),
+                Item::from(feature.enabled.to_string()),
+            ),
+            ("modes".to_string(), Item::from(feature.modes.as_slice())),
+        ])
+    }
+}
+
+impl From<&Service> for Item {
+    fn from(service: &Service) -> Self {
+        let mut entries = vec![
+            ("name".to_string(), Item::from(service.name.as_str())),
+            ("image".to_string(), Item::from(service.image.as_str())),
+        ];
+        if !service.ports.is_empty() {
+            entries.push(("ports".to_string(), Item::from(service.ports.as_slice())));
+        }
+        if !service.environment.is_empty() {
+            entries.push(("environment".to_string(), Item::from(&service.environment)));
+        }
+        if let Some(restart) = &service.restart {
+            entries.push(("restart".to_string(), Item::from(restart.as_str())));
+        }
+        for (key, values) in service_lists(service) {
+            if !values.is_empty() {
+                entries.push((key.to_string(), Item::from(values.as_slice())));
+            }
+        }
+        if let Some(healthcheck) = &service.healthcheck {
+            entries.push(("healthcheck".to_string(), Item::from(healthcheck)));
+        }
+        Item::Mapping(entries)
+    }
+}
+
+/// List fields of a service, in the order they are written.
+fn service_lists(service: &Service) -> [(&'static str, &Vec<String>); 6] {
+    [
+        ("env_file", &service.env_file),
+        ("depends_on", &service.depends_on),
+        ("command", &service.command),
+        ("entrypoint", &service.entrypoint),
+        ("networks", &service.networks),
+        ("volumes", &service.volumes),
+    ]
+}
+
+impl From<&Healthcheck> for Item {
+    fn from(healthcheck: &Healthcheck) -> Self {
+        Item::Mapping(
+            healthcheck
+                .fields()
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), Item::from(value)))
+                .collect(),
+        )
+    }
+}
+
+impl From<&Task> for Item {
+    fn from(task: &Task) -> Self {
+        let mut entries = vec![
+            ("name".to_string(), Item::from(task.name.as_str())),
+            ("command".to_string(), Item::from(task.command.as_str())),
+        ];
+        for (key, values) in [
+            ("depends_on", &task.depends_on),
+            ("inputs", &task.inputs),
+            ("outputs", &task.outputs),
+        ] {
+            if !values.is_empty() {
+                entries.push((key.to_string(), Item::from(values.as_slice())));
+            }
+        }
+        Item::Mapping(entries)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses `services[0].environment.POSTGRES_DB` into path segments.
+fn parse_path(path: &str) -> Result<Vec<Segment>, DevSpinError> {
+    let invalid = || DevSpinError::InvalidEdit(format!("invalid path `{}`", path));
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut indices) = match part.find('[') {
+            Some(idx) => part.split_at(idx),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+        while let Some(rest) = indices.strip_prefix('[') {
+            let (index, tail) = rest.split_once(']').ok_or_else(invalid)?;
+            segments.push(Segment::Index(index.parse().map_err(|_| invalid())?));
+            indices = tail;
+        }
+        if !indices.is_empty() || (key.is_empty() && segments.is_empty()) {
+            return Err(invalid());
+        }
+    }
+    Ok(segments)
+}
+
+fn display_path(segments: &[Segment]) -> String {
+    let mut output = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => {
+                if !output.is_empty() {
+                    output.push('.');
+                }
+                output.push_str(key);
+            }
+            Segment::Index(index) => output.push_str(&format!("[{}]", index)),
+        }
+    }
+    output
+}
+
+/// How a resolved node is attached to its parent.
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Root,
+    /// Value of a mapping entry whose key sits at `key`.
+    Entry {
+        key: Span,
+        first_in_item: bool,
+    },
+    /// Item of a block sequence whose dash sits at `column` (1-based).
+    Item {
+        column: usize,
+    },
+    /// Item of a flow collection.
+    Flow,
+}
+
+/// A devspin.yml kept as text, with the tree of its current content.
+#[derive(Debug, Clone)]
+pub struct Document {
+    path: String,
+    lines: Vec<String>,
+    newline: &'static str,
+    trailing_newline: bool,
+    root: Node,
+}
+
+impl Document {
+    /// Parses `source`. `path` is only used in error messages.
+    pub fn parse(source: &str, path: &str) -> Result<Self, DevSpinError> {
+        let root = yaml::parse(source, path)?;
+        Ok(Self {
+            path: path.to_string(),
+            lines: source.lines().map(str::to_string).collect(),
+            newline: if source.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+            trailing_newline: source.ends_with('\n') || source.is_empty(),
+            root,
+        })
+    }
+
+    /// Reads and parses a devspin.yml from disk.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DevSpinError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let source = std::fs::read_to_string(path).map_err(|source| DevSpinError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        Self::parse(&source, &display)
+    }
+
+    /// Writes the document back to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DevSpinError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string()).map_err(|source| DevSpinError::Io {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// Path given to [`Document::parse`], used in error messages.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Reads the document into the config structs.
+    pub fn config(&self) -> Result<DevSpinConfig, DevSpinError> {
+        DevSpinGenerator::parse_str(&self.to_string(), &self.path)
+    }
+
+    /// Sets the value at `path` (`base`, `env.PORT`, `services[0].image`),
+    /// creating missing mapping keys along the way.
+    pub fn set(&mut self, path: &str, item: impl Into<Item>) -> Result<(), DevSpinError> {
+        self.set_at(&parse_path(path)?, item.into())
+    }
+
+    /// Appends `item` to the sequence at `path`, creating it when missing.
+    pub fn push(&mut self, path: &str, item: impl Into<Item>) -> Result<(), DevSpinError> {
+        self.push_at(&parse_path(path)?, item.into())
+    }
+
+    /// Removes the mapping entry or sequence item at `path`.
+    pub fn remove(&mut self, path: &str) -> Result<(), DevSpinError> {
+        self.remove_at(&parse_path(path)?)
+    }
+
+    /// Applies the differences between the document and `config`, leaving
+    /// every unchanged node as it was written.
+    pub fn update(&mut self, config: &DevSpinConfig) -> Result<(), DevSpinError> {
+        let current = self.config()?;
+
+        for (key, old, new) in [
+            ("version", &current.header.version, &config.header.version),
+            ("name", &current.header.name, &config.header.name),
+            ("base", &current.header.base, &config.header.base),
+        ] {
+            if old != new {
+                self.set_at(&[key_segment(key)], Item::from(new.as_str()))?;
+            }
+        }
+
+        let (old, new) = (&current.content, &config.content);
+        for (name, feature) in &new.spin_features {
+            let path = [key_segment("spin-features"), key_segment(name)];
+            match old.spin_features.get(name) {
+                None => self.set_at(&path, Item::from(feature))?,
+                Some(existing) => {
+                    if existing.enabled != feature.enabled {
+                        let item = Item::from(feature.enabled.to_string());
+                        self.set_at(&extend(&path, "enabled"), item)?;
+                    }
+                    if existing.modes != feature.modes {
+                        let item = Item::from(feature.modes.as_slice());
+                        self.set_at(&extend(&path, "modes"), item)?;
+                    }
+                }
+            }
+        }
+        for name in old.spin_features.keys() {
+            if !new.spin_features.contains_key(name) {
+                self.remove_at(&[key_segment("spin-features"), key_segment(name)])?;
+            }
+        }
+
+        if old.system != new.system {
+            let path = [key_segment("system")];
+            if !old.system.is_empty() && new.system.starts_with(&old.system) {
+                for package in &new.system[old.system.len()..] {
+                    self.push_at(&path, Item::from(package.as_str()))?;
+                }
+            } else if new.system.is_empty() {
+                self.remove_at(&path)?;
+            } else {
+                self.set_at(&path, Item::from(new.system.as_slice()))?;
+            }
+        }
+
+        self.update_map(&[key_segment("env")], &old.env, &new.env)?;
+        if old.env_file != new.env_file {
+            let path = [key_segment("env_file")];
+            if new.env_file.is_empty() {
+                self.remove_at(&path)?;
+            } else {
+                self.set_at(&path, Item::from(new.env_file.as_slice()))?;
+            }
+        }
+
+        let old_names: Vec<&str> = old.services.iter().map(|s| s.name.as_str()).collect();
+        for service in &new.services {
+            match old_names.iter().position(|name| *name == service.name) {
+                Some(index) => self.update_service(index, &old.services[index], service)?,
+                None => self.push_at(&[key_segment("services")], Item::from(service))?,
+            }
+        }
+        for (index, existing) in old.services.iter().enumerate().rev() {
+            if !new.services.iter().any(|s| s.name == existing.name) {
+                self.remove_at(&[key_segment("services"), Segment::Index(index)])?;
+            }
+        }
+
+        for task in &new.tasks {
+            match old.tasks.iter().position(|t| t.name == task.name) {
+                Some(index) => self.update_task(index, &old.tasks[index], task)?,
+                None => self.push_at(&[key_segment("tasks")], Item::from(task))?,
+            }
+        }
+        for (index, existing) in old.tasks.iter().enumerate().rev() {
+            if !new.tasks.iter().any(|t| t.name == existing.name) {
+                self.remove_at(&[key_segment("tasks"), Segment::Index(index)])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update_task(&mut self, index: usize, old: &Task, new: &Task) -> Result<(), DevSpinError> {
+        let path = [key_segment("tasks"), Segment::Index(index)];
+        if old.command != new.command {
+            self.set_at(&extend(&path, "command"), Item::from(new.command.as_str()))?;
+        }
+        for (key, old_values, new_values) in [
+            ("depends_on", &old.depends_on, &new.depends_on),
+            ("inputs", &old.inputs, &new.inputs),
+            ("outputs", &old.outputs, &new.outputs),
+        ] {
+            if old_values == new_values {
+                continue;
+            }
+            if new_values.is_empty() {
+                self.remove_at(&extend(&path, key))?;
+            } else {
+                self.set_at(&extend(&path, key), Item::from(new_values.as_slice()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn update_service(
+        &mut self,
+        index: usize,
+        old: &Service,
+        new: &Service,
+    ) -> Result<(), DevSpinError> {
+        let path = [key_segment("services"), Segment::Index(index)];
+        if old.image != new.image {
+            self.set_at(&extend(&path, "image"), Item::from(new.image.as_str()))?;
+        }
+        if old.ports != new.ports {
+            if new.ports.is_empty() {
+                self.remove_at(&extend(&path, "ports"))?;
+            } else {
+                self.set_at(&extend(&path, "ports"), Item::from(new.ports.as_slice()))?;
+            }
+        }
+        if old.restart != new.restart {
+            match &new.restart {
+                Some(restart) => {
+                    self.set_at(&extend(&path, "restart"), Item::from(restart.as_str()))?
+                }
+                None => self.remove_at(&extend(&path, "restart"))?,
+            }
+        }
+        for ((key, old_values), (_, new_values)) in
+            service_lists(old).into_iter().zip(service_lists(new))
+        {
+            if old_values == new_values {
+                continue;
+            }
+            if new_values.is_empty() {
+                self.remove_at(&extend(&path, key))?;
+            } else {
+                self.set_at(&extend(&path, key), Item::from(new_values.as_slice()))?;
+            }
+        }
+        if old.healthcheck != new.healthcheck {
+            match &new.healthcheck {
+                Some(healthcheck) => {
+                    self.set_at(&extend(&path, "healthcheck"), Item::from(healthcheck))?
+                }
+                None => self.remove_at(&extend(&path, "healthcheck"))?,
+            }
+        }
+        self.update_map(
+            &extend(&path, "environment"),
+            &old.environment,
+            &new.environment,
+        )
+    }
+
+    fn update_map(
+        &mut self,
+        path: &[Segment],
+        old: &IndexMap<String, String>,
+        new: &IndexMap<String, String>,
+    ) -> Result<(), DevSpinError> {
+        if old == new {
+            return Ok(());
+        }
+        if new.is_empty() {
+            return self.remove_at(path);
+        }
+        for (key, value) in new {
+            if old.get(key) != Some(value) {
+                self.set_at(&extend(path, key), Item::from(value.as_str()))?;
+            }
+        }
+        for key in old.keys() {
+            if !new.contains_key(key) {
+                self.remove_at(&extend(path, key))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_at(&mut self, segments: &[Segment], item: Item) -> Result<(), DevSpinError> {
+        let (depth, node, slot) = self.resolve(segments)?;
+        let (span, value, flow) = (node.span, node.value.clone(), node.flow);
+
+        if depth == segments.len() {
+            return self.replace(segments, span, &value, flow, slot, &item);
+        }
+
+        // Build the missing tail as nested mappings.
+        let mut built = item;
+        for segment in segments[depth + 1..].iter().rev() {
+            match segment {
+                Segment::Key(key) => built = Item::Mapping(vec![(key.clone(), built)]),
+                Segment::Index(_) => return Err(self.missing(segments, depth)),
+            }
+        }
+        let Segment::Key(key) = &segments[depth] else {
+            return Err(self.missing(segments, depth));
+        };
+
+        match value {
+            Value::Mapping(_) if flow => Err(self.unsupported(segments, "a flow mapping")),
+            Value::Mapping(_) => {
+                let lines = render_entry(key, &built, span.column - 1);
+                self.insert_lines(span.end_line, lines);
+                self.reparse()
+            }
+            Value::Null if matches!(slot, Slot::Root) => {
+                let lines = render_entry(key, &built, 0);
+                self.insert_lines(self.lines.len(), lines);
+                self.reparse()
+            }
+            Value::Null => {
+                let item = Item::Mapping(vec![(key.clone(), built)]);
+                self.replace(&segments[..depth], span, &value, flow, slot, &item)
+            }
+            _ => Err(DevSpinError::InvalidEdit(format!(
+                "`{}` is not a mapping",
+                display_path(&segments[..depth])
+            ))),
+        }
+    }
+
+    fn push_at(&mut self, segments: &[Segment], item: Item) -> Result<(), DevSpinError> {
+        let (depth, node, _) = self.resolve(segments)?;
+        if depth < segments.len() || node.is_null() {
+            return self.set_at(segments, Item::Sequence(vec![item]));
+        }
+
+        match &node.value {
+            Value::Sequence(items) if node.flow => {
+                let mut items = items.iter().map(node_to_item).collect::<Vec<_>>();
+                items.push(item);
+                let span = node.span;
+                self.splice(span, &render_flow(&Item::Sequence(items)));
+                self.reparse()
+            }
+            Value::Sequence(_) => {
+                let span = node.span;
+                let lines = render_item(&item, span.column - 1);
+                self.insert_lines(span.end_line, lines);
+                self.reparse()
+            }
+            _ => Err(DevSpinError::InvalidEdit(format!(
+                "`{}` is not a sequence",
+                display_path(segments)
+            ))),
+        }
+    }
+
+    fn remove_at(&mut self, segments: &[Segment]) -> Result<(), DevSpinError> {
+        let (depth, node, slot) = self.resolve(segments)?;
+        if depth < segments.len() {
+            return Ok(());
+        }
+        let span = node.span;
+
+        match slot {
+            Slot::Root => Err(self.unsupported(segments, "the document root")),
+            Slot::Flow => {
+                let (parent_depth, parent, _) = self.resolve(&segments[..segments.len() - 1])?;
+                debug_assert_eq!(parent_depth, segments.len() - 1);
+                let (Value::Sequence(items), Some(Segment::Index(index))) =
+                    (&parent.value, segments.last())
+                else {
+                    return Err(self.unsupported(segments, "a flow mapping"));
+                };
+                let mut items = items.iter().map(node_to_item).collect::<Vec<_>>();
+                items.remove(*index);
+                let parent_span = parent.span;
+                self.splice(parent_span, &render_flow(&Item::Sequence(items)));
+                self.reparse()
+            }
+            Slot::Entry { key, first_in_item } => {
+                let (start, end) = (key.line - 1, span.end_line.max(key.line));
+                let next_key_line = self.lines[end..]
+                    .iter()
+                    .position(|line| !is_blank(line))
+                    .map(|offset| end + offset)
+                    .filter(|&line| indent_of(&self.lines[line]) == key.column - 1);
+                if first_in_item {
+                    // The dash belongs to the first key: hand it over to the next key,
+                    // or leave an empty mapping behind.
+                    let dash = key.column - 3;
+                    match next_key_line {
+                        Some(next) => {
+                            self.lines[next].replace_range(dash..dash + 2, "- ");
+                        }
+                        None => {
+                            let pad = " ".repeat(dash);
+                            self.lines.insert(end, format!("{}- {{}}", pad));
+                        }
+                    }
+                }
+                self.lines.drain(start..end);
+                self.reparse()
+            }
+            Slot::Item { column } => {
+                let start = self.dash_line(span.line - 1, column - 1);
+                self.lines.drain(start..span.end_line);
+                self.reparse()
+            }
+        }
+    }
+
+    /// Replaces the node at `segments` (currently `value` at `span`) with `item`.
+    fn replace(
+        &mut self,
+        segments: &[Segment],
+        span: Span,
+        value: &Value,
+        flow: bool,
+        slot: Slot,
+        item: &Item,
+    ) -> Result<(), DevSpinError> {
+        let single_line_scalar = matches!(
+            value,
+            Value::Scalar(
+                _,
+                ScalarStyle::Plain | ScalarStyle::SingleQuoted | ScalarStyle::DoubleQuoted
+            )
+        );
+
+        match (slot, item) {
+            (Slot::Flow, Item::Scalar(text)) => {
+                self.splice(span, &yaml::format_flow_value(text));
+            }
+            (Slot::Flow, _) => return Err(self.unsupported(segments, "a flow collection")),
+            (_, Item::Sequence(items))
+                if flow && items.iter().all(|i| matches!(i, Item::Scalar(_))) =>
+            {
+                // Keep `[a, b]` lists in flow style.
+                self.splice(span, &render_flow(item));
+            }
+            (Slot::Entry { .. } | Slot::Item { .. }, Item::Scalar(text))
+                if single_line_scalar && !yaml::format_value(text, 0).contains('\n') =>
+            {
+                self.splice(span, &yaml::format_value(text, 0));
+            }
+            (Slot::Entry { key, .. }, _) => {
+                let Some(Segment::Key(name)) = segments.last() else {
+                    unreachable!("entries are addressed by key");
+                };
+                let start = key.line - 1;
+                let end = span.end_line.max(key.line);
+                let prefix = self.lines[start][..key.column - 1].to_string();
+                let mut lines = render_entry(name, item, key.column - 1);
+                lines[0].replace_range(..key.column - 1, &prefix);
+                self.lines.splice(start..end, lines);
+            }
+            (Slot::Item { column }, _) => {
+                let start = self.dash_line(span.line - 1, column - 1);
+                let end = span.end_line.max(start + 1);
+                let prefix = self.lines[start][..column - 1].to_string();
+                let mut lines = render_item(item, column - 1);
+                lines[0].replace_range(..column - 1, &prefix);
+                self.lines.splice(start..end, lines);
+            }
+            (Slot::Root, _) => return Err(self.unsupported(segments, "the document root")),
+        }
+        self.reparse()
+    }
+
+    /// Walks `segments` as far as the tree goes. Returns how many segments
+    /// matched, the deepest node reached and how it hangs off its parent.
+    fn resolve(&self, segments: &[Segment]) -> Result<(usize, &Node, Slot), DevSpinError> {
+        let mut node = &self.root;
+        let mut slot = Slot::Root;
+        for (depth, segment) in segments.iter().enumerate() {
+            match (segment, &node.value) {
+                (Segment::Key(key), Value::Mapping(entries)) => {
+                    let Some((index, entry)) =
+                        entries.iter().enumerate().find(|(_, e)| &e.key == key)
+                    else {
+                        return Ok((depth, node, slot));
+                    };
+                    slot = if node.flow {
+                        Slot::Flow
+                    } else {
+                        Slot::Entry {
+                            key: entry.key_span,
+                            first_in_item: index == 0
+                                && matches!(slot, Slot::Item { column } if column + 2 == entry.key_span.column),
+                        }
+                    };
+                    node = &entry.value;
+                }
+                (Segment::Index(index), Value::Sequence(items)) => {
+                    let Some(item) = items.get(*index) else {
+                        return Err(self.missing(segments, depth));
+                    };
+                    slot = if node.flow {
+                        Slot::Flow
+                    } else {
+                        Slot::Item {
+                            column: node.span.column,
+                        }
+                    };
+                    node = item;
+                }
+                _ => return Ok((depth, node, slot)),
+            }
+        }
+        Ok((segments.len(), node, slot))
+    }
+
+    /// Finds the line holding the dash of the sequence item starting on `line`.
+    fn dash_line(&self, line: usize, column: usize) -> usize {
+        (0..=line)
+            .rev()
+            .find(|&l| self.lines[l].as_bytes().get(column) == Some(&b'-'))
+            .unwrap_or(line)
+    }
+
+    fn splice(&mut self, span: Span, text: &str) {
+        let (start, end) = (span.line - 1, span.end_line - 1);
+        let head = self.lines[start][..span.column - 1].to_string();
+        let tail = self.lines[end][span.end_column - 1..].to_string();
+        let replaced: Vec<String> = format!("{}{}{}", head, text, tail)
+            .split('\n')
+            .map(str::to_string)
+            .collect();
+        self.lines.splice(start..=end, replaced);
+    }
+
+    fn insert_lines(&mut self, at: usize, lines: Vec<String>) {
+        self.lines.splice(at..at, lines);
+    }
+
+    fn reparse(&mut self) -> Result<(), DevSpinError> {
+        self.root = yaml::parse(&self.to_string(), &self.path)?;
+        Ok(())
+    }
+
+    fn missing(&self, segments: &[Segment], depth: usize) -> DevSpinError {
+        DevSpinError::InvalidEdit(format!(
+            "`{}` does not exist",
+            display_path(&segments[..=depth])
+        ))
+    }
+
+    fn unsupported(&self, segments: &[Segment], what: &str) -> DevSpinError {
+        DevSpinError::InvalidEdit(format!(
+            "cannot edit `{}` inside {}",
+            display_path(segments),
+            what
+        ))
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lines.join(self.newline))?;
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str(self.newline)?;
+        }
+        Ok(())
+    }
+}
+
+fn key_segment(key: &str) -> Segment {
+    Segment::Key(key.to_string())
+}
+
+fn extend(path: &[Segment], key: &str) -> Vec<Segment> {
+    let mut extended = path.to_vec();
+    extended.push(key_segment(key));
+    extended
+}
+
+fn is_blank(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn node_to_item(node: &Node) -> Item {
+    match &node.value {
+        Value::Null => Item::Scalar(String::new()),
+        Value::Scalar(text, _) => Item::Scalar(text.clone()),
+        Value::Sequence(items) => Item::Sequence(items.iter().map(node_to_item).collect()),
+        Value::Mapping(entries) => Item::Mapping(
+            entries
+                .iter()
+                .map(|entry| (entry.key.clone(), node_to_item(&entry.value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Renders `key: item` in block style at `indent`.
+fn render_entry(key: &str, item: &Item, indent: usize) -> Vec<String> {
+    let pad = " ".repeat(indent);
+    let key = yaml::format_value(key, indent);
+    match item {
+        Item::Scalar(text) => format!("{}{}: {}", pad, key, yaml::format_value(text, indent))
+            .split('\n')
+            .map(str::to_string)
+            .collect(),
+        Item::Sequence(items) if items.is_empty() => vec![format!("{}{}: []", pad, key)],
+        Item::Mapping(entries) if entries.is_empty() => vec![format!("{}{}: {{}}", pad, key)],
+        Item::Sequence(items) => std::iter::once(format!("{}{}:", pad, key))
+            .chain(items.iter().flat_map(|item| render_item(item, indent + 2)))
+            .collect(),
+        Item::Mapping(entries) => std::iter::once(format!("{}{}:", pad, key))
+            .chain(
+                entries
+                    .iter()
+                    .flat_map(|(key, item)| render_entry(key, item, indent + 2)),
+            )
+            .collect(),
+    }
+}
+
+/// Renders `- item` in block style with the dash at `indent`.
+fn render_item(item: &Item, indent: usize) -> Vec<String> {
+    let pad = " ".repeat(indent);
+    match item {
+        Item::Scalar(text) => format!("{}- {}", pad, yaml::format_value(text, indent))
+            .split('\n')
+            .map(str::to_string)
+            .collect(),
+        Item::Mapping(entries) if !entries.is_empty() => {
+            let mut lines: Vec<String> = entries
+                .iter()
+                .flat_map(|(key, item)| render_entry(key, item, indent + 2))
+                .collect();
+            lines[0].replace_range(indent..indent + 2, "- ");
+            lines
+        }
+        Item::Sequence(items) if !items.is_empty() => std::iter::once(format!("{}-", pad))
+            .chain(items.iter().flat_map(|item| render_item(item, indent + 2)))
+            .collect(),
+        _ => vec![format!("{}- {}", pad, render_flow(item))],
+    }
+}
+
+/// Renders `item` in flow style (`[a, b]`, `{k: v}`).
+fn render_flow(item: &Item) -> String {
+    match item {
+        Item::Scalar(text) => yaml::format_flow_value(text),
+        Item::Sequence(items) => format!(
+            "[{}]",
+            items.iter().map(render_flow).collect::<Vec<_>>().join(", ")
+        ),
+        Item::Mapping(entries) => format!(
+            "{{{}}}",
+            entries
+                .iter()
+                .map(|(key, item)| format!(
+                    "{}: {}",
+                    yaml::format_flow_value(key),
+                    render_flow(item)
+                ))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
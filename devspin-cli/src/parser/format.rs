@@ -0,0 +1,463 @@
+use crate::error::DevSpinError;
+use crate::parser::devspin_generator::{
+    check_restart_policy, closest_match, format_duration, parse_duration, split_command,
+    DevSpinConfig, DevSpinConfigContent, DevSpinConfigHeader, DevSpinGenerator, Healthcheck, Probe,
+    Service, SpinFeature, Task, VolumeMount,
+};
+use crate::parser::interpolate;
+use crate::parser::migrate::{self, Support};
+use indexmap::IndexMap;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// File formats a config can be read from and written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// The format of `path` from its extension; `None` when it is not a config extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "yml" | "yaml" => Some(Format::Yaml),
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Yaml => "yml",
+            Format::Json => "json",
+            Format::Toml => "toml",
+        }
+    }
+
+    /// Conventional name of a config file in this format, such as `devspin.json`.
+    pub fn file_name(&self) -> String {
+        format!("devspin.{}", self.extension())
+    }
+
+    /// Parses a config written in this format. `path` is only used in error messages.
+    ///
+    /// YAML goes through [`DevSpinGenerator::parse_str`]. JSON and TOML are read
+    /// with serde, then get the checks serde cannot express: volume syntax,
+    /// `depends_on` names and published healthcheck ports.
+    pub fn parse(&self, source: &str, path: &str) -> Result<DevSpinConfig, DevSpinError> {
+        let config: DevSpinConfig = match self {
+            Format::Yaml => return DevSpinGenerator::parse_str(source, path),
+            Format::Json => serde_json::from_str(source).map_err(|e| {
+                let message = e.to_string();
+                // serde_json appends the location, which the snippet already shows
+                let message = match message.rsplit_once(" at line ") {
+                    Some((message, _)) => message.to_string(),
+                    None => message,
+                };
+                located_error(source, path, e.line(), e.column(), message)
+            })?,
+            Format::Toml => toml::from_str(source).map_err(|e| {
+                let (line, column) = match e.span() {
+                    Some(span) => {
+                        let before = &source[..span.start.min(source.len())];
+                        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                        (
+                            before.matches('\n').count() + 1,
+                            before.len() - line_start + 1,
+                        )
+                    }
+                    None => (0, 0),
+                };
+                located_error(source, path, line, column, e.message().trim())
+            })?,
+        };
+        check(&config).map_err(|message| DevSpinError::Invalid {
+            path: path.to_string(),
+            message,
+        })?;
+        Ok(config)
+    }
+
+    /// Writes `config` in this format.
+    pub fn write(&self, config: &DevSpinConfig) -> String {
+        match self {
+            Format::Yaml => config.write(),
+            Format::Json => {
+                serde_json::to_string_pretty(config).expect("a config always serializes to JSON")
+                    + "\n"
+            }
+            Format::Toml => {
+                toml::to_string_pretty(config).expect("a config always serializes to TOML")
+            }
+        }
+    }
+}
+
+/// A parse error pointing at `line` and `column` (1-based), or at no location
+/// when `line` is 0, as serde reports errors found once a value is complete.
+fn located_error(
+    source: &str,
+    path: &str,
+    line: usize,
+    column: usize,
+    message: impl Into<String>,
+) -> DevSpinError {
+    // `split` rather than `lines`: errors at the end of input point past the last line break
+    match source.split('\n').nth(line.wrapping_sub(1)) {
+        Some(text) if line > 0 => {
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            DevSpinError::parse(path, text, line, column.max(1), message)
+        }
+        _ => DevSpinError::Invalid {
+            path: path.to_string(),
+            message: message.into(),
+        },
+    }
+}
+
+/// The checks of the YAML reader that span several fields.
+fn check(config: &DevSpinConfig) -> Result<(), String> {
+    let version = &config.header.version;
+    match migrate::support(version) {
+        Support::Newer | Support::Unknown => return Err(migrate::unsupported(version)),
+        // Migrations only rewrite YAML documents.
+        Support::Outdated => {
+            return Err(format!(
+                "version `{}` is outdated, only YAML configs can be migrated to {}",
+                version,
+                migrate::CURRENT_VERSION
+            ))
+        }
+        Support::Current => {}
+    }
+
+    let services = &config.content.services;
+    let service_names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+    let task_names: Vec<&str> = config
+        .content
+        .tasks
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect();
+
+    for service in services {
+        for volume in &service.volumes {
+            VolumeMount::parse(volume)
+                .map_err(|message| format!("service `{}`: {}", service.name, message))?;
+        }
+        check_dependencies(&service.depends_on, &service_names, "service")?;
+        let port = match service.healthcheck.as_ref().map(|h| &h.probe) {
+            Some(Probe::Tcp(port)) | Some(Probe::Http {
+                port: Some(port), ..
+            }) => Some(*port),
+            Some(Probe::Http { port: None, .. }) => match service.first_port() {
+                Some(port) => Some(port),
+                None => {
+                    return Err(format!(
+                        "service `{}`: an `http` healthcheck needs a `port` or a published port in `ports`",
+                        service.name
+                    ))
+                }
+            },
+            _ => None,
+        };
+        // Published ports are known once the references are expanded.
+        let interpolated = service
+            .ports
+            .iter()
+            .any(|spec| interpolate::has_references(spec));
+        if let Some(port) = port.filter(|port| !interpolated && service.host_port(*port).is_none())
+        {
+            return Err(format!(
+                "service `{}`: port {} is not published on a host port, add `\"{}:{}\"` to `ports`",
+                service.name, port, port, port
+            ));
+        }
+    }
+    for task in &config.content.tasks {
+        check_dependencies(&task.depends_on, &task_names, "task")?;
+    }
+    Ok(())
+}
+
+fn check_dependencies(depends_on: &[String], names: &[&str], what: &str) -> Result<(), String> {
+    for name in depends_on {
+        if !names.contains(&name.as_str()) {
+            let mut message = format!("unknown {} `{}` in depends_on", what, name);
+            if let Some(suggestion) = closest_match(name, names) {
+                message.push_str(&format!(", did you mean `{}`?", suggestion));
+            }
+            return Err(message);
+        }
+    }
+    Ok(())
+}
+
+impl<'de> Deserialize<'de> for DevSpinConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // The header and content flattened by hand: serde buffers flattened
+        // fields, which loses the location of errors and cannot deny unknown keys
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Document {
+            #[serde(deserialize_with = "scalar")]
+            version: String,
+            name: String,
+            base: String,
+            #[serde(rename = "spin-features", default)]
+            spin_features: IndexMap<String, SpinFeature>,
+            #[serde(default)]
+            system: Vec<String>,
+            #[serde(default, deserialize_with = "scalar_map")]
+            env: IndexMap<String, String>,
+            #[serde(default, deserialize_with = "one_or_many")]
+            env_file: Vec<String>,
+            #[serde(default)]
+            services: Vec<Service>,
+            #[serde(default)]
+            tasks: Vec<Task>,
+        }
+
+        let document = Document::deserialize(deserializer)?;
+        Ok(DevSpinConfig {
+            header: DevSpinConfigHeader {
+                version: document.version,
+                name: document.name,
+                base: document.base,
+            },
+            content: DevSpinConfigContent {
+                spin_features: document.spin_features,
+                system: document.system,
+                env: document.env,
+                env_file: document.env_file,
+                services: document.services,
+                tasks: document.tasks,
+            },
+        })
+    }
+}
+
+impl Serialize for Healthcheck {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        match &self.probe {
+            Probe::Command(command) => map.serialize_entry("command", command)?,
+            Probe::Tcp(port) => map.serialize_entry("tcp", port)?,
+            Probe::Http { path, port } => {
+                map.serialize_entry("http", path)?;
+                if let Some(port) = port {
+                    map.serialize_entry("port", port)?;
+                }
+            }
+        }
+        if self.interval != Self::DEFAULT_INTERVAL {
+            map.serialize_entry("interval", &format_duration(self.interval))?;
+        }
+        if self.timeout != Self::DEFAULT_TIMEOUT {
+            map.serialize_entry("timeout", &format_duration(self.timeout))?;
+        }
+        if self.retries != Self::DEFAULT_RETRIES {
+            map.serialize_entry("retries", &self.retries)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Healthcheck {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Fields {
+            command: Option<String>,
+            tcp: Option<u16>,
+            http: Option<String>,
+            port: Option<u16>,
+            interval: Option<Scalar>,
+            timeout: Option<Scalar>,
+            retries: Option<u32>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let probe = match (fields.command, fields.tcp, fields.http) {
+            (Some(command), None, None) => Probe::Command(command),
+            (None, Some(port), None) => Probe::Tcp(port),
+            (None, None, Some(path)) => {
+                if !path.starts_with('/') {
+                    return Err(de::Error::custom(
+                        "expected `http` to be a path such as `/health`",
+                    ));
+                }
+                Probe::Http {
+                    path,
+                    port: fields.port,
+                }
+            }
+            _ => {
+                return Err(de::Error::custom(
+                    "a healthcheck needs exactly one of `command`, `tcp` or `http`",
+                ))
+            }
+        };
+        if fields.port.is_some() && !matches!(probe, Probe::Http { .. }) {
+            return Err(de::Error::custom(
+                "`port` only applies to `http` healthchecks",
+            ));
+        }
+
+        let mut healthcheck = Healthcheck::new(probe);
+        for (key, value, target) in [
+            ("interval", fields.interval, &mut healthcheck.interval),
+            ("timeout", fields.timeout, &mut healthcheck.timeout),
+        ] {
+            if let Some(Scalar(text)) = value {
+                *target = parse_duration(&text).ok_or_else(|| {
+                    de::Error::custom(format!(
+                        "expected `{}` to be a duration such as `2s` or `500ms`",
+                        key
+                    ))
+                })?;
+            }
+        }
+        if let Some(retries) = fields.retries {
+            healthcheck.retries = retries;
+        }
+        Ok(healthcheck)
+    }
+}
+
+// Deserializers referenced by `deserialize_with` in devspin_generator.rs, so
+// that JSON and TOML accept what the YAML reader accepts.
+
+/// A string, number or boolean, read as its text like an unquoted YAML scalar.
+struct Scalar(String);
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScalarVisitor;
+
+        impl Visitor<'_> for ScalarVisitor {
+            type Value = Scalar;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string, number or boolean")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Scalar, E> {
+                Ok(Scalar(value.to_string()))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Scalar, E> {
+                // Keep `1.0` as written rather than `1`
+                Ok(Scalar(if value.fract() == 0.0 {
+                    format!("{:.1}", value)
+                } else {
+                    value.to_string()
+                }))
+            }
+        }
+
+        deserializer.deserialize_any(ScalarVisitor)
+    }
+}
+
+pub(crate) fn scalar<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Scalar::deserialize(deserializer).map(|Scalar(text)| text)
+}
+
+pub(crate) fn scalars<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    let scalars = Vec::<Scalar>::deserialize(deserializer)?;
+    Ok(scalars.into_iter().map(|Scalar(text)| text).collect())
+}
+
+pub(crate) fn scalar_map<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<IndexMap<String, String>, D::Error> {
+    let map = IndexMap::<String, Scalar>::deserialize(deserializer)?;
+    Ok(map
+        .into_iter()
+        .map(|(key, Scalar(text))| (key, text))
+        .collect())
+}
+
+/// A single string or a list of them.
+pub(crate) fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error> {
+    struct OneOrManyVisitor;
+
+    impl<'de> Visitor<'de> for OneOrManyVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a string or a list of strings")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Vec<String>, E> {
+            Ok(vec![value.to_string()])
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<String>, A::Error> {
+            let mut values = Vec::new();
+            while let Some(value) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(values)
+        }
+    }
+
+    deserializer.deserialize_any(OneOrManyVisitor)
+}
+
+/// A command given as a list of words or as a single line split like a shell would.
+pub(crate) fn command<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    struct CommandVisitor;
+
+    impl<'de> Visitor<'de> for CommandVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a command line or a list of words")
+        }
+
+        fn visit_str<E: de::Error>(self, line: &str) -> Result<Vec<String>, E> {
+            split_command(line)
+                .map_err(|message| E::custom(format!("invalid command: {}", message)))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<String>, A::Error> {
+            let mut words = Vec::new();
+            while let Some(word) = seq.next_element()? {
+                words.push(word);
+            }
+            Ok(words)
+        }
+    }
+
+    deserializer.deserialize_any(CommandVisitor)
+}
+
+pub(crate) fn restart<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let policy = String::deserialize(deserializer)?;
+    check_restart_policy(&policy).map_err(de::Error::custom)?;
+    Ok(Some(policy))
+}
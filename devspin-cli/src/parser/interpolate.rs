@@ -0,0 +1,289 @@
+//! `${VAR}` expansion in config values.
+//!
+//! `env`, service `environment`, `image`, `ports` and task commands may refer
+//! to variables as `${VAR}`, `${VAR:-default}` (used when `VAR` is unset or
+//! empty) or `${VAR:?message}` (an error when `VAR` is unset or empty). `$${`
+//! is a literal `${`. A reference is looked up in the [`Layer`]s, highest
+//! priority first, then in the config's own `env`, whose values may refer to
+//! each other as long as they do not form a cycle.
+//!
+//! The same order decides the value of each `env` entry: a layer defining
+//! the variable overrides the config.
+//!
+//! Task commands run in a shell, which understands the same syntax: a
+//! reference devspin cannot resolve, or any other `${...}` form, is left for
+//! the shell to expand.
+
+use crate::error::DevSpinError;
+use crate::parser::devspin_generator::DevSpinConfig;
+use indexmap::IndexMap;
+
+/// Variables from one source, such as the process environment or a `.env` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layer {
+    /// Where the variables come from, for messages.
+    pub name: String,
+    pub vars: IndexMap<String, String>,
+    /// Whether the layer only overrides the variables of the project rather
+    /// than adding its own to `env`, as the process environment does.
+    pub overrides_only: bool,
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>, vars: IndexMap<String, String>) -> Self {
+        Self {
+            name: name.into(),
+            vars,
+            overrides_only: false,
+        }
+    }
+
+    /// The variables of the current process.
+    pub fn process() -> Self {
+        Self {
+            overrides_only: true,
+            ..Self::new("process environment", std::env::vars().collect())
+        }
+    }
+}
+
+/// Whether `text` contains a `${` that expansion may replace.
+pub fn has_references(text: &str) -> bool {
+    text.contains("${")
+}
+
+/// Returns `config` with every reference expanded and `env` holding the
+/// variables of the project: its own entries and those of the `layers` that
+/// add variables, each with the value of the first layer defining it.
+/// `layers` come first, highest priority first; `path` is only used in error
+/// messages.
+pub fn resolve(
+    config: &DevSpinConfig,
+    layers: &[Layer],
+    path: &str,
+) -> Result<DevSpinConfig, DevSpinError> {
+    let mut resolver = Resolver {
+        layers,
+        env: &config.content.env,
+        resolved: IndexMap::new(),
+        stack: Vec::new(),
+    };
+    let fail = |context: String, failure: Failure| DevSpinError::Invalid {
+        path: path.to_string(),
+        message: format!(
+            "{}: {}",
+            failure.context.unwrap_or(context),
+            failure.message
+        ),
+    };
+
+    let mut resolved = config.clone();
+    let mut keys: Vec<&String> = config.content.env.keys().collect();
+    for layer in layers.iter().rev().filter(|layer| !layer.overrides_only) {
+        for key in layer.vars.keys() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    resolved.content.env = IndexMap::new();
+    for key in keys {
+        let value = resolver
+            .lookup(key)
+            .map_err(|failure| fail(format!("env `{}`", key), failure))?
+            .unwrap_or_default();
+        resolved.content.env.insert(key.clone(), value);
+    }
+    for service in &mut resolved.content.services {
+        let context = format!("service `{}`", service.name);
+        let strict = |resolver: &mut Resolver, text: &mut String, what: String| {
+            *text = resolver
+                .expand(text, Mode::Strict)
+                .map_err(|failure| fail(format!("{} {}", context, what), failure))?;
+            Ok::<(), DevSpinError>(())
+        };
+        strict(&mut resolver, &mut service.image, "image".to_string())?;
+        for port in &mut service.ports {
+            strict(&mut resolver, port, "ports".to_string())?;
+        }
+        for (key, value) in service.environment.iter_mut() {
+            strict(&mut resolver, value, format!("environment `{}`", key))?;
+        }
+    }
+    for task in &mut resolved.content.tasks {
+        task.command = resolver
+            .expand(&task.command, Mode::Shell)
+            .map_err(|failure| fail(format!("task `{}` command", task.name), failure))?;
+    }
+    Ok(resolved)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Every reference must resolve.
+    Strict,
+    /// Unresolved references are left for the shell.
+    Shell,
+}
+
+/// Why a value could not be expanded. `context` names the `env` entry where
+/// it happened, when the failure is inside one.
+struct Failure {
+    context: Option<String>,
+    message: String,
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Self {
+            context: None,
+            message,
+        }
+    }
+}
+
+struct Resolver<'a> {
+    layers: &'a [Layer],
+    env: &'a IndexMap<String, String>,
+    /// Expanded `env` values.
+    resolved: IndexMap<String, String>,
+    /// `env` entries being expanded, to detect cycles.
+    stack: Vec<String>,
+}
+
+impl Resolver<'_> {
+    fn lookup(&mut self, name: &str) -> Result<Option<String>, Failure> {
+        if let Some(value) = self.layers.iter().find_map(|layer| layer.vars.get(name)) {
+            return Ok(Some(value.clone()));
+        }
+        self.env_value(name)
+    }
+
+    /// The expanded value of `env` entry `name`.
+    fn env_value(&mut self, name: &str) -> Result<Option<String>, Failure> {
+        if let Some(value) = self.resolved.get(name) {
+            return Ok(Some(value.clone()));
+        }
+        let Some(raw) = self.env.get(name) else {
+            return Ok(None);
+        };
+        if let Some(start) = self.stack.iter().position(|entry| entry == name) {
+            let mut cycle = self.stack[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(format!("`{}` refers to itself ({})", name, cycle.join(" → ")).into());
+        }
+
+        self.stack.push(name.to_string());
+        let value = self.expand(raw, Mode::Strict);
+        self.stack.pop();
+        let value = value.map_err(|mut failure| {
+            failure
+                .context
+                .get_or_insert_with(|| format!("env `{}`", name));
+            failure
+        })?;
+        self.resolved.insert(name.to_string(), value.clone());
+        Ok(Some(value))
+    }
+
+    fn expand(&mut self, text: &str, mode: Mode) -> Result<String, Failure> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            output.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            if let Some(after) = tail.strip_prefix("$${") {
+                output.push_str("${");
+                rest = after;
+                continue;
+            }
+            let Some(inner) = tail.strip_prefix("${") else {
+                output.push('$');
+                rest = &tail[1..];
+                continue;
+            };
+            let Some(end) = closing_brace(inner) else {
+                if mode == Mode::Shell {
+                    output.push_str(tail);
+                    return Ok(output);
+                }
+                return Err(format!("unterminated `${{` in `{}`", text).into());
+            };
+            let reference = &tail[..end + 3];
+            match self.reference(&inner[..end], mode)? {
+                Some(value) => output.push_str(&value),
+                None => output.push_str(reference),
+            }
+            rest = &tail[end + 3..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    /// Expands the inside of `${...}`. `None` leaves the reference as written.
+    fn reference(&mut self, inner: &str, mode: Mode) -> Result<Option<String>, Failure> {
+        let name_len = inner
+            .char_indices()
+            .find(|&(i, c)| !is_name_char(i, c))
+            .map_or(inner.len(), |(i, _)| i);
+        let (name, operator) = inner.split_at(name_len);
+        let valid = operator.is_empty() || operator.starts_with(":-") || operator.starts_with(":?");
+        if name.is_empty() || !valid {
+            if mode == Mode::Shell {
+                return Ok(None);
+            }
+            return Err(format!(
+                "invalid reference `${{{}}}`, expected `${{VAR}}`, `${{VAR:-default}}` or `${{VAR:?message}}`",
+                inner
+            )
+            .into());
+        }
+        let fallback = (!operator.is_empty()).then_some(operator);
+
+        let value = self.lookup(name)?;
+        match (fallback, value) {
+            (None, Some(value)) => Ok(Some(value)),
+            (Some(_), Some(value)) if !value.is_empty() => Ok(Some(value)),
+            // The shell sees the same variables and applies the same rules
+            (_, _) if mode == Mode::Shell => Ok(None),
+            (None, None) => Err(format!(
+                "`{}` is not set, give it a default with `${{{}:-default}}`",
+                name, name
+            )
+            .into()),
+            (Some(fallback), _) => {
+                let (kind, argument) = fallback.split_at(2);
+                if kind == ":-" {
+                    return self.expand(argument, mode).map(Some);
+                }
+                let message = self.expand(argument, mode)?;
+                let message = if message.is_empty() {
+                    format!("`{}` is not set", name)
+                } else {
+                    format!("`{}` is not set: {}", name, message)
+                };
+                Err(message.into())
+            }
+        }
+    }
+}
+
+/// Letters, digits and `_`, not starting with a digit.
+fn is_name_char(index: usize, c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic() || (index > 0 && c.is_ascii_digit())
+}
+
+/// Byte offset of the `}` closing a `${`, given the text after it. Braces
+/// of nested references in a default are skipped.
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,156 @@
+//! Versions of the devspin.yml format and the migrations between them.
+//!
+//! Each [`Migration`] rewrites a [`Document`] from one version to the next,
+//! so a file several versions behind is brought up to date one step at a
+//! time, keeping its comments and layout.
+
+use crate::error::DevSpinError;
+use crate::parser::document::Document;
+use std::cmp::Ordering;
+
+/// Version of the format written and read by this release.
+pub const CURRENT_VERSION: &str = "1.0";
+
+/// One step rewriting a config from version `from` to version `to`.
+#[derive(Debug)]
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// What the step changes.
+    pub summary: &'static str,
+    /// Rewrites the content; `version` itself is updated afterwards.
+    pub apply: fn(&mut Document) -> Result<(), DevSpinError>,
+}
+
+/// The steps up to [`CURRENT_VERSION`], oldest first. Each step starts from
+/// the version the previous one ends at.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// How this release handles a `version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Support {
+    /// The version this release writes.
+    Current,
+    /// An older version that is migrated when read.
+    Outdated,
+    /// A version released after this one, which cannot be read.
+    Newer,
+    /// A version with no migration path, which cannot be read.
+    Unknown,
+}
+
+/// Versions this release can read, oldest first.
+pub fn known_versions() -> Vec<&'static str> {
+    known(MIGRATIONS)
+}
+
+/// Tells how `version` is handled, given the built-in [`MIGRATIONS`].
+pub fn support(version: &str) -> Support {
+    support_with(version, MIGRATIONS)
+}
+
+/// Rewrites `document` to [`CURRENT_VERSION`] and returns the steps applied,
+/// none when it is already current.
+pub fn migrate(document: &mut Document) -> Result<Vec<&'static Migration>, DevSpinError> {
+    migrate_with(document, MIGRATIONS)
+}
+
+/// Like [`migrate`], with `steps` in place of the built-in migrations. The
+/// target is the version the last step ends at.
+pub fn migrate_with<'m>(
+    document: &mut Document,
+    steps: &'m [Migration],
+) -> Result<Vec<&'m Migration>, DevSpinError> {
+    let mut applied = Vec::new();
+    let mut version = version_of(document)?;
+    match support_with(&version, steps) {
+        Support::Current => return Ok(applied),
+        Support::Outdated => {}
+        Support::Newer | Support::Unknown => {
+            return Err(DevSpinError::Invalid {
+                path: document.path().to_string(),
+                message: unsupported_with(&version, steps),
+            })
+        }
+    }
+
+    while let Some(step) = steps.iter().find(|step| same(step.from, &version)) {
+        // A chain that loops back would never reach the target.
+        if applied.len() == steps.len() {
+            break;
+        }
+        (step.apply)(document)?;
+        document.set("version", step.to)?;
+        applied.push(step);
+        version = step.to.to_string();
+    }
+    Ok(applied)
+}
+
+/// Error message for a version that cannot be read or migrated.
+pub fn unsupported(version: &str) -> String {
+    unsupported_with(version, MIGRATIONS)
+}
+
+fn unsupported_with(version: &str, steps: &[Migration]) -> String {
+    let known = known(steps);
+    if support_with(version, steps) == Support::Newer {
+        format!(
+            "version `{}` is newer than this devspin supports (up to {}), update devspin to read this file",
+            version,
+            known.last().copied().unwrap_or(CURRENT_VERSION)
+        )
+    } else {
+        format!(
+            "unknown version `{}` (known versions: {})",
+            version,
+            known.join(", ")
+        )
+    }
+}
+
+fn known(steps: &[Migration]) -> Vec<&'static str> {
+    let mut versions: Vec<&'static str> = steps.iter().map(|step| step.from).collect();
+    versions.push(steps.last().map_or(CURRENT_VERSION, |step| step.to));
+    versions
+}
+
+fn support_with(version: &str, steps: &[Migration]) -> Support {
+    let known = known(steps);
+    let current = known[known.len() - 1];
+    if same(version, current) {
+        Support::Current
+    } else if known.iter().any(|known| same(known, version)) {
+        Support::Outdated
+    } else if compare(version, current) == Some(Ordering::Greater) {
+        Support::Newer
+    } else {
+        Support::Unknown
+    }
+}
+
+fn version_of(document: &Document) -> Result<String, DevSpinError> {
+    document
+        .root()
+        .get("version")
+        .and_then(|node| node.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| DevSpinError::MissingField("version".to_string()))
+}
+
+fn same(a: &str, b: &str) -> bool {
+    compare(a, b) == Some(Ordering::Equal)
+}
+
+/// Compares dotted versions numerically, so that `1` is `1.0` and `1.10`
+/// comes after `1.9`. `None` when either is not made of numbers.
+fn compare(a: &str, b: &str) -> Option<Ordering> {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        version.split('.').map(|part| part.parse().ok()).collect()
+    };
+    let (mut a, mut b) = (parse(a)?, parse(b)?);
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    Some(a.cmp(&b))
+}
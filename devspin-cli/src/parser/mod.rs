@@ -1 +1,8 @@
 pub mod devspin_generator;
+pub mod document;
+pub mod format;
+pub mod interpolate;
+pub mod migrate;
+pub mod schema;
+pub mod validator;
+pub mod yaml;
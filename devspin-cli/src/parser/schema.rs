@@ -0,0 +1,157 @@
+use crate::parser::devspin_generator::{
+    format_duration, DevSpinConfig, Healthcheck, RESTART_POLICIES,
+};
+use crate::parser::migrate::known_versions;
+use crate::parser::validator::SPIN_FEATURE_MODES;
+use schemars::generate::SchemaSettings;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use std::borrow::Cow;
+
+/// JSON Schema (draft 7, as understood by yaml-language-server) of a whole
+/// devspin.yml, generated from [`DevSpinConfig`].
+pub fn schema() -> Schema {
+    let mut schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<DevSpinConfig>();
+    schema.insert("title".to_string(), "devspin.yml".into());
+    schema.insert(
+        "description".to_string(),
+        "Configuration of a devspin project".into(),
+    );
+    schema
+}
+
+/// [`schema`] as pretty-printed JSON, ending with a newline.
+pub fn schema_json() -> String {
+    let json = serde_json::to_string_pretty(&schema()).expect("a schema is always valid JSON");
+    json + "\n"
+}
+
+// Schemas of the fields the parser reads more loosely than their Rust type
+// suggests. They are referenced by `schema_with` in devspin_generator.rs.
+
+/// `1.0` is a number to YAML tools but is read as a string.
+pub(crate) fn version(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": ["string", "number"],
+        "examples": known_versions(),
+    })
+}
+
+/// Any scalar: unquoted values such as `1.0` or `true` are read as strings.
+pub(crate) fn scalar(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "type": ["string", "number", "boolean"] })
+}
+
+/// A mapping of variable names to scalars (`env`, `environment`).
+pub(crate) fn scalar_map(generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "object",
+        "additionalProperties": scalar(generator),
+    })
+}
+
+pub(crate) fn ports(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "array",
+        "items": {
+            "type": ["string", "integer"],
+            "examples": ["5432:5432", "127.0.0.1:8080:80", "53:53/udp"],
+        },
+    })
+}
+
+pub(crate) fn spin_feature_modes(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "array",
+        "items": { "enum": SPIN_FEATURE_MODES },
+    })
+}
+
+/// A single string or a list of them.
+pub(crate) fn one_or_many(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } },
+        ],
+    })
+}
+
+/// A command given as a single line or as a list of words.
+pub(crate) fn command(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } },
+        ],
+    })
+}
+
+pub(crate) fn restart(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            { "enum": RESTART_POLICIES },
+            { "type": "string", "pattern": "^on-failure:[0-9]+$" },
+        ],
+    })
+}
+
+/// `2s`, `500ms`, `1m`; a bare number is in seconds.
+fn duration(default: String) -> Schema {
+    json_schema!({
+        "type": ["string", "integer"],
+        "default": default,
+        "examples": ["500ms", "2s", "1m"],
+    })
+}
+
+fn port() -> Schema {
+    json_schema!({ "type": "integer", "minimum": 1, "maximum": 65535 })
+}
+
+// Written by hand: the probe is chosen by which key is present rather than
+// by a tag, which the derive cannot express.
+impl JsonSchema for Healthcheck {
+    fn schema_name() -> Cow<'static, str> {
+        "Healthcheck".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "How to tell that the service is ready. Exactly one of `command`, `tcp` or `http` is the probe.",
+            "type": "object",
+            "properties": {
+                "command": {
+                    "description": "Shell command run inside the container, healthy when it exits with 0.",
+                    "type": "string",
+                },
+                "tcp": {
+                    "description": "Container port accepting TCP connections.",
+                    "allOf": [port()],
+                },
+                "http": {
+                    "description": "Path answering an HTTP `GET` with 2xx or 3xx, such as `/health`.",
+                    "type": "string",
+                },
+                "port": {
+                    "description": "Container port of an `http` probe; the first published port by default.",
+                    "allOf": [port()],
+                },
+                "interval": duration(format_duration(Healthcheck::DEFAULT_INTERVAL)),
+                "timeout": duration(format_duration(Healthcheck::DEFAULT_TIMEOUT)),
+                "retries": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": Healthcheck::DEFAULT_RETRIES,
+                },
+            },
+            "additionalProperties": false,
+            "oneOf": [
+                { "required": ["command"] },
+                { "required": ["tcp"] },
+                { "required": ["http"] },
+            ],
+        })
+    }
+}
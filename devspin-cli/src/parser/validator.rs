@@ -0,0 +1,676 @@
+use crate::error::DevSpinError;
+use crate::parser::devspin_generator::{
+    closest_match, DevSpinConfig, DevSpinGenerator, PortMapping,
+};
+use crate::parser::interpolate;
+use crate::parser::schema::schema;
+use crate::parser::yaml::{self, Node, ScalarStyle, Span, Value};
+use serde_json::Value as Json;
+use std::fmt;
+use std::path::Path;
+
+/// Spin features devspin knows how to run.
+pub const SPIN_FEATURES: &[&str] = &["lint", "format"];
+/// Files a spin feature applies to: the current file, its directory or the whole project.
+pub const SPIN_FEATURE_MODES: &[&str] = &["currFile", "currDir", "all"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The checks run by [`validate_str`]. Codes are stable so CI logs and
+/// documentation can refer to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    DuplicateTask,
+    DuplicateService,
+    MalformedPort,
+    PortCollision,
+    EmptyImage,
+    UnknownSpinFeature,
+    UnknownSpinFeatureMode,
+    InvalidEnvName,
+    /// Reported by [`validate_schema`] only.
+    SchemaViolation,
+}
+
+impl Rule {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::DuplicateTask => "DS002",
+            Rule::DuplicateService => "DS003",
+            Rule::MalformedPort => "DS004",
+            Rule::PortCollision => "DS005",
+            Rule::EmptyImage => "DS006",
+            Rule::UnknownSpinFeature => "DS007",
+            Rule::UnknownSpinFeatureMode => "DS008",
+            Rule::InvalidEnvName => "DS009",
+            Rule::SchemaViolation => "DS010",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            // Features from a newer devspin are ignored rather than rejected.
+            Rule::UnknownSpinFeature => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// One finding of the validation, located in the source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    /// The source line with a caret under the location.
+    pub snippet: String,
+}
+
+impl Diagnostic {
+    pub fn code(&self) -> &'static str {
+        self.rule.code()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}[{}]: {}\n{}",
+            self.path,
+            self.line,
+            self.column,
+            self.severity,
+            self.code(),
+            self.message,
+            self.snippet
+        )
+    }
+}
+
+/// Runs the semantic checks on the content of a devspin.yml, sorted by
+/// location. Syntax and structure errors are returned as `Err`, as the
+/// checks need a config that parses.
+pub fn validate_str(source: &str, path: &str) -> Result<Vec<Diagnostic>, DevSpinError> {
+    let config = DevSpinGenerator::parse_str(source, path)?;
+    let root = yaml::parse(source, path)?;
+    let mut validator = Validator {
+        path,
+        lines: source.lines().collect(),
+        config: &config,
+        root: &root,
+        diagnostics: Vec::new(),
+    };
+    validator.check_spin_features();
+    validator.check_env_names();
+    validator.check_names("tasks", Rule::DuplicateTask, "task");
+    validator.check_names("services", Rule::DuplicateService, "service");
+    validator.check_images();
+    validator.check_ports();
+
+    let mut diagnostics = validator.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    Ok(diagnostics)
+}
+
+/// Reads and validates a devspin.yml from disk.
+pub fn validate_file(path: impl AsRef<Path>) -> Result<Vec<Diagnostic>, DevSpinError> {
+    let (source, display) = read(path.as_ref())?;
+    validate_str(&source, &display)
+}
+
+/// Checks the content of a devspin.yml against the JSON Schema exported by
+/// `devspin schema`, the way an editor would. Only YAML syntax errors are
+/// returned as `Err`, so every mistake the schema can see is reported at once.
+///
+/// `pattern` and `format` are not checked.
+pub fn validate_schema(source: &str, path: &str) -> Result<Vec<Diagnostic>, DevSpinError> {
+    let root = yaml::parse(source, path)?;
+    let schema = schema().to_value();
+    let mut checker = SchemaChecker {
+        root: &schema,
+        findings: Vec::new(),
+    };
+    checker.check(&root, &schema, "");
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut diagnostics: Vec<Diagnostic> = checker
+        .findings
+        .into_iter()
+        .map(|(span, message)| locate(path, &lines, Rule::SchemaViolation, span, message))
+        .collect();
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    Ok(diagnostics)
+}
+
+/// Reads and checks a devspin.yml from disk against the schema.
+pub fn validate_schema_file(path: impl AsRef<Path>) -> Result<Vec<Diagnostic>, DevSpinError> {
+    let (source, display) = read(path.as_ref())?;
+    validate_schema(&source, &display)
+}
+
+fn read(path: &Path) -> Result<(String, String), DevSpinError> {
+    let display = path.display().to_string();
+    let source = std::fs::read_to_string(path).map_err(|source| DevSpinError::Io {
+        path: display.clone(),
+        source,
+    })?;
+    Ok((source, display))
+}
+
+/// Builds the diagnostic of `rule` at `span`, with the snippet of its line.
+fn locate(path: &str, lines: &[&str], rule: Rule, span: Span, message: String) -> Diagnostic {
+    let line = lines.get(span.line - 1).copied().unwrap_or_default();
+    let DevSpinError::Parse {
+        message,
+        line,
+        column,
+        snippet,
+        ..
+    } = DevSpinError::parse(path, line, span.line, span.column, message)
+    else {
+        unreachable!("DevSpinError::parse builds a Parse error");
+    };
+    Diagnostic {
+        rule,
+        severity: rule.severity(),
+        message,
+        path: path.to_string(),
+        line,
+        column,
+        snippet,
+    }
+}
+
+/// Whether `name` can be exported as an environment variable by every shell.
+pub fn is_valid_env_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+struct Validator<'a> {
+    path: &'a str,
+    lines: Vec<&'a str>,
+    config: &'a DevSpinConfig,
+    root: &'a Node,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, rule: Rule, span: Span, message: impl Into<String>) {
+        let diagnostic = locate(self.path, &self.lines, rule, span, message.into());
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Items of the top-level sequence `key`.
+    fn items(&self, key: &str) -> &'a [Node] {
+        let root: &'a Node = self.root;
+        root.get(key)
+            .and_then(Node::as_sequence)
+            .unwrap_or_default()
+    }
+
+    fn check_spin_features(&mut self) {
+        let Some(entries) = self.root.get("spin-features").and_then(Node::as_mapping) else {
+            return;
+        };
+        for entry in entries {
+            if !SPIN_FEATURES.contains(&entry.key.as_str()) {
+                let mut message = format!("unknown spin feature `{}`", entry.key);
+                match closest_match(&entry.key, SPIN_FEATURES) {
+                    Some(suggestion) => {
+                        message.push_str(&format!(", did you mean `{}`?", suggestion))
+                    }
+                    None => message
+                        .push_str(&format!(" (known features: {})", SPIN_FEATURES.join(", "))),
+                }
+                self.report(Rule::UnknownSpinFeature, entry.key_span, message);
+            }
+
+            let modes = entry.value.get("modes").and_then(Node::as_sequence);
+            for mode in modes.unwrap_or_default() {
+                let name = mode.as_str().unwrap_or_default();
+                if SPIN_FEATURE_MODES.contains(&name) {
+                    continue;
+                }
+                let mut message = format!("unknown mode `{}`", name);
+                match closest_match(name, SPIN_FEATURE_MODES) {
+                    Some(suggestion) => {
+                        message.push_str(&format!(", did you mean `{}`?", suggestion))
+                    }
+                    None => message.push_str(&format!(
+                        " (expected one of {})",
+                        SPIN_FEATURE_MODES.join(", ")
+                    )),
+                }
+                self.report(Rule::UnknownSpinFeatureMode, mode.span, message);
+            }
+        }
+    }
+
+    fn check_env_names(&mut self) {
+        let mut maps: Vec<&Node> = self.root.get("env").into_iter().collect();
+        for service in self.items("services") {
+            maps.extend(service.get("environment"));
+        }
+        for map in maps {
+            for entry in map.as_mapping().unwrap_or_default() {
+                if !is_valid_env_name(&entry.key) {
+                    let message = format!(
+                        "invalid environment variable name `{}` (use letters, digits and `_`, not starting with a digit)",
+                        entry.key
+                    );
+                    self.report(Rule::InvalidEnvName, entry.key_span, message);
+                }
+            }
+        }
+    }
+
+    /// Reports every task or service reusing the name of an earlier one.
+    fn check_names(&mut self, key: &str, rule: Rule, what: &str) {
+        let mut seen: Vec<(&str, usize)> = Vec::new();
+        let mut duplicates = Vec::new();
+        for item in self.items(key) {
+            let Some(name) = item.get("name") else {
+                continue;
+            };
+            let text = name.as_str().unwrap_or_default();
+            match seen.iter().find(|(seen, _)| *seen == text) {
+                Some((_, first_line)) => duplicates.push((
+                    name.span,
+                    format!(
+                        "duplicate {} name `{}` (first declared on line {})",
+                        what, text, first_line
+                    ),
+                )),
+                None => seen.push((text, name.span.line)),
+            }
+        }
+        for (span, message) in duplicates {
+            self.report(rule, span, message);
+        }
+    }
+
+    fn check_images(&mut self) {
+        let mut empty = Vec::new();
+        for (node, service) in self
+            .items("services")
+            .iter()
+            .zip(&self.config.content.services)
+        {
+            if service.image.trim().is_empty() {
+                let span = node
+                    .get("image")
+                    .or_else(|| node.get("name"))
+                    .map_or(node.span, |n| n.span);
+                empty.push((span, format!("service `{}` has no `image`", service.name)));
+            }
+        }
+        for (span, message) in empty {
+            self.report(Rule::EmptyImage, span, message);
+        }
+    }
+
+    /// Malformed mappings, and host ports published twice (by one service or
+    /// across services) on overlapping addresses.
+    fn check_ports(&mut self) {
+        // (ip, protocol, first, last, service, line) of every fixed host port range
+        let mut published: Vec<(Option<String>, String, u16, u16, String, usize)> = Vec::new();
+        let mut findings = Vec::new();
+
+        for (node, service) in self
+            .items("services")
+            .iter()
+            .zip(&self.config.content.services)
+        {
+            let ports = node.get("ports").and_then(Node::as_sequence);
+            for port in ports.unwrap_or_default() {
+                let spec = port.as_str().unwrap_or_default();
+                // Checked once the references are expanded, when the ports are used.
+                if interpolate::has_references(spec) {
+                    continue;
+                }
+                let mapping = match PortMapping::parse(spec) {
+                    Ok(mapping) => mapping,
+                    Err(message) => {
+                        findings.push((Rule::MalformedPort, port.span, message));
+                        continue;
+                    }
+                };
+                let Some((first, last)) = mapping.host else {
+                    continue;
+                };
+                let ip = mapping.ip.filter(|ip| ip != "0.0.0.0" && ip != "::");
+                let clash = published
+                    .iter()
+                    .find(|(other_ip, protocol, start, end, ..)| {
+                        *protocol == mapping.protocol
+                            && first <= *end
+                            && *start <= last
+                            && (ip.is_none() || other_ip.is_none() || ip == *other_ip)
+                    });
+                if let Some((.., owner, line)) = clash {
+                    let message = format!(
+                        "`{}` publishes a host port already used by service `{}` on line {}",
+                        spec, owner, line
+                    );
+                    findings.push((Rule::PortCollision, port.span, message));
+                    continue;
+                }
+                published.push((
+                    ip,
+                    mapping.protocol,
+                    first,
+                    last,
+                    service.name.clone(),
+                    port.span.line,
+                ));
+            }
+        }
+        for (rule, span, message) in findings {
+            self.report(rule, span, message);
+        }
+    }
+}
+
+/// Walks the YAML tree alongside a JSON Schema. Supports the keywords the
+/// exported schema uses: `$ref`, `type`, `enum`, `minimum`, `maximum`,
+/// `properties`, `required`, `additionalProperties`, `items`, `allOf`,
+/// `anyOf` and `oneOf`.
+struct SchemaChecker<'s> {
+    root: &'s Json,
+    findings: Vec<(Span, String)>,
+}
+
+impl<'s> SchemaChecker<'s> {
+    /// `at` is the dotted path of `node`, empty for the document.
+    fn check(&mut self, node: &Node, schema: &'s Json, at: &str) {
+        let Some(schema) = schema.as_object() else {
+            if schema == &Json::Bool(false) {
+                self.findings
+                    .push((node.span, format!("{} is not allowed", label(at))));
+            }
+            return;
+        };
+
+        if let Some(target) = schema
+            .get("$ref")
+            .and_then(Json::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| self.root.pointer(pointer))
+        {
+            self.check(node, target, at);
+        }
+
+        let kind = json_type(node);
+        if let Some(types) = schema.get("type") {
+            let types = names(types);
+            if !types.iter().any(|t| type_matches(t, kind)) {
+                let expected: Vec<&str> = types.iter().map(|t| describe_type(t)).collect();
+                let message = format!(
+                    "{} should be {}, found {}",
+                    label(at),
+                    or_list(&expected),
+                    describe_type(kind)
+                );
+                self.findings.push((node.span, message));
+                return;
+            }
+        }
+        if let Some(values) = schema.get("enum").and_then(Json::as_array) {
+            let text = node.as_str().unwrap_or_default();
+            if !values.iter().any(|value| scalar_text(value) == text) {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| format!("`{}`", scalar_text(value)))
+                    .collect();
+                let message = format!(
+                    "{} should be one of {}, found `{}`",
+                    label(at),
+                    values.join(", "),
+                    text
+                );
+                self.findings.push((node.span, message));
+            }
+        }
+        if let Some(number) = node.as_str().and_then(|text| text.parse::<f64>().ok()) {
+            let minimum = schema.get("minimum").and_then(Json::as_f64);
+            let maximum = schema.get("maximum").and_then(Json::as_f64);
+            if minimum.is_some_and(|min| number < min) || maximum.is_some_and(|max| number > max) {
+                let message = format!(
+                    "{} should be between {} and {}, found {}",
+                    label(at),
+                    minimum.map_or("-∞".to_string(), |n| n.to_string()),
+                    maximum.map_or("∞".to_string(), |n| n.to_string()),
+                    number
+                );
+                self.findings.push((node.span, message));
+            }
+        }
+
+        for branch in schema
+            .get("allOf")
+            .and_then(Json::as_array)
+            .into_iter()
+            .flatten()
+        {
+            self.check(node, branch, at);
+        }
+        for (keyword, exactly_one) in [("anyOf", false), ("oneOf", true)] {
+            let Some(branches) = schema.get(keyword).and_then(Json::as_array) else {
+                continue;
+            };
+            let matching = branches
+                .iter()
+                .filter(|branch| self.matches(node, branch))
+                .count();
+            // When a single branch has the node's type, its own errors say more
+            let candidates: Vec<&Json> = branches
+                .iter()
+                .filter(|branch| self.accepts_type(branch, kind))
+                .collect();
+            if let (&[branch], 0) = (&candidates[..], matching) {
+                self.check(node, branch, at);
+            } else if matching == 0 || (exactly_one && matching > 1) {
+                let forms: Vec<String> = branches.iter().map(|b| self.describe(b)).collect();
+                let message = if matching == 0 {
+                    format!("{} should be {}", label(at), or_list(&forms))
+                } else {
+                    format!("{} should be only one of: {}", label(at), forms.join(", "))
+                };
+                self.findings.push((node.span, message));
+            }
+        }
+
+        if let Some(entries) = node.as_mapping() {
+            let properties = schema.get("properties").and_then(Json::as_object);
+            for key in schema
+                .get("required")
+                .and_then(Json::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Json::as_str)
+            {
+                if !entries.iter().any(|entry| entry.key == key) {
+                    let message = format!("{} is missing the `{}` key", label(at), key);
+                    self.findings.push((node.span, message));
+                }
+            }
+            for entry in entries {
+                let path = if at.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{}.{}", at, entry.key)
+                };
+                match properties.and_then(|properties| properties.get(&entry.key)) {
+                    Some(property) => self.check(&entry.value, property, &path),
+                    None => match schema.get("additionalProperties") {
+                        Some(Json::Bool(false)) => {
+                            let known: Vec<&str> = properties
+                                .map(|p| p.keys().map(String::as_str).collect())
+                                .unwrap_or_default();
+                            let mut message =
+                                format!("unknown key `{}` in {}", entry.key, label(at));
+                            if let Some(suggestion) = closest_match(&entry.key, &known) {
+                                message.push_str(&format!(", did you mean `{}`?", suggestion));
+                            }
+                            self.findings.push((entry.key_span, message));
+                        }
+                        Some(additional) => self.check(&entry.value, additional, &path),
+                        None => {}
+                    },
+                }
+            }
+        }
+
+        if let (Some(items), Some(schema)) = (node.as_sequence(), schema.get("items")) {
+            for (index, item) in items.iter().enumerate() {
+                self.check(item, schema, &format!("{}[{}]", at, index));
+            }
+        }
+    }
+
+    fn matches(&self, node: &Node, schema: &'s Json) -> bool {
+        let mut checker = SchemaChecker {
+            root: self.root,
+            findings: Vec::new(),
+        };
+        checker.check(node, schema, "");
+        checker.findings.is_empty()
+    }
+
+    /// Whether the `type` of `schema`, if any, allows a node of JSON type `kind`.
+    fn accepts_type(&self, schema: &Json, kind: &str) -> bool {
+        let schema = self.resolve(schema);
+        match schema.get("type") {
+            Some(types) => names(types).iter().any(|t| type_matches(t, kind)),
+            None => true,
+        }
+    }
+
+    /// Follows a local `$ref`, if `schema` is one.
+    fn resolve<'j>(&'j self, schema: &'j Json) -> &'j Json {
+        schema
+            .get("$ref")
+            .and_then(Json::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| self.root.pointer(pointer))
+            .unwrap_or(schema)
+    }
+
+    /// Short description of a subschema, for `anyOf` and `oneOf` messages.
+    fn describe(&self, schema: &Json) -> String {
+        let schema = self.resolve(schema);
+        if let Some(keys) = schema.get("required") {
+            let keys: Vec<String> = names(keys).iter().map(|k| format!("`{}`", k)).collect();
+            return format!("a mapping with {}", keys.join(" and "));
+        }
+        if let Some(values) = schema.get("enum").and_then(Json::as_array) {
+            let values: Vec<String> = values
+                .iter()
+                .map(|value| format!("`{}`", scalar_text(value)))
+                .collect();
+            return values.join(", ");
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Json::as_str) {
+            return format!("a string matching `{}`", pattern);
+        }
+        match schema.get("type") {
+            Some(types) => {
+                let types: Vec<&str> = names(types).iter().map(|t| describe_type(t)).collect();
+                or_list(&types)
+            }
+            None => "any value".to_string(),
+        }
+    }
+}
+
+/// How a message names the node at `at`.
+fn label(at: &str) -> String {
+    if at.is_empty() {
+        "the document".to_string()
+    } else {
+        format!("`{}`", at)
+    }
+}
+
+/// `a`, `a or b`, `a, b or c`
+fn or_list(items: &[impl AsRef<str>]) -> String {
+    let items: Vec<&str> = items.iter().map(AsRef::as_ref).collect();
+    match items.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+/// The strings of a keyword given as one string or an array of them.
+fn names(value: &Json) -> Vec<&str> {
+    match value {
+        Json::String(name) => vec![name.as_str()],
+        Json::Array(names) => names.iter().filter_map(Json::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The JSON type a YAML tool would see for `node`.
+fn json_type(node: &Node) -> &'static str {
+    match &node.value {
+        Value::Null => "null",
+        Value::Sequence(_) => "array",
+        Value::Mapping(_) => "object",
+        Value::Scalar(text, ScalarStyle::Plain) => match text.as_str() {
+            "true" | "false" => "boolean",
+            "null" | "~" => "null",
+            _ if text.parse::<i64>().is_ok() => "integer",
+            _ if text.chars().any(|c| c.is_ascii_digit())
+                && text.chars().all(|c| "0123456789+-.eE".contains(c))
+                && text.parse::<f64>().is_ok() =>
+            {
+                "number"
+            }
+            _ => "string",
+        },
+        Value::Scalar(..) => "string",
+    }
+}
+
+fn type_matches(expected: &str, kind: &str) -> bool {
+    expected == kind || (expected == "number" && kind == "integer")
+}
+
+fn describe_type(name: &str) -> &str {
+    match name {
+        "string" => "a string",
+        "integer" => "an integer",
+        "number" => "a number",
+        "boolean" => "a boolean",
+        "array" => "a sequence",
+        "object" => "a mapping",
+        "null" => "an empty value",
+        other => other,
+    }
+}
+
+fn scalar_text(value: &Json) -> String {
+    match value {
+        Json::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
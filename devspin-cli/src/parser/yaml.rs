@@ -0,0 +1,1013 @@
+//! Indentation-aware parser for the YAML subset used by devspin.yml.
+//!
+//! Supported: block mappings and sequences (including `- key: value` entries),
+//! single-line flow collections (`[a, b]`, `{k: v}`), plain, single-quoted and
+//! double-quoted scalars, literal (`|`) and folded (`>`) block scalars with
+//! chomping indicators, and `#` comments. Every node keeps its source span so
+//! that later stages can point diagnostics at the offending line and column.
+
+use crate::error::DevSpinError;
+
+/// Location of a node in the source. Lines and columns are 1-based, columns
+/// are byte offsets, and `end_column` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    fn point(line: usize, column: usize) -> Self {
+        Self {
+            line,
+            column,
+            end_line: line,
+            end_column: column,
+        }
+    }
+
+    fn to(self, end: Span) -> Self {
+        Self {
+            end_line: end.end_line,
+            end_column: end.end_column,
+            ..self
+        }
+    }
+
+    /// Extends the span so it ends no earlier than `other`.
+    fn max_end(self, other: Span) -> Self {
+        if (other.end_line, other.end_column) > (self.end_line, self.end_column) {
+            self.to(other)
+        } else {
+            self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarStyle {
+    Plain,
+    SingleQuoted,
+    DoubleQuoted,
+    Literal,
+    Folded,
+}
+
+/// How a block scalar treats its final line break and trailing empty lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chomping {
+    /// `|-`: no trailing line break.
+    Strip,
+    /// `|`: a single trailing line break.
+    Clip,
+    /// `|+`: every trailing line break is kept.
+    Keep,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Scalar(String, ScalarStyle),
+    Sequence(Vec<Node>),
+    Mapping(Vec<Entry>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub key_span: Span,
+    pub value: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub value: Value,
+    pub span: Span,
+    /// Whether the collection was written in flow style (`[a, b]` / `{k: v}`).
+    pub flow: bool,
+}
+
+impl Node {
+    fn new(value: Value, span: Span) -> Self {
+        Self {
+            value,
+            span,
+            flow: false,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::Scalar(text, _) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[Entry]> {
+        match &self.value {
+            Value::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.value, Value::Null)
+    }
+
+    /// Looks up `key` when this node is a mapping.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.as_mapping()?
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| &entry.value)
+    }
+
+    /// Human readable kind, used in diagnostics ("expected a mapping, found a scalar").
+    pub fn kind(&self) -> &'static str {
+        match self.value {
+            Value::Null => "an empty value",
+            Value::Scalar(..) => "a scalar",
+            Value::Sequence(_) => "a sequence",
+            Value::Mapping(_) => "a mapping",
+        }
+    }
+}
+
+/// Parses `source` into a node tree. `path` is only used in error messages.
+pub fn parse(source: &str, path: &str) -> Result<Node, DevSpinError> {
+    let mut parser = Parser::new(source, path)?;
+    parser.parse_document()
+}
+
+struct Parser<'a> {
+    path: &'a str,
+    lines: Vec<&'a str>,
+    /// Byte offset where the content of each line starts. This is the
+    /// indentation, except for `- ` entries whose inline content is re-read
+    /// as if it started after the dash.
+    starts: Vec<usize>,
+    /// Byte offset where the content of each line ends (comments and
+    /// trailing whitespace excluded).
+    ends: Vec<usize>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str, path: &'a str) -> Result<Self, DevSpinError> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut starts = Vec::with_capacity(lines.len());
+        let mut ends = Vec::with_capacity(lines.len());
+
+        // Indentation of the open block scalar header, and of its content once
+        // known: a tab there is text, not indentation
+        let mut block: Option<(usize, Option<usize>)> = None;
+        for (idx, line) in lines.iter().enumerate() {
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            let end = line[..comment_start(line)].trim_end().len().max(indent);
+            if let Some((header, content)) = block {
+                let blank = line.trim().is_empty();
+                let inside = blank || indent >= content.unwrap_or(header + 1);
+                if inside {
+                    if !blank && content.is_none() {
+                        block = Some((header, Some(indent)));
+                    }
+                    starts.push(indent);
+                    ends.push(end);
+                    continue;
+                }
+                block = None;
+            }
+            if end > indent && line[indent..].starts_with('\t') {
+                return Err(DevSpinError::parse(
+                    path,
+                    line,
+                    idx + 1,
+                    indent + 1,
+                    "tabs are not allowed for indentation",
+                ));
+            }
+            if ends_with_block_header(&line[indent..end]) {
+                block = Some((indent, None));
+            }
+            starts.push(indent);
+            ends.push(end);
+        }
+
+        let mut parser = Self {
+            path,
+            lines,
+            starts,
+            ends,
+            pos: 0,
+        };
+        if let Some(i) = parser.peek() {
+            if parser.content(i) == "---" {
+                parser.pos += 1;
+            }
+        }
+        Ok(parser)
+    }
+
+    fn content(&self, i: usize) -> &'a str {
+        &self.lines[i][self.starts[i]..self.ends[i]]
+    }
+
+    /// Skips blank and comment-only lines and returns the next content line.
+    fn peek(&mut self) -> Option<usize> {
+        while self.pos < self.lines.len() && self.content(self.pos).is_empty() {
+            self.pos += 1;
+        }
+        (self.pos < self.lines.len()).then_some(self.pos)
+    }
+
+    fn error(&self, i: usize, byte: usize, message: impl Into<String>) -> DevSpinError {
+        DevSpinError::parse(self.path, self.lines[i], i + 1, byte + 1, message)
+    }
+
+    fn parse_document(&mut self) -> Result<Node, DevSpinError> {
+        let Some(i) = self.peek() else {
+            return Ok(Node::new(Value::Null, Span::point(1, 1)));
+        };
+        let root = self.parse_block(self.starts[i], 0)?;
+
+        if let Some(i) = self.peek() {
+            if self.content(i) != "..." {
+                return Err(self.error(i, self.starts[i], "unexpected content after the document"));
+            }
+        }
+        Ok(root)
+    }
+
+    /// Parses the block node starting on the next content line, which sits at `indent`.
+    /// `parent_indent` is the indentation of the enclosing collection.
+    fn parse_block(&mut self, indent: usize, parent_indent: usize) -> Result<Node, DevSpinError> {
+        let i = self.pos;
+        let content = self.content(i);
+        if is_sequence_entry(content) {
+            self.parse_sequence(indent)
+        } else if split_key(content).is_some() {
+            self.parse_mapping(indent)
+        } else {
+            self.pos += 1;
+            self.parse_inline(i, self.starts[i], parent_indent)
+        }
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<Node, DevSpinError> {
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut span = Span::point(self.pos + 1, indent + 1);
+
+        while let Some(i) = self.peek() {
+            let line_indent = self.starts[i];
+            if line_indent < indent {
+                break;
+            }
+            if line_indent > indent {
+                return Err(self.error(i, line_indent, "unexpected indentation"));
+            }
+
+            let content = self.content(i);
+            if is_sequence_entry(content) {
+                return Err(self.error(
+                    i,
+                    line_indent,
+                    "unexpected sequence entry, expected a `key: value` pair",
+                ));
+            }
+            let Some(split) = split_key(content) else {
+                return Err(self.error(i, line_indent, "expected a `key: value` pair"));
+            };
+            let key = split.key(self, i)?;
+            let key_span = Span {
+                line: i + 1,
+                column: line_indent + 1,
+                end_line: i + 1,
+                end_column: line_indent + split.key_end + 1,
+            };
+            if entries.iter().any(|entry| entry.key == key) {
+                return Err(self.error(i, line_indent, format!("duplicate key `{}`", key)));
+            }
+            self.pos += 1;
+
+            let value = match split.rest {
+                Some(rest) => self.parse_inline(i, line_indent + rest, indent)?,
+                None => {
+                    let after_colon = Span::point(i + 1, line_indent + split.key_end + 2);
+                    match self.peek() {
+                        Some(j) if self.starts[j] > indent => {
+                            self.parse_block(self.starts[j], indent)?
+                        }
+                        Some(j)
+                            if self.starts[j] == indent && is_sequence_entry(self.content(j)) =>
+                        {
+                            self.parse_sequence(indent)?
+                        }
+                        _ => Node::new(Value::Null, after_colon),
+                    }
+                }
+            };
+
+            span = span.to(value.span.max_end(key_span));
+            entries.push(Entry {
+                key,
+                key_span,
+                value,
+            });
+        }
+
+        Ok(Node::new(Value::Mapping(entries), span))
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<Node, DevSpinError> {
+        let mut items = Vec::new();
+        let mut span = Span::point(self.pos + 1, indent + 1);
+
+        while let Some(i) = self.peek() {
+            let line_indent = self.starts[i];
+            if line_indent < indent {
+                break;
+            }
+            if line_indent > indent {
+                return Err(self.error(i, line_indent, "unexpected indentation"));
+            }
+            let content = self.content(i);
+            if !is_sequence_entry(content) {
+                break;
+            }
+
+            let rest = &content[1..];
+            let inline_start = line_indent + 1 + (rest.len() - rest.trim_start().len());
+            let dash_end = Span::point(i + 1, line_indent + 2);
+
+            let item = if rest.trim().is_empty() {
+                self.pos += 1;
+                match self.peek() {
+                    Some(j) if self.starts[j] > indent => {
+                        self.parse_block(self.starts[j], indent)?
+                    }
+                    _ => Node::new(Value::Null, dash_end),
+                }
+            } else {
+                // Re-read the entry's inline content as if the line started after the dash,
+                // so `- name: x` opens a mapping whose keys are aligned on `name`.
+                self.starts[i] = inline_start;
+                self.parse_block(inline_start, indent)?
+            };
+
+            span = span.to(item.span);
+            items.push(item);
+        }
+
+        Ok(Node::new(Value::Sequence(items), span))
+    }
+
+    /// Parses the value written on line `i` from byte `start` to the end of its content.
+    /// Block scalars continue on the following lines indented deeper than `parent_indent`.
+    fn parse_inline(
+        &mut self,
+        i: usize,
+        start: usize,
+        parent_indent: usize,
+    ) -> Result<Node, DevSpinError> {
+        let line = self.lines[i];
+        let end = self.ends[i];
+        let text = &line[start..end];
+
+        match text.as_bytes()[0] {
+            b'|' | b'>' => self.parse_block_scalar(i, start, parent_indent),
+            b'&' | b'*' | b'!' => {
+                Err(self.error(i, start, "anchors, aliases and tags are not supported"))
+            }
+            b'[' | b'{' | b'"' | b'\'' => {
+                let mut flow = FlowParser {
+                    parser: self,
+                    line: i,
+                    pos: start,
+                    end,
+                };
+                let node = flow.parse_value(false)?;
+                flow.skip_spaces();
+                if flow.pos < end {
+                    return Err(self.error(i, flow.pos, "unexpected characters after the value"));
+                }
+                Ok(node)
+            }
+            _ => {
+                if let Some(idx) = text.find(": ") {
+                    return Err(self.error(
+                        i,
+                        start + idx,
+                        "mapping values are not allowed here (quote the value if it contains `: `)",
+                    ));
+                }
+                let span = Span {
+                    line: i + 1,
+                    column: start + 1,
+                    end_line: i + 1,
+                    end_column: end + 1,
+                };
+                Ok(Node::new(
+                    Value::Scalar(text.to_string(), ScalarStyle::Plain),
+                    span,
+                ))
+            }
+        }
+    }
+
+    /// Parses a `|` or `>` block scalar whose header sits on line `i` at byte `start`.
+    fn parse_block_scalar(
+        &mut self,
+        i: usize,
+        start: usize,
+        parent_indent: usize,
+    ) -> Result<Node, DevSpinError> {
+        let header = &self.lines[i][start..self.ends[i]];
+        let style = if header.starts_with('|') {
+            ScalarStyle::Literal
+        } else {
+            ScalarStyle::Folded
+        };
+
+        let mut chomping = Chomping::Clip;
+        let mut explicit_indent = None;
+        for (offset, ch) in header.char_indices().skip(1) {
+            match ch {
+                '-' | '+' if chomping == Chomping::Clip => {
+                    chomping = if ch == '-' {
+                        Chomping::Strip
+                    } else {
+                        Chomping::Keep
+                    };
+                }
+                '1'..='9' if explicit_indent.is_none() => {
+                    explicit_indent = Some(parent_indent + (ch as usize - '0' as usize));
+                }
+                _ => {
+                    return Err(self.error(
+                        i,
+                        start + offset,
+                        "invalid block scalar header, expected `|`, `>`, `-`, `+` or an indentation digit",
+                    ))
+                }
+            }
+        }
+
+        // The first non-empty line fixes the indentation unless the header gave it.
+        let content_indent = explicit_indent.or_else(|| {
+            self.lines[i + 1..]
+                .iter()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start_matches(' ').len())
+        });
+
+        let mut lines: Vec<&str> = Vec::new();
+        let mut last = i;
+        if let Some(indent) = content_indent.filter(|&indent| indent > parent_indent) {
+            for (j, line) in self.lines.iter().enumerate().skip(i + 1) {
+                if line.trim().is_empty() {
+                    lines.push(line.get(indent..).unwrap_or(""));
+                    continue;
+                }
+                let line_indent = line.len() - line.trim_start_matches(' ').len();
+                if line_indent < indent {
+                    break;
+                }
+                lines.push(&line[indent..]);
+                last = j;
+            }
+        }
+        // Empty lines after the last content line only matter for keep chomping.
+        let content_lines = last - i;
+        let trailing = lines.len() - content_lines;
+        lines.truncate(content_lines);
+
+        let mut text = match style {
+            ScalarStyle::Literal => lines.join("\n"),
+            _ => fold_lines(&lines),
+        };
+        if !lines.is_empty() && chomping != Chomping::Strip {
+            text.push('\n');
+        }
+        if chomping == Chomping::Keep {
+            text.push_str(&"\n".repeat(trailing));
+        }
+
+        self.pos = last + 1;
+        let span = Span {
+            line: i + 1,
+            column: start + 1,
+            end_line: last + 1,
+            end_column: self.lines[last].len() + 1,
+        };
+        Ok(Node::new(Value::Scalar(text, style), span))
+    }
+}
+
+/// Single-line flow collections and quoted scalars.
+struct FlowParser<'p, 'a> {
+    parser: &'p Parser<'a>,
+    line: usize,
+    pos: usize,
+    end: usize,
+}
+
+impl FlowParser<'_, '_> {
+    fn bytes(&self) -> &[u8] {
+        self.parser.lines[self.line].as_bytes()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        (self.pos < self.end).then(|| self.bytes()[self.pos])
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, pos: usize, message: impl Into<String>) -> DevSpinError {
+        self.parser.error(self.line, pos, message)
+    }
+
+    fn span(&self, start: usize) -> Span {
+        Span {
+            line: self.line + 1,
+            column: start + 1,
+            end_line: self.line + 1,
+            end_column: self.pos + 1,
+        }
+    }
+
+    /// Parses one value. Inside a flow collection plain scalars stop at `,`, `]`,
+    /// `}` (and `:` for mapping keys).
+    fn parse_value(&mut self, in_flow: bool) -> Result<Node, DevSpinError> {
+        self.skip_spaces();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'[') => self.parse_sequence(),
+            Some(b'{') => self.parse_mapping(),
+            Some(b'"') => self.parse_double_quoted(),
+            Some(b'\'') => self.parse_single_quoted(),
+            Some(_) if in_flow => {
+                let text = self.parse_plain(false);
+                if text.is_empty() {
+                    return Err(self.error(start, "expected a value"));
+                }
+                Ok(Node::new(
+                    Value::Scalar(text, ScalarStyle::Plain),
+                    self.span(start),
+                ))
+            }
+            _ => Err(self.error(start, "expected a value")),
+        }
+    }
+
+    fn parse_plain(&mut self, is_key: bool) -> String {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            let ends_key = is_key && b == b':';
+            if matches!(b, b',' | b']' | b'}') || ends_key {
+                break;
+            }
+            self.pos += 1;
+        }
+        let raw = &self.parser.lines[self.line][start..self.pos];
+        let trimmed = raw.trim_end();
+        self.pos = start + trimmed.len();
+        trimmed.to_string()
+    }
+
+    fn parse_sequence(&mut self) -> Result<Node, DevSpinError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some(b']') => break,
+                None => return Err(self.error(start, "unterminated flow sequence, expected `]`")),
+                _ => {}
+            }
+            items.push(self.parse_value(true)?);
+            self.skip_spaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => break,
+                None => return Err(self.error(start, "unterminated flow sequence, expected `]`")),
+                Some(_) => return Err(self.error(self.pos, "expected `,` or `]`")),
+            }
+        }
+        self.pos += 1;
+        let mut node = Node::new(Value::Sequence(items), self.span(start));
+        node.flow = true;
+        Ok(node)
+    }
+
+    fn parse_mapping(&mut self) -> Result<Node, DevSpinError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut entries: Vec<Entry> = Vec::new();
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some(b'}') => break,
+                None => return Err(self.error(start, "unterminated flow mapping, expected `}`")),
+                _ => {}
+            }
+
+            let key_start = self.pos;
+            let key = match self.peek() {
+                Some(b'"' | b'\'') => match self.parse_value(true)?.value {
+                    Value::Scalar(text, _) => text,
+                    _ => unreachable!("quoted keys are scalars"),
+                },
+                _ => self.parse_plain(true),
+            };
+            if key.is_empty() {
+                return Err(self.error(key_start, "expected a key"));
+            }
+            let key_span = self.span(key_start);
+            if entries.iter().any(|entry| entry.key == key) {
+                return Err(self.error(key_start, format!("duplicate key `{}`", key)));
+            }
+
+            self.skip_spaces();
+            if self.peek() != Some(b':') {
+                return Err(self.error(self.pos, "expected `:` after the key"));
+            }
+            self.pos += 1;
+            self.skip_spaces();
+            let value = match self.peek() {
+                Some(b',' | b'}') => Node::new(Value::Null, self.span(self.pos)),
+                _ => self.parse_value(true)?,
+            };
+            entries.push(Entry {
+                key,
+                key_span,
+                value,
+            });
+
+            self.skip_spaces();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => break,
+                None => return Err(self.error(start, "unterminated flow mapping, expected `}`")),
+                Some(_) => return Err(self.error(self.pos, "expected `,` or `}`")),
+            }
+        }
+        self.pos += 1;
+        let mut node = Node::new(Value::Mapping(entries), self.span(start));
+        node.flow = true;
+        Ok(node)
+    }
+
+    fn parse_single_quoted(&mut self) -> Result<Node, DevSpinError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut text = String::new();
+        let line = self.parser.lines[self.line];
+        loop {
+            let Some(b) = self.peek() else {
+                return Err(self.error(start, "unterminated single-quoted string"));
+            };
+            if b == b'\'' {
+                if self.bytes().get(self.pos + 1) == Some(&b'\'') && self.pos + 1 < self.end {
+                    text.push('\'');
+                    self.pos += 2;
+                    continue;
+                }
+                self.pos += 1;
+                break;
+            }
+            let ch = line[self.pos..].chars().next().unwrap_or_default();
+            text.push(ch);
+            self.pos += ch.len_utf8();
+        }
+        Ok(Node::new(
+            Value::Scalar(text, ScalarStyle::SingleQuoted),
+            self.span(start),
+        ))
+    }
+
+    fn parse_double_quoted(&mut self) -> Result<Node, DevSpinError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut text = String::new();
+        let line = self.parser.lines[self.line];
+        loop {
+            let Some(b) = self.peek() else {
+                return Err(self.error(start, "unterminated double-quoted string"));
+            };
+            match b {
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'\\' => {
+                    let escape_at = self.pos;
+                    self.pos += 1;
+                    let Some(code) = self.peek() else {
+                        return Err(self.error(start, "unterminated double-quoted string"));
+                    };
+                    self.pos += 1;
+                    match code {
+                        b'n' => text.push('\n'),
+                        b't' => text.push('\t'),
+                        b'r' => text.push('\r'),
+                        b'0' => text.push('\0'),
+                        b'"' => text.push('"'),
+                        b'\\' => text.push('\\'),
+                        b'/' => text.push('/'),
+                        b' ' => text.push(' '),
+                        b'x' | b'u' | b'U' => {
+                            let len = match code {
+                                b'x' => 2,
+                                b'u' => 4,
+                                _ => 8,
+                            };
+                            let digits = line.get(self.pos..self.pos + len).unwrap_or("");
+                            let ch = u32::from_str_radix(digits, 16)
+                                .ok()
+                                .filter(|_| digits.len() == len && self.pos + len <= self.end)
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error(escape_at, "invalid unicode escape"))?;
+                            text.push(ch);
+                            self.pos += len;
+                        }
+                        _ => return Err(self.error(escape_at, "unknown escape sequence")),
+                    }
+                }
+                _ => {
+                    let ch = line[self.pos..].chars().next().unwrap_or_default();
+                    text.push(ch);
+                    self.pos += ch.len_utf8();
+                }
+            }
+        }
+        Ok(Node::new(
+            Value::Scalar(text, ScalarStyle::DoubleQuoted),
+            self.span(start),
+        ))
+    }
+}
+
+/// Joins the lines of a folded block scalar: a single line break between two
+/// regular lines becomes a space, while empty and more-indented lines keep
+/// their line breaks.
+fn fold_lines(lines: &[&str]) -> String {
+    let mut text = String::new();
+    let mut empty_lines = 0;
+    let mut previous_regular = None;
+
+    for line in lines {
+        if line.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+        let regular = !line.starts_with([' ', '\t']);
+        match previous_regular {
+            None => text.push_str(&"\n".repeat(empty_lines)),
+            Some(previous) if previous && regular => {
+                if empty_lines == 0 {
+                    text.push(' ');
+                } else {
+                    text.push_str(&"\n".repeat(empty_lines));
+                }
+            }
+            Some(_) => text.push_str(&"\n".repeat(empty_lines + 1)),
+        }
+        text.push_str(line);
+        empty_lines = 0;
+        previous_regular = Some(regular);
+    }
+    text
+}
+
+fn is_sequence_entry(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Result of splitting `key: rest`. Offsets are relative to the line content.
+struct KeySplit {
+    key_end: usize,
+    rest: Option<usize>,
+}
+
+impl KeySplit {
+    fn key(&self, parser: &Parser, i: usize) -> Result<String, DevSpinError> {
+        let start = parser.starts[i];
+        let raw = &parser.content(i)[..self.key_end];
+        if raw.starts_with('"') || raw.starts_with('\'') {
+            let mut flow = FlowParser {
+                parser,
+                line: i,
+                pos: start,
+                end: start + self.key_end,
+            };
+            let node = flow.parse_value(false)?;
+            return Ok(node.as_str().unwrap_or_default().to_string());
+        }
+        Ok(raw.to_string())
+    }
+}
+
+/// Detects a block mapping entry: a (possibly quoted) key followed by `:` and a
+/// space or the end of the line.
+fn split_key(content: &str) -> Option<KeySplit> {
+    let bytes = content.as_bytes();
+    let key_end = match bytes.first()? {
+        b'[' | b'{' | b'|' | b'>' | b'#' | b'&' | b'*' | b'!' | b'?' | b'%' | b'@' | b'`' => {
+            return None
+        }
+        quote @ (b'"' | b'\'') => {
+            let mut i = 1;
+            loop {
+                match bytes.get(i)? {
+                    b'\\' if *quote == b'"' => i += 2,
+                    b if b == quote => {
+                        if *quote == b'\'' && bytes.get(i + 1) == Some(&b'\'') {
+                            i += 2;
+                        } else {
+                            break i + 1;
+                        }
+                    }
+                    _ => i += 1,
+                }
+            }
+        }
+        _ => {
+            let colon = content
+                .match_indices(':')
+                .map(|(idx, _)| idx)
+                .find(|&idx| matches!(bytes.get(idx + 1), None | Some(b' ')))?;
+            content[..colon].trim_end().len()
+        }
+    };
+    if key_end == 0 {
+        return None;
+    }
+
+    let after_key = content[key_end..].trim_start_matches(' ');
+    let after_colon = after_key.strip_prefix(':')?;
+    if !(after_colon.is_empty() || after_colon.starts_with(' ')) {
+        return None;
+    }
+    let rest = after_colon.trim_start();
+    Some(KeySplit {
+        key_end,
+        rest: (!rest.is_empty()).then(|| content.len() - rest.len()),
+    })
+}
+
+/// Whether the content of a line ends with a `|` or `>` block scalar header,
+/// as a mapping value or a sequence entry.
+fn ends_with_block_header(content: &str) -> bool {
+    let rest = content.trim_end_matches(|c: char| matches!(c, '-' | '+' | '1'..='9'));
+    let Some(before) = rest.strip_suffix(['|', '>']) else {
+        return false;
+    };
+    let before = before.trim_end_matches(' ');
+    before.is_empty() || before.ends_with(':') || before == "-" || before.ends_with(" -")
+}
+
+/// Byte offset where a trailing `#` comment starts, or the line length.
+fn comment_start(line: &str) -> usize {
+    let bytes = line.as_bytes();
+    let mut token_start = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match b {
+            b'#' if i == 0 || matches!(bytes[i - 1], b' ' | b'\t') => return i,
+            b'"' | b'\'' if token_start => {
+                i += 1;
+                while i < bytes.len() {
+                    if b == b'"' && bytes[i] == b'\\' {
+                        i += 2;
+                        continue;
+                    }
+                    if bytes[i] == b {
+                        if b == b'\'' && bytes.get(i + 1) == Some(&b'\'') {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                token_start = false;
+            }
+            b' ' | b'\t' => {}
+            b'[' | b'{' | b',' => token_start = true,
+            b':' | b'-' if matches!(bytes.get(i + 1), None | Some(b' ')) => token_start = true,
+            _ => token_start = false,
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Formats `text` as a YAML value for a key indented by `parent_indent` spaces.
+/// Multi-line text becomes a literal block scalar; single-line text is quoted
+/// only when it would not read back as the same plain scalar.
+pub fn format_value(text: &str, parent_indent: usize) -> String {
+    let multi_line = text.contains('\n') && !text.trim_matches('\n').is_empty();
+    if multi_line && !text.contains(['\r', '\t']) {
+        return format_block_scalar(text, parent_indent);
+    }
+    if needs_quotes(text) {
+        return format_double_quoted(text);
+    }
+    text.to_string()
+}
+
+/// Formats `text` as an item of a flow collection, where `,`, `[`, `]`, `{`
+/// and `}` also require quotes.
+pub fn format_flow_value(text: &str) -> String {
+    if needs_quotes(text) || text.contains([',', '[', ']', '{', '}', '\n']) {
+        return format_double_quoted(text);
+    }
+    text.to_string()
+}
+
+fn format_block_scalar(text: &str, parent_indent: usize) -> String {
+    let (chomping, body) = match text.strip_suffix('\n') {
+        Some(body) if body.ends_with('\n') || body.is_empty() => ("+", body),
+        Some(body) => ("", body),
+        None => ("-", text),
+    };
+    let indicator = if body.starts_with(' ') { "2" } else { "" };
+    let indent = " ".repeat(parent_indent + 2);
+
+    let mut output = format!("|{}{}", indicator, chomping);
+    for line in body.split('\n') {
+        output.push('\n');
+        if !line.is_empty() {
+            output.push_str(&indent);
+            output.push_str(line);
+        }
+    }
+    output
+}
+
+fn needs_quotes(text: &str) -> bool {
+    let Some(first) = text.chars().next() else {
+        return true;
+    };
+    text.trim() != text
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.ends_with(':')
+        || text.chars().any(char::is_control)
+        || matches!(
+            first,
+            '[' | ']'
+                | '{'
+                | '}'
+                | ','
+                | '#'
+                | '&'
+                | '*'
+                | '!'
+                | '|'
+                | '>'
+                | '\''
+                | '"'
+                | '%'
+                | '@'
+                | '`'
+                | '?'
+                | ':'
+        )
+        || (first == '-' && (text.len() == 1 || text[1..].starts_with(' ')))
+}
+
+/// Formats `text` as a double-quoted scalar, for files read by tools that
+/// would turn plain scalars such as `5432:5432` or `yes` into other types.
+pub fn format_double_quoted(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+    output.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            '\r' => output.push_str("\\r"),
+            ch if ch.is_control() => output.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => output.push(ch),
+        }
+    }
+    output.push('"');
+    output
+}
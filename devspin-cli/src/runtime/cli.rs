@@ -0,0 +1,424 @@
+use super::{ContainerRuntime, ContainerSpec, ContainerState, ContainerSummary, ExecOutput};
+use crate::prelude::*;
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Container engines driven through their CLI. Podman accepts the same
+/// arguments as docker for everything devspin uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeKind {
+    Docker,
+    Podman,
+}
+
+impl RuntimeKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "docker" => Some(RuntimeKind::Docker),
+            "podman" => Some(RuntimeKind::Podman),
+            _ => None,
+        }
+    }
+
+    /// Executable looked up on PATH
+    pub fn binary(&self) -> &'static str {
+        match self {
+            RuntimeKind::Docker => "docker",
+            RuntimeKind::Podman => "podman",
+        }
+    }
+}
+
+/// Runtime shelling out to the docker or podman binary
+#[derive(Debug, Clone)]
+pub struct CliRuntime {
+    kind: RuntimeKind,
+    binary: PathBuf,
+}
+
+impl CliRuntime {
+    pub fn new(kind: RuntimeKind, binary: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            binary: binary.into(),
+        }
+    }
+
+    pub fn kind(&self) -> RuntimeKind {
+        self.kind
+    }
+
+    /// Run the binary with `args` and capture its output, whatever its exit status
+    async fn output(&self, args: &[String]) -> Result<Output, ProcessError> {
+        Command::new(&self.binary)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| ProcessError::RuntimeFailed {
+                error_msg: format!("Failed to run {}: {}", self.binary.display(), e),
+                exit_status: 127,
+            })
+    }
+
+    /// Run the binary with `args` and return its stdout, failing on a non-zero exit
+    async fn run(&self, args: &[String]) -> Result<String, ProcessError> {
+        let output = self.output(args).await?;
+        if !output.status.success() {
+            // `volume create` / `network rm` name their group, everything else one word
+            let words = match args.first().map(String::as_str) {
+                Some("volume" | "network") => 2,
+                _ => 1,
+            };
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!(
+                    "`{} {}` failed: {}",
+                    self.kind.binary(),
+                    args[..words.min(args.len())].join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                exit_status: 500,
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Arguments of `create` for `spec`, its variables read from `env_file`
+pub fn create_args(spec: &ContainerSpec, env_file: Option<&Path>) -> Vec<String> {
+    let mut args = vec!["create".to_string()];
+    args.extend(spec_args(spec, env_file));
+    args
+}
+
+/// Arguments of `run_attached` for `spec`, with a terminal when `tty` is set
+pub fn run_args(spec: &ContainerSpec, tty: bool, env_file: Option<&Path>) -> Vec<String> {
+    let mut args = strings(&["run", "--rm", "--interactive"]);
+    if tty {
+        args.push("--tty".to_string());
+    }
+    args.extend(spec_args(spec, env_file));
+    args
+}
+
+/// The variables of a container in a file only the user can read, for
+/// `--env-file`: the values stay out of the process list, and out of the
+/// environment of the runtime CLI, where `DOCKER_HOST` or `HOME` would change
+/// what it talks to. The file is deleted when dropped; `None` without variables.
+pub fn env_file(env: &IndexMap<String, String>) -> Result<Option<NamedTempFile>, ProcessError> {
+    if env.is_empty() {
+        return Ok(None);
+    }
+    let mut content = String::new();
+    for (key, value) in env {
+        // One variable per line, a line break would start another one
+        if key.contains(['\n', '\r']) || value.contains(['\n', '\r']) {
+            return Err(ProcessError::InvalidConfig {
+                error_msg: format!(
+                    "variable `{}` holds a line break, which cannot be passed to a container",
+                    key.escape_debug()
+                ),
+                exit_status: 422,
+            });
+        }
+        content.push_str(&format!("{}={}\n", key, value));
+    }
+    let failed = |e: std::io::Error| ProcessError::RuntimeFailed {
+        error_msg: format!("Failed to write the variables of the container: {}", e),
+        exit_status: 500,
+    };
+    let mut file = tempfile::Builder::new()
+        .prefix("devspin-env-")
+        .tempfile()
+        .map_err(failed)?;
+    file.write_all(content.as_bytes()).map_err(failed)?;
+    Ok(Some(file))
+}
+
+fn spec_args(spec: &ContainerSpec, env_file: Option<&Path>) -> Vec<String> {
+    let mut args = vec!["--name".to_string(), spec.name.clone()];
+    args.extend(label_args(&spec.labels));
+    for port in &spec.ports {
+        args.push("--publish".to_string());
+        args.push(port.clone());
+    }
+    if let Some(env_file) = env_file {
+        args.push("--env-file".to_string());
+        args.push(env_file.display().to_string());
+    }
+    if let Some(workdir) = &spec.workdir {
+        args.extend(["--workdir".to_string(), workdir.clone()]);
+    }
+    if let Some(hostname) = &spec.hostname {
+        args.extend(["--hostname".to_string(), hostname.clone()]);
+    }
+    for volume in &spec.volumes {
+        args.push("--volume".to_string());
+        args.push(volume.clone());
+    }
+    // Other networks are joined with `network connect` once the container exists
+    if let Some(network) = spec.networks.first() {
+        args.extend(["--network".to_string(), network.clone()]);
+        args.extend(["--network-alias".to_string(), spec.alias.clone()]);
+    }
+    if let Some(restart) = &spec.restart {
+        args.extend(["--restart".to_string(), restart.clone()]);
+    }
+    // The entrypoint flag only takes the executable, its arguments go before the command
+    if let Some((executable, entrypoint_args)) = spec.entrypoint.split_first() {
+        args.extend(["--entrypoint".to_string(), executable.clone()]);
+        args.push(spec.image.clone());
+        args.extend_from_slice(entrypoint_args);
+    } else {
+        args.push(spec.image.clone());
+    }
+    args.extend_from_slice(&spec.command);
+    args
+}
+
+fn label_args(labels: &IndexMap<String, String>) -> Vec<String> {
+    labels
+        .iter()
+        .flat_map(|(key, value)| ["--label".to_string(), format!("{}={}", key, value)])
+        .collect()
+}
+
+fn names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[async_trait]
+impl ContainerRuntime for CliRuntime {
+    fn name(&self) -> &str {
+        self.kind.binary()
+    }
+
+    async fn pull(&self, image: &str) -> Result<(), ProcessError> {
+        self.run(&strings(&["pull", image])).await.map(drop)
+    }
+
+    async fn create(&self, spec: &ContainerSpec) -> Result<String, ProcessError> {
+        let env_file = env_file(&spec.env)?;
+        let args = create_args(spec, env_file.as_ref().map(NamedTempFile::path));
+        let id = self.run(&args).await?;
+        Ok(id.trim().to_string())
+    }
+
+    async fn start(&self, container: &str) -> Result<(), ProcessError> {
+        self.run(&strings(&["start", container])).await.map(drop)
+    }
+
+    async fn stop(&self, container: &str) -> Result<(), ProcessError> {
+        self.run(&strings(&["stop", container])).await.map(drop)
+    }
+
+    async fn logs(&self, container: &str, tail: usize) -> Result<String, ProcessError> {
+        let args = strings(&["logs", "--tail", &tail.to_string(), container]);
+        let output = self.output(&args).await?;
+        // Containers write to both streams; keep stdout first as the runtime does not interleave them
+        Ok(format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+
+    async fn inspect(&self, container: &str) -> Result<ContainerState, ProcessError> {
+        let format = "{{.Id}}\t{{.Name}}\t{{.State.Status}}\t{{.State.ExitCode}}";
+        let output = self
+            .run(&strings(&["inspect", "--format", format, container]))
+            .await?;
+        let fields: Vec<&str> = output.trim().split('\t').collect();
+        let [id, name, status, exit_code] = fields[..] else {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!("Unexpected inspect output for {}: {}", container, output),
+                exit_status: 500,
+            });
+        };
+        Ok(ContainerState {
+            id: id.to_string(),
+            name: name.trim_start_matches('/').to_string(),
+            status: status.to_string(),
+            exit_code: exit_code.parse().unwrap_or(-1),
+        })
+    }
+
+    async fn remove(&self, container: &str, volumes: bool) -> Result<(), ProcessError> {
+        let mut args = strings(&["rm", "--force"]);
+        if volumes {
+            args.push("--volumes".to_string());
+        }
+        args.push(container.to_string());
+        self.run(&args).await.map(drop)
+    }
+
+    async fn exec(&self, container: &str, command: &[String]) -> Result<ExecOutput, ProcessError> {
+        let mut args = strings(&["exec", container]);
+        args.extend_from_slice(command);
+        let output = self.output(&args).await?;
+        Ok(ExecOutput {
+            exit_code: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+
+    async fn list(&self, key: &str, value: &str) -> Result<Vec<ContainerSummary>, ProcessError> {
+        let filter = format!("label={}={}", key, value);
+        let format = "{{.ID}}\t{{.Names}}\t{{.State}}";
+        let output = self
+            .run(&strings(&[
+                "ps", "--all", "--filter", &filter, "--format", format,
+            ]))
+            .await?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                Some(ContainerSummary {
+                    id: fields.next()?.to_string(),
+                    name: fields.next()?.to_string(),
+                    status: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn create_volume(
+        &self,
+        name: &str,
+        labels: &IndexMap<String, String>,
+    ) -> Result<(), ProcessError> {
+        let mut args = strings(&["volume", "create"]);
+        args.extend(label_args(labels));
+        args.push(name.to_string());
+        self.run(&args).await.map(drop)
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), ProcessError> {
+        self.run(&strings(&["volume", "rm", name])).await.map(drop)
+    }
+
+    async fn list_volumes(&self, key: &str, value: &str) -> Result<Vec<String>, ProcessError> {
+        let filter = format!("label={}={}", key, value);
+        let output = self
+            .run(&strings(&[
+                "volume",
+                "ls",
+                "--filter",
+                &filter,
+                "--format",
+                "{{.Name}}",
+            ]))
+            .await?;
+        Ok(names(&output))
+    }
+
+    async fn create_network(
+        &self,
+        name: &str,
+        labels: &IndexMap<String, String>,
+    ) -> Result<(), ProcessError> {
+        let mut args = strings(&["network", "create"]);
+        args.extend(label_args(labels));
+        args.push(name.to_string());
+        self.run(&args).await.map(drop)
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), ProcessError> {
+        self.run(&strings(&["network", "rm", name])).await.map(drop)
+    }
+
+    async fn list_networks(&self, key: &str, value: &str) -> Result<Vec<String>, ProcessError> {
+        let filter = format!("label={}={}", key, value);
+        let output = self
+            .run(&strings(&[
+                "network",
+                "ls",
+                "--filter",
+                &filter,
+                "--format",
+                "{{.Name}}",
+            ]))
+            .await?;
+        Ok(names(&output))
+    }
+
+    async fn connect_network(
+        &self,
+        network: &str,
+        container: &str,
+        alias: &str,
+    ) -> Result<(), ProcessError> {
+        let args = strings(&["network", "connect", "--alias", alias, network, container]);
+        self.run(&args).await.map(drop)
+    }
+
+    async fn build(&self, tag: &str, dockerfile: &str) -> Result<(), ProcessError> {
+        // The Dockerfile comes from stdin; the context is an empty directory
+        let context = std::env::temp_dir().join(format!("devspin-build-{}", std::process::id()));
+        std::fs::create_dir_all(&context).map_err(|e| ProcessError::RuntimeFailed {
+            error_msg: format!("Failed to create {}: {}", context.display(), e),
+            exit_status: 500,
+        })?;
+        let args = strings(&["build", "--tag", tag, "--file", "-"]);
+        let status = async {
+            let mut child = Command::new(&self.binary)
+                .args(&args)
+                .arg(&context)
+                .stdin(Stdio::piped())
+                .spawn()?;
+            let mut stdin = child.stdin.take().expect("stdin is piped");
+            stdin.write_all(dockerfile.as_bytes()).await?;
+            drop(stdin);
+            child.wait().await
+        }
+        .await;
+        let _ = std::fs::remove_dir_all(&context);
+
+        let status = status.map_err(|e| ProcessError::RuntimeFailed {
+            error_msg: format!("Failed to run {}: {}", self.binary.display(), e),
+            exit_status: 127,
+        })?;
+        if !status.success() {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!("`{} build` of {} failed", self.kind.binary(), tag),
+                exit_status: 500,
+            });
+        }
+        Ok(())
+    }
+
+    async fn run_attached(&self, spec: &ContainerSpec) -> Result<i32, ProcessError> {
+        let env_file = env_file(&spec.env)?;
+        let args = run_args(
+            spec,
+            std::io::stdin().is_terminal(),
+            env_file.as_ref().map(NamedTempFile::path),
+        );
+        // `env_file` is deleted once the container has exited
+        let status = Command::new(&self.binary)
+            .args(args)
+            .status()
+            .await
+            .map_err(|e| ProcessError::RuntimeFailed {
+                error_msg: format!("Failed to run {}: {}", self.binary.display(), e),
+                exit_status: 127,
+            })?;
+        Ok(status.code().unwrap_or(-1))
+    }
+}
@@ -0,0 +1,498 @@
+use super::{ContainerRuntime, ContainerSpec, ContainerState, ContainerSummary, ExecOutput};
+use crate::prelude::*;
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A call made to [`FakeRuntime`], recorded in order
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeCall {
+    Pull(String),
+    Create(String),
+    Start(String),
+    Stop(String),
+    Logs(String),
+    Inspect(String),
+    Remove {
+        container: String,
+        volumes: bool,
+    },
+    Exec {
+        container: String,
+        command: Vec<String>,
+    },
+    List {
+        key: String,
+        value: String,
+    },
+    CreateVolume(String),
+    RemoveVolume(String),
+    ListVolumes {
+        key: String,
+        value: String,
+    },
+    CreateNetwork(String),
+    RemoveNetwork(String),
+    ListNetworks {
+        key: String,
+        value: String,
+    },
+    ConnectNetwork {
+        network: String,
+        container: String,
+        alias: String,
+    },
+    Build {
+        tag: String,
+        dockerfile: String,
+    },
+    RunAttached(String),
+}
+
+#[derive(Debug, Clone)]
+struct FakeContainer {
+    id: String,
+    spec: ContainerSpec,
+    status: String,
+    exit_code: i32,
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    calls: Vec<RuntimeCall>,
+    /// Containers by name, in creation order
+    containers: IndexMap<String, FakeContainer>,
+    logs: HashMap<String, String>,
+    exec_results: HashMap<String, ExecOutput>,
+    missing_images: Vec<String>,
+    /// Exit code of containers that stop as soon as they start
+    crashing: HashMap<String, i32>,
+    /// Exit code of `run_attached`, 0 by default
+    attached_exit_code: i32,
+    /// Specs passed to `run_attached`
+    attached: Vec<ContainerSpec>,
+    /// Labels of each volume and network, by name
+    volumes: IndexMap<String, IndexMap<String, String>>,
+    networks: IndexMap<String, IndexMap<String, String>>,
+    next_id: usize,
+}
+
+impl FakeState {
+    fn find(&self, container: &str) -> Result<&FakeContainer, ProcessError> {
+        self.containers
+            .values()
+            .find(|c| c.id == container || c.spec.name == container)
+            .ok_or_else(|| no_such_container(container))
+    }
+
+    fn find_mut(&mut self, container: &str) -> Result<&mut FakeContainer, ProcessError> {
+        self.containers
+            .values_mut()
+            .find(|c| c.id == container || c.spec.name == container)
+            .ok_or_else(|| no_such_container(container))
+    }
+}
+
+fn no_such_container(container: &str) -> ProcessError {
+    ProcessError::RuntimeFailed {
+        error_msg: format!("No such container: {}", container),
+        exit_status: 404,
+    }
+}
+
+/// In-memory runtime recording every call, so tests can assert on exactly
+/// which containers would be created and started without a daemon
+#[derive(Debug, Default)]
+pub struct FakeRuntime {
+    state: Mutex<FakeState>,
+}
+
+impl FakeRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output returned by `logs` for `container` (a container name)
+    pub fn with_logs(self, container: &str, logs: &str) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.logs.insert(container.to_string(), logs.to_string());
+        drop(state);
+        self
+    }
+
+    /// Result of every `exec` in `container`. Commands succeed by default.
+    pub fn with_exec_result(self, container: &str, output: ExecOutput) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.exec_results.insert(container.to_string(), output);
+        drop(state);
+        self
+    }
+
+    /// Make `pull` fail for `image`
+    pub fn with_missing_image(self, image: &str) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .missing_images
+            .push(image.to_string());
+        self
+    }
+
+    /// Make `container` exit with `exit_code` right after it starts
+    pub fn with_crash(self, container: &str, exit_code: i32) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.crashing.insert(container.to_string(), exit_code);
+        drop(state);
+        self
+    }
+
+    /// Make `run_attached` return `exit_code`
+    pub fn with_attached_exit_code(self, exit_code: i32) -> Self {
+        self.state.lock().unwrap().attached_exit_code = exit_code;
+        self
+    }
+
+    /// Add an existing container, as if created by an earlier run
+    pub fn with_container(self, spec: ContainerSpec, running: bool) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let container = FakeContainer {
+            id: format!("fake-{}", state.next_id),
+            status: if running { "running" } else { "created" }.to_string(),
+            exit_code: 0,
+            spec,
+        };
+        state
+            .containers
+            .insert(container.spec.name.clone(), container);
+        drop(state);
+        self
+    }
+
+    /// Add an existing volume
+    pub fn with_volume(self, name: &str, labels: IndexMap<String, String>) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.volumes.insert(name.to_string(), labels);
+        drop(state);
+        self
+    }
+
+    /// Add an existing network
+    pub fn with_network(self, name: &str, labels: IndexMap<String, String>) -> Self {
+        let mut state = self.state.lock().unwrap();
+        state.networks.insert(name.to_string(), labels);
+        drop(state);
+        self
+    }
+
+    /// Names of the existing volumes, in creation order
+    pub fn volumes(&self) -> Vec<String> {
+        self.state.lock().unwrap().volumes.keys().cloned().collect()
+    }
+
+    /// Names of the existing networks, in creation order
+    pub fn networks(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .networks
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    pub fn calls(&self) -> Vec<RuntimeCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Specs of the containers currently running, in creation order
+    pub fn running(&self) -> Vec<ContainerSpec> {
+        self.state
+            .lock()
+            .unwrap()
+            .containers
+            .values()
+            .filter(|c| c.status == "running")
+            .map(|c| c.spec.clone())
+            .collect()
+    }
+
+    /// Specs passed to `run_attached`, in call order
+    pub fn attached(&self) -> Vec<ContainerSpec> {
+        self.state.lock().unwrap().attached.clone()
+    }
+
+    /// Names of every existing container, in creation order
+    pub fn container_names(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .containers
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn record(&self, call: RuntimeCall) -> std::sync::MutexGuard<'_, FakeState> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(call);
+        state
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for FakeRuntime {
+    fn name(&self) -> &str {
+        "fake"
+    }
+
+    async fn pull(&self, image: &str) -> Result<(), ProcessError> {
+        let state = self.record(RuntimeCall::Pull(image.to_string()));
+        if state.missing_images.iter().any(|missing| missing == image) {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!("pull access denied for {}", image),
+                exit_status: 404,
+            });
+        }
+        Ok(())
+    }
+
+    async fn create(&self, spec: &ContainerSpec) -> Result<String, ProcessError> {
+        let mut state = self.record(RuntimeCall::Create(spec.name.clone()));
+        if state.containers.contains_key(&spec.name) {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!(
+                    "Conflict. The container name {} is already in use",
+                    spec.name
+                ),
+                exit_status: 409,
+            });
+        }
+        state.next_id += 1;
+        let id = format!("fake-{}", state.next_id);
+        state.containers.insert(
+            spec.name.clone(),
+            FakeContainer {
+                id: id.clone(),
+                spec: spec.clone(),
+                status: "created".to_string(),
+                exit_code: 0,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn start(&self, container: &str) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::Start(container.to_string()));
+        let name = state.find(container)?.spec.name.clone();
+        let crash = state.crashing.get(&name).copied();
+        let found = state.find_mut(container)?;
+        match crash {
+            Some(exit_code) => {
+                found.status = "exited".to_string();
+                found.exit_code = exit_code;
+            }
+            None => found.status = "running".to_string(),
+        }
+        Ok(())
+    }
+
+    async fn stop(&self, container: &str) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::Stop(container.to_string()));
+        state.find_mut(container)?.status = "exited".to_string();
+        Ok(())
+    }
+
+    async fn logs(&self, container: &str, tail: usize) -> Result<String, ProcessError> {
+        let state = self.record(RuntimeCall::Logs(container.to_string()));
+        let name = state.find(container)?.spec.name.clone();
+        let logs = state.logs.get(&name).cloned().unwrap_or_default();
+        let lines: Vec<&str> = logs.lines().collect();
+        let start = lines.len().saturating_sub(tail);
+        Ok(lines[start..]
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect())
+    }
+
+    async fn inspect(&self, container: &str) -> Result<ContainerState, ProcessError> {
+        let state = self.record(RuntimeCall::Inspect(container.to_string()));
+        let found = state.find(container)?;
+        Ok(ContainerState {
+            id: found.id.clone(),
+            name: found.spec.name.clone(),
+            status: found.status.clone(),
+            exit_code: found.exit_code,
+        })
+    }
+
+    async fn remove(&self, container: &str, volumes: bool) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::Remove {
+            container: container.to_string(),
+            volumes,
+        });
+        let name = state.find(container)?.spec.name.clone();
+        state.containers.shift_remove(&name);
+        Ok(())
+    }
+
+    async fn exec(&self, container: &str, command: &[String]) -> Result<ExecOutput, ProcessError> {
+        let state = self.record(RuntimeCall::Exec {
+            container: container.to_string(),
+            command: command.to_vec(),
+        });
+        let name = state.find(container)?.spec.name.clone();
+        Ok(state
+            .exec_results
+            .get(&name)
+            .cloned()
+            .unwrap_or(ExecOutput {
+                exit_code: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+            }))
+    }
+
+    async fn list(&self, key: &str, value: &str) -> Result<Vec<ContainerSummary>, ProcessError> {
+        let state = self.record(RuntimeCall::List {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+        Ok(state
+            .containers
+            .values()
+            .filter(|c| c.spec.labels.get(key).is_some_and(|v| v == value))
+            .map(|c| ContainerSummary {
+                id: c.id.clone(),
+                name: c.spec.name.clone(),
+                status: c.status.clone(),
+            })
+            .collect())
+    }
+
+    async fn create_volume(
+        &self,
+        name: &str,
+        labels: &IndexMap<String, String>,
+    ) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::CreateVolume(name.to_string()));
+        state
+            .volumes
+            .entry(name.to_string())
+            .or_insert_with(|| labels.clone());
+        Ok(())
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::RemoveVolume(name.to_string()));
+        match state.volumes.shift_remove(name) {
+            Some(_) => Ok(()),
+            None => Err(ProcessError::RuntimeFailed {
+                error_msg: format!("No such volume: {}", name),
+                exit_status: 404,
+            }),
+        }
+    }
+
+    async fn list_volumes(&self, key: &str, value: &str) -> Result<Vec<String>, ProcessError> {
+        let state = self.record(RuntimeCall::ListVolumes {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+        Ok(labelled(&state.volumes, key, value))
+    }
+
+    async fn create_network(
+        &self,
+        name: &str,
+        labels: &IndexMap<String, String>,
+    ) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::CreateNetwork(name.to_string()));
+        if state.networks.contains_key(name) {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!("network with name {} already exists", name),
+                exit_status: 409,
+            });
+        }
+        state.networks.insert(name.to_string(), labels.clone());
+        Ok(())
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), ProcessError> {
+        let mut state = self.record(RuntimeCall::RemoveNetwork(name.to_string()));
+        match state.networks.shift_remove(name) {
+            Some(_) => Ok(()),
+            None => Err(ProcessError::RuntimeFailed {
+                error_msg: format!("network {} not found", name),
+                exit_status: 404,
+            }),
+        }
+    }
+
+    async fn list_networks(&self, key: &str, value: &str) -> Result<Vec<String>, ProcessError> {
+        let state = self.record(RuntimeCall::ListNetworks {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+        Ok(labelled(&state.networks, key, value))
+    }
+
+    async fn connect_network(
+        &self,
+        network: &str,
+        container: &str,
+        alias: &str,
+    ) -> Result<(), ProcessError> {
+        let state = self.record(RuntimeCall::ConnectNetwork {
+            network: network.to_string(),
+            container: container.to_string(),
+            alias: alias.to_string(),
+        });
+        state.find(container)?;
+        if !state.networks.contains_key(network) {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!("network {} not found", network),
+                exit_status: 404,
+            });
+        }
+        Ok(())
+    }
+
+    async fn build(&self, tag: &str, dockerfile: &str) -> Result<(), ProcessError> {
+        let state = self.record(RuntimeCall::Build {
+            tag: tag.to_string(),
+            dockerfile: dockerfile.to_string(),
+        });
+        if let Some(image) = dockerfile
+            .lines()
+            .find_map(|line| line.strip_prefix("FROM "))
+            .filter(|image| state.missing_images.iter().any(|missing| missing == image))
+        {
+            return Err(ProcessError::RuntimeFailed {
+                error_msg: format!("`fake build` of {} failed: no image {}", tag, image),
+                exit_status: 500,
+            });
+        }
+        Ok(())
+    }
+
+    async fn run_attached(&self, spec: &ContainerSpec) -> Result<i32, ProcessError> {
+        let mut state = self.record(RuntimeCall::RunAttached(spec.name.clone()));
+        state.attached.push(spec.clone());
+        Ok(state.attached_exit_code)
+    }
+}
+
+/// Names of the resources carrying the `key=value` label
+fn labelled(
+    resources: &IndexMap<String, IndexMap<String, String>>,
+    key: &str,
+    value: &str,
+) -> Vec<String> {
+    resources
+        .iter()
+        .filter(|(_, labels)| labels.get(key).is_some_and(|v| v == value))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
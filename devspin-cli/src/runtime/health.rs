@@ -0,0 +1,102 @@
+use super::ContainerRuntime;
+use crate::parser::devspin_generator::{Healthcheck, Probe, Service};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Host the published ports of containers are probed on
+const PROBE_HOST: &str = "127.0.0.1";
+
+/// Run one attempt of `healthcheck` against `container`, bounded by its timeout.
+/// The error describes why the service is not healthy yet.
+pub async fn probe(
+    runtime: &dyn ContainerRuntime,
+    container: &str,
+    service: &Service,
+    healthcheck: &Healthcheck,
+) -> Result<(), String> {
+    let attempt = async {
+        match &healthcheck.probe {
+            Probe::Command(command) => probe_command(runtime, container, command).await,
+            Probe::Tcp(port) => probe_tcp(host_port(service, *port)?).await,
+            Probe::Http { path, port } => {
+                let port = port
+                    .or_else(|| service.first_port())
+                    .ok_or("no port to probe")?;
+                probe_http(host_port(service, port)?, path).await
+            }
+        }
+    };
+    match tokio::time::timeout(healthcheck.timeout, attempt).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}", describe(healthcheck.timeout))),
+    }
+}
+
+fn host_port(service: &Service, container_port: u16) -> Result<u16, String> {
+    service
+        .host_port(container_port)
+        .ok_or_else(|| format!("port {} is not published on a host port", container_port))
+}
+
+fn describe(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
+
+async fn probe_command(
+    runtime: &dyn ContainerRuntime,
+    container: &str,
+    command: &str,
+) -> Result<(), String> {
+    let command = ["sh".to_string(), "-c".to_string(), command.to_string()];
+    let output = runtime
+        .exec(container, &command)
+        .await
+        .map_err(|e| e.to_string())?;
+    if output.success() {
+        return Ok(());
+    }
+    let detail = if output.stderr.trim().is_empty() {
+        output.stdout.trim()
+    } else {
+        output.stderr.trim()
+    };
+    Err(format!(
+        "command exited with {}: {}",
+        output.exit_code, detail
+    ))
+}
+
+async fn probe_tcp(port: u16) -> Result<(), String> {
+    TcpStream::connect((PROBE_HOST, port))
+        .await
+        .map(drop)
+        .map_err(|e| format!("cannot connect to port {}: {}", port, e))
+}
+
+async fn probe_http(port: u16, path: &str) -> Result<(), String> {
+    let mut stream = TcpStream::connect((PROBE_HOST, port))
+        .await
+        .map_err(|e| format!("cannot connect to port {}: {}", port, e))?;
+    let request = format!(
+        "GET {} HTTP/1.0\r\nHost: {}:{}\r\nConnection: close\r\n\r\n",
+        path, PROBE_HOST, port
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("GET {} failed: {}", path, e))?;
+
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .await
+        .map_err(|e| format!("GET {} failed: {}", path, e))?;
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1).map(str::parse::<u16>) {
+        Some(Ok(status)) if (200..400).contains(&status) => Ok(()),
+        Some(Ok(status)) => Err(format!("GET {} returned {}", path, status)),
+        _ => Err(format!("GET {} returned an invalid response", path)),
+    }
+}
@@ -0,0 +1,353 @@
+use super::{
+    health, project_labels, resource_name, sanitize_name, ContainerRuntime, ContainerSpec,
+    DEFAULT_NETWORK, PROJECT_LABEL,
+};
+use crate::parser::devspin_generator::{Service, VolumeMount};
+use crate::prelude::*;
+use crate::utils::dep_graph::dependency_order;
+use colored::*;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Lines of container output included when a service fails to start
+pub const LOG_TAIL: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct UpOptions {
+    /// Pull images before creating containers
+    pub pull: bool,
+    /// How long each container may take to start running
+    pub timeout: Duration,
+    /// Delay between two readiness checks
+    pub interval: Duration,
+}
+
+impl Default for UpOptions {
+    fn default() -> Self {
+        Self {
+            pull: true,
+            timeout: Duration::from_secs(60),
+            interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Where the project root is mounted in the `devspin shell --container` container
+pub const SHELL_WORKDIR: &str = "/app";
+
+/// What `up` did for a service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceAction {
+    Started,
+    AlreadyRunning,
+}
+
+/// What `down` removed
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Removed {
+    pub containers: Vec<String>,
+    pub networks: Vec<String>,
+    pub volumes: Vec<String>,
+}
+
+/// The containers, networks and volumes of one devspin project, found through
+/// their `devspin.project` label
+pub struct Environment<'a> {
+    runtime: &'a dyn ContainerRuntime,
+    project: String,
+    root: PathBuf,
+}
+
+impl<'a> Environment<'a> {
+    pub fn new(runtime: &'a dyn ContainerRuntime, project: impl Into<String>) -> Self {
+        Self {
+            runtime,
+            project: project.into(),
+            root: std::env::current_dir().unwrap_or_default(),
+        }
+    }
+
+    /// Project root relative bind mounts are resolved against (defaults to the cwd)
+    pub fn with_root(mut self, root: &Path) -> Self {
+        self.root = root.to_path_buf();
+        self
+    }
+
+    /// Services in startup order: every service comes after its `depends_on`
+    pub fn startup_order<'s>(
+        &self,
+        services: &'s [Service],
+    ) -> Result<Vec<&'s Service>, ProcessError> {
+        let graph: IndexMap<String, Vec<String>> = services
+            .iter()
+            .map(|service| (service.name.clone(), service.depends_on.clone()))
+            .collect();
+        let order = dependency_order(&graph, &[]).map_err(|e| ProcessError::InvalidConfig {
+            error_msg: format!("Cannot start services: {}", e),
+            exit_status: 422,
+        })?;
+        Ok(order
+            .iter()
+            .filter_map(|name| services.iter().find(|service| &service.name == name))
+            .collect())
+    }
+
+    /// Start every service after its dependencies and wait until each one is
+    /// ready. Running containers are kept; stopped ones are recreated from the
+    /// current config.
+    pub async fn up(
+        &self,
+        services: &[Service],
+        options: &UpOptions,
+    ) -> Result<Vec<(String, ServiceAction)>, ProcessError> {
+        let services = self.startup_order(services)?;
+        let specs: Vec<ContainerSpec> = services
+            .iter()
+            .map(|service| ContainerSpec::for_service(&self.project, service).with_root(&self.root))
+            .collect();
+        self.create_networks(&specs).await?;
+        self.create_volumes(&services).await?;
+
+        let existing = self.runtime.list(PROJECT_LABEL, &self.project).await?;
+        let mut actions = Vec::with_capacity(services.len());
+
+        for (service, spec) in services.into_iter().zip(specs) {
+            match existing.iter().find(|c| c.name == spec.name) {
+                Some(container) if container.status == "running" => {
+                    println!("{} {} already running", "✔".green(), service.name.bold());
+                    actions.push((service.name.clone(), ServiceAction::AlreadyRunning));
+                    continue;
+                }
+                Some(container) => self.runtime.remove(&container.id, false).await?,
+                None => {}
+            }
+
+            println!(
+                "{} {} ({})",
+                "▶ Starting".cyan().bold(),
+                service.name.bold(),
+                service.image
+            );
+            if options.pull {
+                self.runtime.pull(&spec.image).await?;
+            }
+            let id = self.runtime.create(&spec).await?;
+            for network in spec.networks.iter().skip(1) {
+                self.runtime
+                    .connect_network(network, &id, &spec.alias)
+                    .await?;
+            }
+            self.runtime.start(&id).await?;
+            self.wait_running(service, &id, options).await?;
+            if service.healthcheck.is_some() {
+                println!("  waiting for {} to be healthy", service.name);
+                self.wait_healthy(service, &id).await?;
+            }
+
+            println!("{} {} ready", "✔".green(), service.name.bold());
+            actions.push((service.name.clone(), ServiceAction::Started));
+        }
+        Ok(actions)
+    }
+
+    /// Create the project networks the containers join, unless they exist
+    async fn create_networks(&self, specs: &[ContainerSpec]) -> Result<(), ProcessError> {
+        let existing = self
+            .runtime
+            .list_networks(PROJECT_LABEL, &self.project)
+            .await?;
+        let mut created: Vec<&String> = Vec::new();
+        for network in specs.iter().flat_map(|spec| &spec.networks) {
+            if existing.contains(network) || created.contains(&network) {
+                continue;
+            }
+            self.runtime
+                .create_network(network, &project_labels(&self.project))
+                .await?;
+            created.push(network);
+        }
+        Ok(())
+    }
+
+    /// Create the named volumes up front so they carry the project label
+    async fn create_volumes(&self, services: &[&Service]) -> Result<(), ProcessError> {
+        for service in services {
+            for volume in &service.volumes {
+                if let Ok(VolumeMount::Named { name, .. }) = VolumeMount::parse(volume) {
+                    let name = super::resource_name(&self.project, &name);
+                    self.runtime
+                        .create_volume(&name, &project_labels(&self.project))
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Image the project shell container runs, e.g. `shop-shell`
+    pub fn shell_image(&self) -> String {
+        format!("{}-shell", sanitize_name(&self.project).to_lowercase())
+    }
+
+    /// Build `dockerfile` as the project shell image and run `command` in a
+    /// throwaway container with the project root mounted at [`SHELL_WORKDIR`].
+    /// The container joins the project `default` network when `up` created it,
+    /// so services are reachable by name. Returns the exit code of `command`.
+    pub async fn shell(
+        &self,
+        dockerfile: &str,
+        env: IndexMap<String, String>,
+        command: Vec<String>,
+    ) -> Result<i32, ProcessError> {
+        let image = self.shell_image();
+        println!("{} {}", "▶ Building".cyan().bold(), image.bold());
+        self.runtime.build(&image, dockerfile).await?;
+
+        let network = resource_name(&self.project, DEFAULT_NETWORK);
+        let networks = self
+            .runtime
+            .list_networks(PROJECT_LABEL, &self.project)
+            .await?;
+        let networks = if networks.contains(&network) {
+            vec![network]
+        } else {
+            eprintln!(
+                "{} services are not reachable from the shell, start them with `devspin up`",
+                "warning:".yellow().bold()
+            );
+            Vec::new()
+        };
+
+        let spec = ContainerSpec {
+            name: super::container_name(&self.project, &format!("shell-{}", std::process::id())),
+            image,
+            env,
+            labels: project_labels(&self.project),
+            volumes: vec![format!("{}:{}", self.root.display(), SHELL_WORKDIR)],
+            command,
+            networks,
+            workdir: Some(SHELL_WORKDIR.to_string()),
+            alias: "shell".to_string(),
+            hostname: Some(sanitize_name(&self.project).to_lowercase()),
+            ..ContainerSpec::default()
+        };
+        self.runtime.run_attached(&spec).await
+    }
+
+    /// Block until every running service with a healthcheck passes it. Services
+    /// that are not running are returned so the caller can point at `devspin up`.
+    pub async fn wait_ready(&self, services: &[Service]) -> Result<Vec<String>, ProcessError> {
+        let existing = self.runtime.list(PROJECT_LABEL, &self.project).await?;
+        let mut not_running = Vec::new();
+        for service in services.iter().filter(|s| s.healthcheck.is_some()) {
+            let name = ContainerSpec::for_service(&self.project, service).name;
+            match existing.iter().find(|c| c.name == name) {
+                Some(container) if container.status == "running" => {
+                    self.wait_healthy(service, &container.id).await?
+                }
+                _ => not_running.push(service.name.clone()),
+            }
+        }
+        Ok(not_running)
+    }
+
+    /// Stop and remove every container and network of the project, and its
+    /// volumes (named and anonymous) when `volumes` is set
+    pub async fn down(&self, volumes: bool) -> Result<Removed, ProcessError> {
+        let mut removed = Removed::default();
+        for container in self.runtime.list(PROJECT_LABEL, &self.project).await? {
+            if container.status == "running" {
+                self.runtime.stop(&container.id).await?;
+            }
+            self.runtime.remove(&container.id, volumes).await?;
+            removed.containers.push(container.name);
+        }
+        for network in self
+            .runtime
+            .list_networks(PROJECT_LABEL, &self.project)
+            .await?
+        {
+            self.runtime.remove_network(&network).await?;
+            removed.networks.push(network);
+        }
+        if volumes {
+            for volume in self
+                .runtime
+                .list_volumes(PROJECT_LABEL, &self.project)
+                .await?
+            {
+                self.runtime.remove_volume(&volume).await?;
+                removed.volumes.push(volume);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Poll `inspect` until the container runs, failing with its last logs
+    /// when it exits or does not start within the timeout
+    async fn wait_running(
+        &self,
+        service: &Service,
+        id: &str,
+        options: &UpOptions,
+    ) -> Result<(), ProcessError> {
+        let started = Instant::now();
+        loop {
+            let state = self.runtime.inspect(id).await?;
+            if state.is_running() {
+                return Ok(());
+            }
+            if state.status == "exited" || state.status == "dead" {
+                let reason = format!("exited with code {}", state.exit_code);
+                return Err(self.not_ready(service, id, &reason).await);
+            }
+            if started.elapsed() >= options.timeout {
+                let reason = format!("was not running after {}s", options.timeout.as_secs());
+                return Err(self.not_ready(service, id, &reason).await);
+            }
+            tokio::time::sleep(options.interval).await;
+        }
+    }
+
+    /// Retry the service's healthcheck until it passes or runs out of retries
+    async fn wait_healthy(&self, service: &Service, id: &str) -> Result<(), ProcessError> {
+        let Some(healthcheck) = &service.healthcheck else {
+            return Ok(());
+        };
+
+        let mut last_failure = String::new();
+        for attempt in 1..=healthcheck.retries.max(1) {
+            match health::probe(self.runtime, id, service, healthcheck).await {
+                Ok(()) => return Ok(()),
+                Err(reason) => last_failure = reason,
+            }
+            if attempt < healthcheck.retries {
+                tokio::time::sleep(healthcheck.interval).await;
+            }
+        }
+        let reason = format!(
+            "did not become healthy after {} checks ({})",
+            healthcheck.retries.max(1),
+            last_failure
+        );
+        Err(self.not_ready(service, id, &reason).await)
+    }
+
+    async fn not_ready(&self, service: &Service, id: &str, reason: &str) -> ProcessError {
+        let logs = self
+            .runtime
+            .logs(id, LOG_TAIL)
+            .await
+            .unwrap_or_else(|e| format!("(logs unavailable: {})", e));
+        ProcessError::RuntimeFailed {
+            error_msg: format!(
+                "Service '{}' {}. Last logs:\n{}",
+                service.name,
+                reason,
+                logs.trim_end()
+            ),
+            exit_status: 503,
+        }
+    }
+}
@@ -0,0 +1,334 @@
+pub mod cli;
+pub mod fake;
+pub mod health;
+pub mod lifecycle;
+
+use crate::parser::devspin_generator::{Service, VolumeMount};
+use crate::prelude::*;
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+pub use cli::{CliRuntime, RuntimeKind};
+pub use fake::{FakeRuntime, RuntimeCall};
+pub use lifecycle::{Environment, Removed, ServiceAction, UpOptions, SHELL_WORKDIR};
+
+/// Label carrying the devspin project name on every container devspin creates
+pub const PROJECT_LABEL: &str = "devspin.project";
+/// Label carrying the service name
+pub const SERVICE_LABEL: &str = "devspin.service";
+
+/// Network joined by services that do not declare `networks`
+pub const DEFAULT_NETWORK: &str = "default";
+
+/// Everything needed to create the container of a service
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ContainerSpec {
+    pub name: String,
+    pub image: String,
+    pub ports: Vec<String>,
+    pub env: IndexMap<String, String>,
+    pub labels: IndexMap<String, String>,
+    /// Mounts as `source:target[:mode]`, named volumes already prefixed with the project
+    pub volumes: Vec<String>,
+    pub command: Vec<String>,
+    pub entrypoint: Vec<String>,
+    pub restart: Option<String>,
+    /// Full network names; the container is created on the first and connected to the others
+    pub networks: Vec<String>,
+    /// Host name other containers reach this one by on its networks
+    pub alias: String,
+    /// Directory the command starts in, the image's `WORKDIR` when unset
+    pub workdir: Option<String>,
+    /// Host name of the container itself, the runtime's (its id) when unset
+    pub hostname: Option<String>,
+}
+
+impl ContainerSpec {
+    /// Spec of `service` in `project`, named `<project>-<service>` and labelled
+    /// so the project's containers can be listed later. Relative bind mounts
+    /// stay relative until [`ContainerSpec::with_root`].
+    pub fn for_service(project: &str, service: &Service) -> Self {
+        let networks = if service.networks.is_empty() {
+            vec![resource_name(project, DEFAULT_NETWORK)]
+        } else {
+            service
+                .networks
+                .iter()
+                .map(|network| resource_name(project, network))
+                .collect()
+        };
+        let volumes = service
+            .volumes
+            .iter()
+            .map(|spec| match VolumeMount::parse(spec) {
+                Ok(VolumeMount::Named { name, target, mode }) => {
+                    let source = resource_name(project, &name);
+                    mount(&source, &target, mode.as_deref())
+                }
+                _ => spec.clone(),
+            })
+            .collect();
+
+        Self {
+            name: container_name(project, &service.name),
+            image: service.image.clone(),
+            ports: service.ports.clone(),
+            env: service.environment.clone(),
+            labels: project_labels(project)
+                .into_iter()
+                .chain([(SERVICE_LABEL.to_string(), service.name.clone())])
+                .collect(),
+            volumes,
+            command: service.command.clone(),
+            entrypoint: service.entrypoint.clone(),
+            restart: service.restart.clone(),
+            networks,
+            alias: service.name.clone(),
+            workdir: None,
+            hostname: None,
+        }
+    }
+
+    /// Resolve relative bind mount sources against the project `root`
+    pub fn with_root(mut self, root: &Path) -> Self {
+        for volume in &mut self.volumes {
+            if let Ok(VolumeMount::Bind {
+                source,
+                target,
+                mode,
+            }) = VolumeMount::parse(volume)
+            {
+                let source = match source.strip_prefix("~/") {
+                    Some(rest) => std::env::var_os("HOME").map_or_else(
+                        || PathBuf::from(&source),
+                        |home| PathBuf::from(home).join(rest),
+                    ),
+                    None => root.join(&source),
+                };
+                let source = source.components().collect::<PathBuf>();
+                *volume = mount(&source.to_string_lossy(), &target, mode.as_deref());
+            }
+        }
+        self
+    }
+}
+
+fn mount(source: &str, target: &str, mode: Option<&str>) -> String {
+    match mode {
+        Some(mode) => format!("{}:{}:{}", source, target, mode),
+        None => format!("{}:{}", source, target),
+    }
+}
+
+/// Labels put on every container, volume and network of `project`
+pub fn project_labels(project: &str) -> IndexMap<String, String> {
+    IndexMap::from([(PROJECT_LABEL.to_string(), project.to_string())])
+}
+
+/// Name of the volume or network `name` of `project`, e.g. `shop_pgdata`
+pub fn resource_name(project: &str, name: &str) -> String {
+    sanitize_name(&format!("{}_{}", project, name))
+}
+
+/// Container name of `service` in `project`
+pub fn container_name(project: &str, service: &str) -> String {
+    sanitize_name(&format!("{}-{}", project, service))
+}
+
+/// Keep only the characters runtimes accept in container, volume and network names
+pub(crate) fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' | '-' => c,
+            _ => '-',
+        })
+        .collect();
+    name.trim_start_matches(['_', '.', '-']).to_string()
+}
+
+/// Variables telling the app how to reach each service: `<SERVICE>_HOST` and
+/// `<SERVICE>_PORT` for the first port in `ports`. From the host that is
+/// `localhost` and the published port; from a container on the project network
+/// (`from_network`) the service name and the container port. Services without
+/// a reachable port get no variables.
+pub fn connection_env(services: &[Service], from_network: bool) -> IndexMap<String, String> {
+    let mut env = IndexMap::new();
+    for service in services {
+        let Some(container_port) = service.first_port() else {
+            continue;
+        };
+        let (host, port) = if from_network {
+            (service.name.clone(), container_port)
+        } else {
+            match service.host_port(container_port) {
+                Some(port) => ("localhost".to_string(), port),
+                None => continue,
+            }
+        };
+        let prefix = env_prefix(&service.name);
+        env.insert(format!("{}_HOST", prefix), host);
+        env.insert(format!("{}_PORT", prefix), port.to_string());
+    }
+    env
+}
+
+/// `my-db` -> `MY_DB`
+fn env_prefix(service: &str) -> String {
+    service
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => c.to_ascii_uppercase(),
+            _ => '_',
+        })
+        .collect()
+}
+
+/// State reported by `inspect`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerState {
+    pub id: String,
+    pub name: String,
+    /// Runtime status such as `created`, `running` or `exited`
+    pub status: String,
+    pub exit_code: i32,
+}
+
+impl ContainerState {
+    pub fn is_running(&self) -> bool {
+        self.status == "running"
+    }
+}
+
+/// One container returned by `list`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerSummary {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+}
+
+/// Result of a command run inside a container
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecOutput {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ExecOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// Operations devspin needs from a container engine. Containers are
+/// addressed by id or name.
+#[async_trait]
+pub trait ContainerRuntime: Send + Sync {
+    /// Short name used in messages (`docker`, `podman`, `fake`)
+    fn name(&self) -> &str;
+    async fn pull(&self, image: &str) -> Result<(), ProcessError>;
+    /// Create a container and return its id
+    async fn create(&self, spec: &ContainerSpec) -> Result<String, ProcessError>;
+    async fn start(&self, container: &str) -> Result<(), ProcessError>;
+    async fn stop(&self, container: &str) -> Result<(), ProcessError>;
+    /// Last `tail` lines of the container output
+    async fn logs(&self, container: &str, tail: usize) -> Result<String, ProcessError>;
+    async fn inspect(&self, container: &str) -> Result<ContainerState, ProcessError>;
+    /// Remove a container, with its anonymous volumes when `volumes` is set
+    async fn remove(&self, container: &str, volumes: bool) -> Result<(), ProcessError>;
+    async fn exec(&self, container: &str, command: &[String]) -> Result<ExecOutput, ProcessError>;
+    /// Containers (running or not) carrying the `key=value` label
+    async fn list(&self, key: &str, value: &str) -> Result<Vec<ContainerSummary>, ProcessError>;
+    /// Create a named volume; creating an existing volume is not an error
+    async fn create_volume(
+        &self,
+        name: &str,
+        labels: &IndexMap<String, String>,
+    ) -> Result<(), ProcessError>;
+    async fn remove_volume(&self, name: &str) -> Result<(), ProcessError>;
+    /// Names of the volumes carrying the `key=value` label
+    async fn list_volumes(&self, key: &str, value: &str) -> Result<Vec<String>, ProcessError>;
+    async fn create_network(
+        &self,
+        name: &str,
+        labels: &IndexMap<String, String>,
+    ) -> Result<(), ProcessError>;
+    async fn remove_network(&self, name: &str) -> Result<(), ProcessError>;
+    /// Names of the networks carrying the `key=value` label
+    async fn list_networks(&self, key: &str, value: &str) -> Result<Vec<String>, ProcessError>;
+    /// Attach a created container to one more network, reachable as `alias`
+    async fn connect_network(
+        &self,
+        network: &str,
+        container: &str,
+        alias: &str,
+    ) -> Result<(), ProcessError>;
+    /// Build the image `tag` from the `dockerfile` content, with an empty build context
+    async fn build(&self, tag: &str, dockerfile: &str) -> Result<(), ProcessError>;
+    /// Run a container from `spec` attached to the terminal, remove it once it
+    /// exits and return its exit code
+    async fn run_attached(&self, spec: &ContainerSpec) -> Result<i32, ProcessError>;
+}
+
+/// Pick the runtime to use: `DEVSPIN_RUNTIME` (`docker` or `podman`) when set,
+/// otherwise the first of docker and podman found on PATH
+pub fn detect_runtime() -> Result<Box<dyn ContainerRuntime>, ProcessError> {
+    let requested = std::env::var("DEVSPIN_RUNTIME").ok();
+    let kinds = match requested.as_deref() {
+        None | Some("") => vec![RuntimeKind::Docker, RuntimeKind::Podman],
+        Some(name) => {
+            vec![
+                RuntimeKind::from_name(name).ok_or_else(|| ProcessError::RuntimeFailed {
+                    error_msg: format!(
+                        "Unknown DEVSPIN_RUNTIME '{}' (expected docker or podman)",
+                        name
+                    ),
+                    exit_status: 400,
+                })?,
+            ]
+        }
+    };
+
+    for kind in &kinds {
+        if let Some(binary) = find_in_path(kind.binary()) {
+            return Ok(Box::new(CliRuntime::new(*kind, binary)));
+        }
+    }
+    Err(ProcessError::RuntimeFailed {
+        error_msg: format!(
+            "No container runtime found on PATH (looked for {})",
+            kinds
+                .iter()
+                .map(|k| k.binary())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        exit_status: 404,
+    })
+}
+
+/// Full path of `binary` in the directories of PATH
+pub fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            let candidates = [dir.join(binary), dir.join(format!("{}.exe", binary))];
+            candidates.into_iter()
+        })
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
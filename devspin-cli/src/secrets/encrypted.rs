@@ -0,0 +1,132 @@
+use super::SecretStore;
+use crate::error::DevSpinError;
+use age::secrecy::ExposeSecret;
+use age::x25519::Identity;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the identity (private key) file in the store dir
+pub const IDENTITY_FILE: &str = "identity.txt";
+/// Name of the encrypted secrets file in the store dir
+pub const SECRETS_FILE: &str = "secrets.age";
+
+/// Secrets encrypted with [age](https://age-encryption.org) to an X25519 key
+/// generated on first use. Both files are only readable by the user.
+#[derive(Debug, Clone)]
+pub struct AgeStore {
+    dir: PathBuf,
+}
+
+impl AgeStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `devspin` in the user config dir, e.g. `~/.config/devspin` on Linux
+    pub fn in_config_dir() -> Option<Self> {
+        dirs::config_dir().map(|dir| Self::new(dir.join("devspin")))
+    }
+
+    pub fn identity_path(&self) -> PathBuf {
+        self.dir.join(IDENTITY_FILE)
+    }
+
+    pub fn secrets_path(&self) -> PathBuf {
+        self.dir.join(SECRETS_FILE)
+    }
+
+    /// The identity of the store, generated when `create` is set and there is none yet
+    fn identity(&self, create: bool) -> Result<Identity, DevSpinError> {
+        let path = self.identity_path();
+        match std::fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+                .and_then(|key| key.parse::<Identity>().ok())
+                .ok_or_else(|| invalid(&path, "no age identity in the file")),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                let identity = Identity::generate();
+                let content = format!(
+                    "# devspin secret store key, keep it private\n# public key: {}\n{}\n",
+                    identity.to_public(),
+                    identity.to_string().expose_secret()
+                );
+                write_private(&path, content.as_bytes())?;
+                Ok(identity)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(invalid(
+                &path,
+                format!(
+                    "missing, {} cannot be decrypted without it",
+                    self.secrets_path().display()
+                ),
+            )),
+            Err(source) => Err(io_error(&path, source)),
+        }
+    }
+}
+
+impl SecretStore for AgeStore {
+    fn location(&self) -> String {
+        self.secrets_path().display().to_string()
+    }
+
+    fn load(&self) -> Result<IndexMap<String, String>, DevSpinError> {
+        let path = self.secrets_path();
+        let encrypted = match std::fs::read(&path) {
+            Ok(encrypted) => encrypted,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(IndexMap::new()),
+            Err(source) => return Err(io_error(&path, source)),
+        };
+        let identity = self.identity(false)?;
+        let decrypted = age::decrypt(&identity, &encrypted)
+            .map_err(|e| invalid(&path, format!("cannot decrypt: {}", e)))?;
+        serde_json::from_slice(&decrypted)
+            .map_err(|e| invalid(&path, format!("unexpected content: {}", e)))
+    }
+
+    fn save(&self, secrets: &IndexMap<String, String>) -> Result<(), DevSpinError> {
+        let path = self.secrets_path();
+        let identity = self.identity(true)?;
+        let plaintext = serde_json::to_vec(secrets).expect("a map of strings serializes");
+        let encrypted = age::encrypt(&identity.to_public(), &plaintext)
+            .map_err(|e| invalid(&path, format!("cannot encrypt: {}", e)))?;
+        write_private(&path, &encrypted)
+    }
+}
+
+/// Write `content` to `path` with owner-only permissions, through a
+/// temporary file so an interrupted write leaves the previous version
+fn write_private(path: &Path, content: &[u8]) -> Result<(), DevSpinError> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+    let temporary = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let write = |options: &std::fs::OpenOptions| {
+        use std::io::Write;
+        options.open(&temporary)?.write_all(content)?;
+        std::fs::rename(&temporary, path)
+    };
+    write(&options).map_err(|e| io_error(path, e))
+}
+
+fn invalid(path: &Path, message: impl Into<String>) -> DevSpinError {
+    DevSpinError::Invalid {
+        path: path.display().to_string(),
+        message: message.into(),
+    }
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> DevSpinError {
+    DevSpinError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
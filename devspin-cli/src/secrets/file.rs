@@ -0,0 +1,48 @@
+use super::SecretStore;
+use crate::error::DevSpinError;
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+/// Secrets kept unencrypted in a JSON file, for tests and CI where the
+/// values come from elsewhere
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SecretStore for FileStore {
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> Result<IndexMap<String, String>, DevSpinError> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(IndexMap::new()),
+            Err(source) => {
+                return Err(DevSpinError::Io {
+                    path: self.location(),
+                    source,
+                })
+            }
+        };
+        serde_json::from_str(&content).map_err(|e| DevSpinError::Invalid {
+            path: self.location(),
+            message: format!("expected a JSON object of strings: {}", e),
+        })
+    }
+
+    fn save(&self, secrets: &IndexMap<String, String>) -> Result<(), DevSpinError> {
+        let content = serde_json::to_string_pretty(secrets).expect("a map of strings serializes");
+        std::fs::write(&self.path, content + "\n").map_err(|source| DevSpinError::Io {
+            path: self.location(),
+            source,
+        })
+    }
+}
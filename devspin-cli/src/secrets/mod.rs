@@ -0,0 +1,274 @@
+pub mod encrypted;
+pub mod file;
+
+use crate::error::DevSpinError;
+use crate::parser::devspin_generator::DevSpinConfig;
+use crate::parser::interpolate::Layer;
+use crate::prelude::*;
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+pub use encrypted::AgeStore;
+pub use file::FileStore;
+
+/// Prefix of a value read from the secret store, as in `POSTGRES_PASSWORD: secret:pg_password`
+pub const PREFIX: &str = "secret:";
+/// Shown in place of a secret value
+pub const REDACTED: &str = "<redacted>";
+/// Points devspin at a plain [`FileStore`] instead of the encrypted store (for tests and CI)
+pub const FILE_VAR: &str = "DEVSPIN_SECRETS_FILE";
+
+/// Name of the secret `value` refers to, when the whole value is `secret:NAME`
+pub fn reference(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(PREFIX)
+        .filter(|name| is_valid_name(name))
+}
+
+/// The `${VAR}` form of a value built from secrets, as written by
+/// [`Secrets::withhold`]: `secret:` then the value with each secret replaced by
+/// a reference to the `env` variable holding it, and any other `$` doubled
+pub fn template(value: &str) -> Option<&str> {
+    let template = value.strip_prefix(PREFIX)?;
+    (reference(value).is_none() && template.contains("${")).then_some(template)
+}
+
+/// Letters, digits, `_`, `-` and `.`
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Where secret values are kept. Stores hold every secret in one map, read
+/// and written as a whole.
+pub trait SecretStore {
+    /// Where the secrets are kept, for messages
+    fn location(&self) -> String;
+    /// Every secret by name, empty when nothing was stored yet
+    fn load(&self) -> Result<IndexMap<String, String>, DevSpinError>;
+    fn save(&self, secrets: &IndexMap<String, String>) -> Result<(), DevSpinError>;
+
+    fn get(&self, name: &str) -> Result<Option<String>, DevSpinError> {
+        Ok(self.load()?.swap_remove(name))
+    }
+
+    fn set(&self, name: &str, value: &str) -> Result<(), DevSpinError> {
+        let mut secrets = self.load()?;
+        secrets.insert(name.to_string(), value.to_string());
+        self.save(&secrets)
+    }
+
+    /// Remove a secret and tell whether it existed
+    fn remove(&self, name: &str) -> Result<bool, DevSpinError> {
+        let mut secrets = self.load()?;
+        let existed = secrets.shift_remove(name).is_some();
+        if existed {
+            self.save(&secrets)?;
+        }
+        Ok(existed)
+    }
+}
+
+/// The store to use: a [`FileStore`] at `DEVSPIN_SECRETS_FILE` when set, otherwise
+/// the [`AgeStore`] in the user config dir
+pub fn open_store() -> Result<Box<dyn SecretStore>, ProcessError> {
+    if let Some(path) = std::env::var_os(FILE_VAR).filter(|path| !path.is_empty()) {
+        return Ok(Box::new(FileStore::new(PathBuf::from(path))));
+    }
+    let store = AgeStore::in_config_dir().ok_or_else(|| ProcessError::SecretStoreFailed {
+        error_msg: format!(
+            "Could not find the user config dir to keep secrets in, set {} to use a file",
+            FILE_VAR
+        ),
+        exit_status: 404,
+    })?;
+    Ok(Box::new(store))
+}
+
+/// Secret values used by a config, by name. Masks them in anything printed.
+#[derive(Clone, Default, PartialEq)]
+pub struct Secrets {
+    values: IndexMap<String, String>,
+}
+
+// Names only, so the values never end up in a log
+impl std::fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.values.keys()).finish()
+    }
+}
+
+impl Secrets {
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Name of the first secret whose value appears in `text`
+    pub fn find(&self, text: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(_, value)| !value.is_empty() && text.contains(value.as_str()))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// `text` with every secret value replaced by [`REDACTED`]
+    pub fn redact(&self, text: &str) -> String {
+        // Longest first, so a secret containing another one is masked whole
+        let mut values: Vec<&String> = self.values.values().filter(|v| !v.is_empty()).collect();
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        values.into_iter().fold(text.to_string(), |text, value| {
+            text.replace(value.as_str(), REDACTED)
+        })
+    }
+
+    /// `error` with the secret values of its message redacted
+    pub fn redact_error(&self, mut error: ProcessError) -> ProcessError {
+        let message = error.message_mut();
+        *message = self.redact(message);
+        error
+    }
+
+    /// Put a `secret:NAME` reference back in place of every `env` and service
+    /// `environment` value that is a secret, for files written to disk. A value
+    /// built from secrets gets its [`template`] form instead; it is an error
+    /// when no `env` variable holds one of them
+    pub fn withhold(&self, config: &mut DevSpinConfig) -> Result<(), ProcessError> {
+        // Name of each secret, with the first `env` variable holding it whole
+        let holders: IndexMap<String, String> = config
+            .content
+            .env
+            .iter()
+            .filter_map(|(key, value)| Some((self.exact(value)?.to_string(), key.clone())))
+            // Later entries replace earlier ones
+            .rev()
+            .collect();
+        let envs = std::iter::once(("env".to_string(), &mut config.content.env)).chain(
+            config.content.services.iter_mut().map(|service| {
+                let scope = format!("service `{}` environment", service.name);
+                (scope, &mut service.environment)
+            }),
+        );
+        for (scope, env) in envs {
+            for (key, value) in env.iter_mut() {
+                if let Some(name) = self.exact(value) {
+                    *value = format!("{}{}", PREFIX, name);
+                } else if self.find(value).is_some() {
+                    *value = self.template_of(value, &holders, &format!("{} `{}`", scope, key))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the secret that is the whole of `value`
+    fn exact(&self, value: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(_, secret)| !secret.is_empty() && secret.as_str() == value)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// `value` in [`template`] form, `context` naming it in errors
+    fn template_of(
+        &self,
+        value: &str,
+        holders: &IndexMap<String, String>,
+        context: &str,
+    ) -> Result<String, ProcessError> {
+        let mut template = PREFIX.to_string();
+        let mut rest = value;
+        loop {
+            // The first secret in `rest`, the longest one when several start there
+            let next = self
+                .values
+                .iter()
+                .filter(|(_, secret)| !secret.is_empty())
+                .filter_map(|(name, secret)| Some((rest.find(secret.as_str())?, name, secret)))
+                .min_by_key(|(at, _, secret)| (*at, std::cmp::Reverse(secret.len())));
+            let Some((at, name, secret)) = next else {
+                template.push_str(&rest.replace('$', "$$"));
+                return Ok(template);
+            };
+            let key = holders.get(name).ok_or_else(|| ProcessError::InvalidConfig {
+                error_msg: format!(
+                    "{} is built from secret `{}`, which no `env` variable holds: add one set to `{}{}` and refer to it",
+                    context, name, PREFIX, name
+                ),
+                exit_status: 422,
+            })?;
+            template.push_str(&rest[..at].replace('$', "$$"));
+            template.push_str(&format!("${{{}}}", key));
+            rest = &rest[at + secret.len()..];
+        }
+    }
+}
+
+/// Replace the `secret:NAME` values of `config` (`env`, service `environment`)
+/// and of the `layers` that add variables with the values of `store`, before
+/// `${VAR}` references are expanded. The store is only read when a value
+/// refers to it; its failures are returned as [`DevSpinError::Process`].
+pub fn resolve(
+    config: &mut DevSpinConfig,
+    layers: &mut [Layer],
+    store: &dyn SecretStore,
+    path: &str,
+) -> Result<Secrets, DevSpinError> {
+    let mut stored: Option<IndexMap<String, String>> = None;
+    let mut secrets = Secrets::default();
+    let mut lookup = |value: &str, context: String| -> Result<Option<String>, DevSpinError> {
+        let Some(name) = reference(value) else {
+            return Ok(None);
+        };
+        if stored.is_none() {
+            let loaded = store.load().map_err(|e| ProcessError::SecretStoreFailed {
+                error_msg: e.to_string(),
+                exit_status: 500,
+            })?;
+            stored = Some(loaded);
+        }
+        let Some(secret) = stored.as_ref().and_then(|stored| stored.get(name)) else {
+            return Err(DevSpinError::Invalid {
+                path: path.to_string(),
+                message: format!(
+                    "{}: secret `{}` is not in {}, add it with `devspin secret set {}`",
+                    context,
+                    name,
+                    store.location(),
+                    name
+                ),
+            });
+        };
+        secrets.values.insert(name.to_string(), secret.clone());
+        Ok(Some(secret.clone()))
+    };
+
+    // Config values are expanded afterwards, a secret must stay as written
+    let escape = |secret: String| secret.replace("${", "$${");
+    for (key, value) in config.content.env.iter_mut() {
+        if let Some(secret) = lookup(value, format!("env `{}`", key))? {
+            *value = escape(secret);
+        }
+    }
+    for service in &mut config.content.services {
+        for (key, value) in service.environment.iter_mut() {
+            let context = format!("service `{}` environment `{}`", service.name, key);
+            if let Some(secret) = lookup(value, context)? {
+                *value = escape(secret);
+            }
+        }
+    }
+    for layer in layers.iter_mut().filter(|layer| !layer.overrides_only) {
+        for (key, value) in layer.vars.iter_mut() {
+            if let Some(secret) = lookup(value, format!("{} `{}`", layer.name, key))? {
+                *value = secret;
+            }
+        }
+    }
+    Ok(secrets)
+}
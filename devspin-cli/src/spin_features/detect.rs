@@ -0,0 +1,172 @@
+use std::path::Path;
+
+/// Extensions of the files a project type or a file language is read from
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "json", "yaml", "yml", "toml", "mod", "py", "go", "rs", "v", "js", "ts", "jsx", "tsx", "txt",
+];
+
+/// What a config file says about the project it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigType {
+    /// `v.mod`
+    VModule,
+    /// `go.mod`
+    GoModule,
+    /// `Cargo.toml`
+    RustCrate,
+    /// `pyproject.toml` or `requirements.txt`
+    PythonProject,
+    /// `tsconfig.json`
+    TypescriptConfig,
+    /// `package*.json`
+    PackageConfig,
+    /// JSON whose name contains `config` or `settings`
+    AppConfig,
+    GenericJson,
+    /// Any other supported file, with its extension
+    Unknown(String),
+}
+
+impl ConfigType {
+    /// Type of the config file at `path`, `None` when its extension is not supported
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let extension = path.extension()?.to_str()?;
+        if !SUPPORTED_EXTENSIONS.contains(&extension) {
+            return None;
+        }
+        let config_type = match name {
+            "v.mod" => ConfigType::VModule,
+            "go.mod" => ConfigType::GoModule,
+            "Cargo.toml" => ConfigType::RustCrate,
+            "pyproject.toml" | "requirements.txt" => ConfigType::PythonProject,
+            "tsconfig.json" => ConfigType::TypescriptConfig,
+            _ if extension == "json" => {
+                if name.starts_with("package") {
+                    ConfigType::PackageConfig
+                } else if name.contains("config") || name.contains("settings") {
+                    ConfigType::AppConfig
+                } else {
+                    ConfigType::GenericJson
+                }
+            }
+            _ => ConfigType::Unknown(extension.to_string()),
+        };
+        Some(config_type)
+    }
+
+    /// Whether the file marks the root of a project, rather than being one file of it
+    pub fn is_project(&self) -> bool {
+        !matches!(
+            self,
+            ConfigType::AppConfig | ConfigType::GenericJson | ConfigType::Unknown(_)
+        )
+    }
+
+    /// Language the files of this project are written in
+    pub fn language(&self) -> Option<Language> {
+        match self {
+            ConfigType::VModule => Some(Language::V),
+            ConfigType::GoModule => Some(Language::Go),
+            ConfigType::RustCrate => Some(Language::Rust),
+            ConfigType::PythonProject => Some(Language::Python),
+            ConfigType::TypescriptConfig => Some(Language::TypeScript),
+            ConfigType::PackageConfig => Some(Language::JavaScript),
+            ConfigType::AppConfig | ConfigType::GenericJson => Some(Language::Json),
+            ConfigType::Unknown(extension) => Language::from_extension(extension),
+        }
+    }
+}
+
+/// Languages devspin has a linter or a formatter for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    V,
+    JavaScript,
+    TypeScript,
+    Json,
+    Python,
+    Go,
+    Rust,
+    Toml,
+    Yaml,
+}
+
+impl Language {
+    /// Language of a source file with `extension` (without the dot)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "v" => Some(Language::V),
+            "js" | "jsx" => Some(Language::JavaScript),
+            "ts" | "tsx" => Some(Language::TypeScript),
+            "json" => Some(Language::Json),
+            "py" => Some(Language::Python),
+            "go" => Some(Language::Go),
+            "rs" => Some(Language::Rust),
+            "toml" => Some(Language::Toml),
+            "yaml" | "yml" => Some(Language::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Language of the file at `path`, from its extension
+    pub fn of_file(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::V => "V",
+            Language::JavaScript => "JavaScript",
+            Language::TypeScript => "TypeScript",
+            Language::Json => "JSON",
+            Language::Python => "Python",
+            Language::Go => "Go",
+            Language::Rust => "Rust",
+            Language::Toml => "TOML",
+            Language::Yaml => "YAML",
+        }
+    }
+}
+
+/// Languages of the projects whose config files are directly in `dir`, in the
+/// order of the file names
+pub fn project_languages(dir: &Path) -> Vec<Language> {
+    let mut languages = Vec::new();
+    for path in sorted_files(dir) {
+        let language = ConfigType::detect(&path)
+            .filter(ConfigType::is_project)
+            .and_then(|config_type| config_type.language());
+        if let Some(language) = language {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+    }
+    languages
+}
+
+/// Languages of the files directly in `dir`, and of the projects it holds
+pub fn dir_languages(dir: &Path) -> Vec<Language> {
+    let mut languages = project_languages(dir);
+    for path in sorted_files(dir) {
+        if let Some(language) = Language::of_file(&path) {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+    }
+    languages
+}
+
+fn sorted_files(dir: &Path) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
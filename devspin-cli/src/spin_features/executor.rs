@@ -0,0 +1,322 @@
+use crate::parser::devspin_generator::SpinFeature;
+use crate::prelude::*;
+use crate::spin_features::detect::{self, Language};
+use crate::tasks::fingerprint::matching_files;
+use crate::tasks::runner::exit_code;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// The spin features devspin runs tools for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Lint,
+    Format,
+}
+
+impl Operation {
+    /// Key of the feature under `spin-features`
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Operation::Lint => "lint",
+            Operation::Format => "format",
+        }
+    }
+
+    /// Name of the tools doing this operation, for messages
+    pub fn tool_kind(&self) -> &'static str {
+        match self {
+            Operation::Lint => "linter",
+            Operation::Format => "formatter",
+        }
+    }
+}
+
+/// What a feature runs on, with paths relative to the project root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// One file (`currFile`)
+    File(PathBuf),
+    /// A directory and what it holds (`currDir`)
+    Dir(PathBuf),
+    /// The whole project (`all`)
+    Project,
+}
+
+impl Target {
+    /// Target of `path` (relative to the current directory), the project when it is `None`
+    pub fn resolve(path: Option<&Path>, root: &Path) -> Result<Self, ProcessError> {
+        let Some(path) = path else {
+            return Ok(Target::Project);
+        };
+        let invalid = |message: String| ProcessError::TaskFailed {
+            error_msg: message,
+            exit_status: 2,
+        };
+        let absolute = path
+            .canonicalize()
+            .map_err(|e| invalid(format!("Cannot read `{}`: {}", path.display(), e)))?;
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let relative = absolute.strip_prefix(&root).map_err(|_| {
+            invalid(format!(
+                "`{}` is outside the project root {}",
+                path.display(),
+                root.display()
+            ))
+        })?;
+
+        if absolute.is_dir() {
+            if relative.as_os_str().is_empty() {
+                return Ok(Target::Dir(PathBuf::from(".")));
+            }
+            return Ok(Target::Dir(relative.to_path_buf()));
+        }
+        Ok(Target::File(relative.to_path_buf()))
+    }
+
+    /// The `modes` entry allowing this target
+    pub fn mode(&self) -> &'static str {
+        match self {
+            Target::File(_) => "currFile",
+            Target::Dir(_) => "currDir",
+            Target::Project => "all",
+        }
+    }
+
+    /// Whether `feature` runs on this target: it is enabled and its modes
+    /// include this one (no modes allow every target)
+    pub fn allowed_by(&self, feature: &SpinFeature) -> bool {
+        feature.enabled
+            && (feature.modes.is_empty() || feature.modes.iter().any(|m| m == self.mode()))
+    }
+
+    /// Path given to the tools
+    fn arg(&self) -> String {
+        match self {
+            Target::File(path) | Target::Dir(path) => path.display().to_string(),
+            Target::Project => ".".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::File(path) | Target::Dir(path) => write!(f, "{}", path.display()),
+            Target::Project => write!(f, "the project"),
+        }
+    }
+}
+
+/// A linter or formatter invocation, run in the project root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCommand {
+    pub language: Language,
+    pub program: String,
+    pub args: Vec<String>,
+    /// The tool lists the files it would change and exits with 0 (`gofmt -l`)
+    pub lists_files: bool,
+}
+
+impl ToolCommand {
+    fn new(language: Language, program: &str, args: &[&str], target: String) -> Self {
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.push(target);
+        Self {
+            language,
+            program: program.to_string(),
+            args,
+            lists_files: false,
+        }
+    }
+
+    fn without_target(language: Language, program: &str, args: &[&str]) -> Self {
+        Self {
+            language,
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            lists_files: false,
+        }
+    }
+}
+
+impl fmt::Display for ToolCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// The tool doing `operation` on the `language` files of `target`, `None`
+/// when devspin has none. Formatters only report the files they would change
+/// when `check` is set, and rewrite them otherwise.
+pub fn tool_command(
+    operation: Operation,
+    language: Language,
+    target: &Target,
+    check: bool,
+) -> Option<ToolCommand> {
+    let path = target.arg();
+    let command = match (operation, language) {
+        (Operation::Lint, Language::V) => ToolCommand::new(language, "v", &["vet"], path),
+        // clippy checks the crate, a file or a directory is checked along with it
+        (Operation::Lint, Language::Rust) => {
+            ToolCommand::without_target(language, "cargo", &["clippy", "--", "-D", "warnings"])
+        }
+        (Operation::Lint, Language::Go) => {
+            let packages = match target {
+                Target::File(_) => path,
+                Target::Dir(dir) if dir != Path::new(".") => format!("./{}/...", path),
+                Target::Dir(_) | Target::Project => "./...".to_string(),
+            };
+            ToolCommand::new(language, "go", &["vet"], packages)
+        }
+        (Operation::Lint, Language::JavaScript | Language::TypeScript) => {
+            ToolCommand::new(language, "eslint", &[], path)
+        }
+        (Operation::Lint, Language::Python) => match target {
+            Target::File(_) => ToolCommand::new(language, "pylint", &[], path),
+            Target::Dir(_) | Target::Project => {
+                ToolCommand::new(language, "pylint", &["--recursive=y"], path)
+            }
+        },
+        (Operation::Lint, Language::Json | Language::Toml | Language::Yaml) => return None,
+
+        (Operation::Format, Language::V) => {
+            let flag = if check { "-verify" } else { "-w" };
+            ToolCommand::new(language, "v", &["fmt", flag], path)
+        }
+        (Operation::Format, Language::Rust) => match (target, check) {
+            // `cargo fmt` would format the whole crate, [`plan`] lists the
+            // directory's files for rustfmt instead
+            (Target::Dir(_), _) => return None,
+            (Target::File(_), true) => ToolCommand::new(language, "rustfmt", &["--check"], path),
+            (Target::File(_), false) => ToolCommand::new(language, "rustfmt", &[], path),
+            (Target::Project, true) => {
+                ToolCommand::without_target(language, "cargo", &["fmt", "--", "--check"])
+            }
+            (Target::Project, false) => ToolCommand::without_target(language, "cargo", &["fmt"]),
+        },
+        (
+            Operation::Format,
+            Language::JavaScript | Language::TypeScript | Language::Json | Language::Yaml,
+        ) => {
+            let flag = if check { "--check" } else { "--write" };
+            ToolCommand::new(language, "prettier", &[flag], path)
+        }
+        (Operation::Format, Language::Python) => match check {
+            true => ToolCommand::new(language, "black", &["--check", "--diff"], path),
+            false => ToolCommand::new(language, "black", &[], path),
+        },
+        (Operation::Format, Language::Go) => match check {
+            true => ToolCommand {
+                lists_files: true,
+                ..ToolCommand::new(language, "gofmt", &["-l"], path)
+            },
+            false => ToolCommand::new(language, "gofmt", &["-w"], path),
+        },
+        (Operation::Format, Language::Toml) => return None,
+    };
+    Some(command)
+}
+
+/// Tools doing `operation` on `target`, from the languages found there: the
+/// extension of a file, the files and project files of a directory, the
+/// project files of the root. The same command is only run once.
+pub fn plan(
+    operation: Operation,
+    target: &Target,
+    root: &Path,
+    check: bool,
+) -> Result<Vec<ToolCommand>, ProcessError> {
+    let languages = match target {
+        Target::File(path) => Language::of_file(path).into_iter().collect(),
+        Target::Dir(path) => detect::dir_languages(&root.join(path)),
+        Target::Project => detect::project_languages(root),
+    };
+
+    let mut commands: Vec<ToolCommand> = Vec::new();
+    for language in &languages {
+        let command = match (operation, language, target) {
+            (Operation::Format, Language::Rust, Target::Dir(dir)) => rustfmt_dir(root, dir, check),
+            _ => tool_command(operation, *language, target, check),
+        };
+        let Some(command) = command else {
+            continue;
+        };
+        let duplicate = commands
+            .iter()
+            .any(|c| c.program == command.program && c.args == command.args);
+        if !duplicate {
+            commands.push(command);
+        }
+    }
+
+    if commands.is_empty() {
+        let found = match languages.as_slice() {
+            [] => "no supported language found".to_string(),
+            _ => format!(
+                "found {}",
+                languages
+                    .iter()
+                    .map(Language::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        return Err(ProcessError::TaskNotFound {
+            error_msg: format!("No {} for {} ({})", operation.tool_kind(), target, found),
+            exit_status: 404,
+        });
+    }
+    Ok(commands)
+}
+
+/// rustfmt on the `.rs` files under `dir`, `None` when it holds none
+fn rustfmt_dir(root: &Path, dir: &Path, check: bool) -> Option<ToolCommand> {
+    let files = matching_files(root, &[format!("{}/**/*.rs", dir.display())]);
+    if files.is_empty() {
+        return None;
+    }
+    let args: &[&str] = if check { &["--check"] } else { &[] };
+    let mut command = ToolCommand::without_target(Language::Rust, "rustfmt", args);
+    command.args.extend(files);
+    Some(command)
+}
+
+/// Run `command` in `root` with its output on the terminal, and return its
+/// exit code (1 when a tool listing files listed some)
+pub async fn run(command: &ToolCommand, root: &Path) -> Result<u16, ProcessError> {
+    let failed = |e: std::io::Error| ProcessError::TaskFailed {
+        error_msg: match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                format!("`{}` is not installed or not in PATH", command.program)
+            }
+            _ => format!("Failed to start `{}`: {}", command.program, e),
+        },
+        exit_status: 127,
+    };
+    let mut cmd = Command::new(&command.program);
+    cmd.args(&command.args).current_dir(root);
+
+    if !command.lists_files {
+        let status = cmd.status().await.map_err(failed)?;
+        return Ok(exit_code(&status));
+    }
+    let output = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .output()
+        .await
+        .map_err(failed)?;
+    let listed = String::from_utf8_lossy(&output.stdout);
+    print!("{}", listed);
+    match exit_code(&output.status) {
+        0 if !listed.trim().is_empty() => Ok(1),
+        code => Ok(code),
+    }
+}
@@ -0,0 +1,5 @@
+pub mod detect;
+pub mod executor;
+
+pub use detect::{ConfigType, Language};
+pub use executor::{Operation, Target, ToolCommand};
@@ -0,0 +1,193 @@
+use crate::parser::devspin_generator::Task;
+use crate::prelude::*;
+use crate::utils::devspin_finder::should_skip_entry;
+use indexmap::IndexMap;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directory (relative to the project root) holding devspin's local state
+pub const CACHE_DIR: &str = ".devspin";
+const FINGERPRINTS_FILE: &str = "fingerprints";
+
+/// Last successful fingerprint of each task, stored in `.devspin/fingerprints`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FingerprintCache {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+}
+
+impl FingerprintCache {
+    /// Load the cache of the project at `root`. A missing or unreadable cache is empty.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CACHE_DIR).join(FINGERPRINTS_FILE);
+        let entries = std::fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(task, hash)| (task.to_string(), hash.to_string()))
+            .collect();
+        Self { path, entries }
+    }
+
+    pub fn get(&self, task: &str) -> Option<&str> {
+        self.entries.get(task).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, task: &str, fingerprint: String) {
+        self.entries.insert(task.to_string(), fingerprint);
+    }
+
+    pub fn save(&self) -> Result<(), ProcessError> {
+        let write_failed = |e: std::io::Error| ProcessError::CreateFileFailed {
+            error_msg: format!("Failed to write {}: {}", self.path.display(), e),
+            exit_status: 400,
+        };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(write_failed)?;
+        }
+        let content: String = self
+            .entries
+            .iter()
+            .map(|(task, hash)| format!("{}\t{}\n", task, hash))
+            .collect();
+        std::fs::write(&self.path, content).map_err(write_failed)
+    }
+}
+
+/// What [`fingerprint`] found for a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fingerprint {
+    /// The task declares no inputs, it always runs
+    NoInputs,
+    /// The inputs match no file. The task always runs, so a mistyped pattern
+    /// does not leave it up to date forever
+    NoMatch,
+    /// Hash of the command, the env and the input files
+    Hash(String),
+}
+
+/// Hash of everything that decides a task's result: its command, the env and
+/// the content of every file matched by its `inputs`.
+/// Fails only when an input cannot be read.
+pub fn fingerprint(
+    task: &Task,
+    root: &Path,
+    env: &IndexMap<String, String>,
+) -> Result<Fingerprint, ProcessError> {
+    if task.inputs.is_empty() {
+        return Ok(Fingerprint::NoInputs);
+    }
+
+    let files = matching_files(root, &task.inputs);
+    if files.is_empty() {
+        return Ok(Fingerprint::NoMatch);
+    }
+    let file_hashes = files
+        .par_iter()
+        .map(|relative| {
+            let content =
+                std::fs::read(root.join(relative)).map_err(|e| ProcessError::TaskFailed {
+                    error_msg: format!("Failed to read input '{}': {}", relative, e),
+                    exit_status: 1,
+                })?;
+            Ok((relative.as_str(), Sha256::digest(&content)))
+        })
+        .collect::<Result<Vec<_>, ProcessError>>()?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(task.command.as_bytes());
+    let sorted_env: BTreeMap<&String, &String> = env.iter().collect();
+    for (key, value) in sorted_env {
+        hasher.update(format!("\0env\0{}={}", key, value).as_bytes());
+    }
+    for (relative, hash) in file_hashes {
+        hasher.update(format!("\0file\0{}\0", relative).as_bytes());
+        hasher.update(hash);
+    }
+
+    let digest = hasher.finalize();
+    Ok(Fingerprint::Hash(
+        digest.iter().map(|b| format!("{:02x}", b)).collect(),
+    ))
+}
+
+/// Whether every declared output of `task` exists under `root`
+pub fn outputs_exist(task: &Task, root: &Path) -> bool {
+    task.outputs.iter().all(|output| root.join(output).exists())
+}
+
+/// Files under `root` matching any of `patterns`, as sorted `/`-separated
+/// relative paths. Patterns support `*`, `?` and `**` (any number of directories).
+pub fn matching_files(root: &Path, patterns: &[String]) -> Vec<String> {
+    let mut files: Vec<String> = patterns
+        .par_iter()
+        .flat_map_iter(|pattern| {
+            let pattern = pattern.trim_start_matches("./");
+            let base = literal_base(pattern);
+            WalkDir::new(root.join(&base))
+                .follow_links(true)
+                .into_iter()
+                // Junk directories are only skipped below the pattern's literal base,
+                // so `build/*.sh` still matches while `**/*.rs` ignores `target/`.
+                .filter_entry(|entry| entry.depth() == 0 || !should_skip_entry(entry))
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_file())
+                .filter_map(|entry| {
+                    let relative = entry.path().strip_prefix(root).ok()?;
+                    let relative = relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    (!relative.starts_with(CACHE_DIR) && glob_match(pattern, &relative))
+                        .then_some(relative)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Leading directories of `pattern` that contain no wildcard
+fn literal_base(pattern: &str) -> PathBuf {
+    let components: Vec<&str> = pattern.split('/').collect();
+    components[..components.len() - 1]
+        .iter()
+        .take_while(|c| !c.contains(['*', '?']))
+        .collect()
+}
+
+/// Match a `/`-separated relative path against a glob pattern
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_components(&pattern, &path)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_components(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((component, path_rest)) => {
+                match_component(first.as_bytes(), component.as_bytes())
+                    && match_components(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_component(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_component(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_component(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_component(rest, &name[1..]),
+    }
+}
@@ -0,0 +1,3 @@
+pub mod fingerprint;
+pub mod runner;
+pub mod scheduler;
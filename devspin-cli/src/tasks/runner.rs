@@ -0,0 +1,151 @@
+use crate::parser::devspin_generator::Task;
+use crate::prelude::*;
+use crate::secrets::Secrets;
+use colored::*;
+use indexmap::IndexMap;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+/// Build the platform shell invocation for a task command
+pub fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// Run `task` through the shell in `root` with `env` injected.
+/// Output is streamed to the terminal as it is produced. Without `secrets`
+/// the task writes to the terminal directly; otherwise its output goes
+/// through devspin line by line, with the secret values redacted.
+pub async fn run_task(
+    task: &Task,
+    root: &Path,
+    env: &IndexMap<String, String>,
+    secrets: &Secrets,
+) -> Result<ExitStatus, ProcessError> {
+    if !secrets.is_empty() {
+        return run_task_forwarded(task, root, env, secrets, None).await;
+    }
+    shell_command(&task.command)
+        .current_dir(root)
+        .envs(env)
+        .status()
+        .await
+        .map_err(|e| ProcessError::TaskFailed {
+            error_msg: format!("Failed to start task '{}': {}", task.name, e),
+            exit_status: 127,
+        })
+}
+
+/// Exit code to report for a finished task (128 + signal when it was killed)
+pub fn exit_code(status: &ExitStatus) -> u16 {
+    if let Some(code) = status.code() {
+        return code as u16;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal as u16;
+        }
+    }
+    1
+}
+
+/// Like [`run_task`], but prefixes every output line with the task name so
+/// that tasks running side by side stay readable. Values of `secrets` are
+/// redacted from the lines.
+pub async fn run_task_prefixed(
+    task: &Task,
+    root: &Path,
+    env: &IndexMap<String, String>,
+    secrets: &Secrets,
+) -> Result<ExitStatus, ProcessError> {
+    let prefix = format!("[{}]", task.name).cyan().to_string();
+    run_task_forwarded(task, root, env, secrets, Some(prefix)).await
+}
+
+/// Run `task` with its output lines forwarded to the terminal, redacted and
+/// after `prefix` when there is one
+async fn run_task_forwarded(
+    task: &Task,
+    root: &Path,
+    env: &IndexMap<String, String>,
+    secrets: &Secrets,
+    prefix: Option<String>,
+) -> Result<ExitStatus, ProcessError> {
+    let start_failed = |e: std::io::Error| ProcessError::TaskFailed {
+        error_msg: format!("Failed to start task '{}': {}", task.name, e),
+        exit_status: 127,
+    };
+
+    let mut child = shell_command(&task.command)
+        .current_dir(root)
+        .envs(env)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(start_failed)?;
+
+    let stdout = child.stdout.take().map(|out| {
+        let prefix = prefix.clone();
+        tokio::spawn(forward_lines(out, prefix, secrets.clone(), false))
+    });
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| tokio::spawn(forward_lines(err, prefix, secrets.clone(), true)));
+
+    let status = child.wait().await.map_err(start_failed)?;
+    for forwarder in [stdout, stderr].into_iter().flatten() {
+        let _ = forwarder.await;
+    }
+    Ok(status)
+}
+
+async fn forward_lines(
+    reader: impl AsyncRead + Unpin,
+    prefix: Option<String>,
+    secrets: Secrets,
+    to_stderr: bool,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    // Bytes rather than `lines()`, which gives up on the first line that is not UTF-8
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let end = buf
+            .strip_suffix(b"\n")
+            .map(|rest| rest.strip_suffix(b"\r").unwrap_or(rest))
+            .unwrap_or(&buf);
+        let line = secrets.redact(&String::from_utf8_lossy(end));
+        let Some(prefix) = &prefix else {
+            if to_stderr {
+                eprintln!("{}", line);
+            } else {
+                println!("{}", line);
+            }
+            continue;
+        };
+        if to_stderr {
+            eprintln!("{} {}", prefix, line);
+        } else {
+            println!("{} {}", prefix, line);
+        }
+    }
+}
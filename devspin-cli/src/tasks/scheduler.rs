@@ -0,0 +1,278 @@
+use crate::parser::devspin_generator::Task;
+use crate::prelude::*;
+use crate::secrets::Secrets;
+use crate::tasks::fingerprint::{fingerprint, outputs_exist, Fingerprint, FingerprintCache};
+use crate::tasks::runner::{exit_code, run_task, run_task_prefixed};
+use crate::utils::dep_graph::dependency_order;
+use colored::*;
+use indexmap::IndexMap;
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Outcome of one task in a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Succeeded,
+    /// Skipped because its inputs and outputs match the last successful run
+    UpToDate,
+    /// Exit code of the failed task
+    Failed(u16),
+    /// Not started because a dependency failed
+    Cancelled,
+}
+
+impl TaskStatus {
+    /// Whether dependents of a task with this status may start
+    pub fn is_success(&self) -> bool {
+        matches!(self, TaskStatus::Succeeded | TaskStatus::UpToDate)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskReport {
+    pub name: String,
+    pub status: TaskStatus,
+    pub duration: Option<Duration>,
+}
+
+/// Reports for every task of a run, in execution order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RunSummary {
+    pub reports: Vec<TaskReport>,
+}
+
+impl RunSummary {
+    pub fn success(&self) -> bool {
+        self.reports.iter().all(|report| report.status.is_success())
+    }
+
+    /// First failed task and its exit code
+    pub fn first_failure(&self) -> Option<(&str, u16)> {
+        self.reports.iter().find_map(|report| match report.status {
+            TaskStatus::Failed(code) => Some((report.name.as_str(), code)),
+            _ => None,
+        })
+    }
+
+    /// Render the summary as an aligned table
+    pub fn table(&self) -> String {
+        let width = self
+            .reports
+            .iter()
+            .map(|report| report.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("TASK".len());
+
+        let mut output = format!("{:<width$}  {:<11}  {}\n", "TASK", "STATUS", "DURATION");
+        for report in &self.reports {
+            let status = match report.status {
+                TaskStatus::Succeeded => format!("{:<11}", "ok").green(),
+                TaskStatus::UpToDate => format!("{:<11}", "up-to-date").dimmed(),
+                TaskStatus::Failed(code) => format!("{:<11}", format!("failed({})", code)).red(),
+                TaskStatus::Cancelled => format!("{:<11}", "cancelled").yellow(),
+            };
+            let duration = report
+                .duration
+                .map_or_else(|| "-".to_string(), |d| format!("{:.2}s", d.as_secs_f64()));
+            output.push_str(&format!(
+                "{:<width$}  {}  {}\n",
+                report.name, status, duration
+            ));
+        }
+        output
+    }
+}
+
+/// Runs tasks in dependency order, starting independent ones concurrently
+pub struct Scheduler {
+    tasks: Vec<Task>,
+    root: PathBuf,
+    env: IndexMap<String, String>,
+    jobs: usize,
+    force: bool,
+    secrets: Secrets,
+}
+
+impl Scheduler {
+    pub fn new(tasks: Vec<Task>, root: PathBuf, env: IndexMap<String, String>) -> Self {
+        let jobs = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            tasks,
+            root,
+            env,
+            jobs,
+            force: false,
+            secrets: Secrets::default(),
+        }
+    }
+
+    /// Maximum number of tasks running at the same time (at least 1)
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Run every task even when its fingerprint is unchanged
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Secret values of `env`, redacted from the output of every task and from
+    /// errors. With secrets, a task running alone no longer writes to the
+    /// terminal directly: its output is forwarded line by line.
+    pub fn with_secrets(mut self, secrets: Secrets) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Tasks needed to run `targets`, dependencies first
+    pub fn plan(&self, targets: &[String]) -> Result<Vec<String>, ProcessError> {
+        let graph: IndexMap<String, Vec<String>> = self
+            .tasks
+            .iter()
+            .map(|task| (task.name.clone(), task.depends_on.clone()))
+            .collect();
+
+        dependency_order(&graph, targets).map_err(|e| ProcessError::InvalidConfig {
+            error_msg: format!("Cannot schedule tasks: {}", e),
+            exit_status: 422,
+        })
+    }
+
+    /// Run `targets` and their dependencies. A failed task cancels every task
+    /// depending on it; unrelated tasks keep running.
+    pub async fn run(&self, targets: &[String]) -> Result<RunSummary, ProcessError> {
+        let plan = self.plan(targets)?;
+        let tasks: Vec<&Task> = plan
+            .iter()
+            .filter_map(|name| self.tasks.iter().find(|task| &task.name == name))
+            .collect();
+
+        let mut waiting_on: Vec<usize> = tasks
+            .iter()
+            .map(|task| task.depends_on.iter().collect::<HashSet<_>>().len())
+            .collect();
+        let mut ready: VecDeque<usize> = (0..tasks.len()).filter(|&i| waiting_on[i] == 0).collect();
+        let mut results: Vec<Option<(TaskStatus, Duration)>> = vec![None; tasks.len()];
+        let prefix_output = tasks.len() > 1;
+        let cache = Arc::new(Mutex::new(FingerprintCache::load(&self.root)));
+        let mut running = JoinSet::new();
+
+        loop {
+            while running.len() < self.jobs {
+                let Some(i) = ready.pop_front() else {
+                    break;
+                };
+                let task = tasks[i].clone();
+                let root = self.root.clone();
+                let env = self.env.clone();
+                let cache = Arc::clone(&cache);
+                let force = self.force;
+                let secrets = self.secrets.clone();
+                running.spawn(async move {
+                    let started = Instant::now();
+                    let current = current_fingerprint(&task, &root, &env).await;
+                    if let Some(current) = &current {
+                        let cached =
+                            cache.lock().unwrap().get(&task.name) == Some(current.as_str());
+                        if !force && cached && outputs_exist(&task, &root) {
+                            return (i, TaskStatus::UpToDate, started.elapsed());
+                        }
+                    }
+
+                    let result = if prefix_output {
+                        run_task_prefixed(&task, &root, &env, &secrets).await
+                    } else {
+                        run_task(&task, &root, &env, &secrets).await
+                    };
+                    let status = match result {
+                        Ok(status) if status.success() => TaskStatus::Succeeded,
+                        Ok(status) => TaskStatus::Failed(exit_code(&status)),
+                        Err(e) => {
+                            eprintln!("{} {}", "error:".red().bold(), secrets.redact_error(e));
+                            TaskStatus::Failed(127)
+                        }
+                    };
+                    if let (TaskStatus::Succeeded, Some(current)) = (status, current) {
+                        cache.lock().unwrap().insert(&task.name, current);
+                    }
+                    (i, status, started.elapsed())
+                });
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let (i, status, duration) = match joined {
+                Ok(joined) => joined,
+                Err(e) => {
+                    // Keep what the tasks that finished have recorded
+                    cache.lock().unwrap().save()?;
+                    return Err(ProcessError::TaskFailed {
+                        error_msg: format!("Task runner crashed: {}", e),
+                        exit_status: 1,
+                    });
+                }
+            };
+            results[i] = Some((status, duration));
+
+            // Dependents of a failed task never become ready and end up cancelled.
+            if status.is_success() {
+                for (j, task) in tasks.iter().enumerate() {
+                    if task.depends_on.contains(&tasks[i].name) {
+                        waiting_on[j] -= 1;
+                        if waiting_on[j] == 0 {
+                            ready.push_back(j);
+                        }
+                    }
+                }
+            }
+        }
+
+        cache.lock().unwrap().save()?;
+
+        let reports = tasks
+            .iter()
+            .zip(results)
+            .map(|(task, result)| TaskReport {
+                name: task.name.clone(),
+                status: result.map_or(TaskStatus::Cancelled, |(status, _)| status),
+                duration: result.map(|(_, duration)| duration),
+            })
+            .collect();
+        Ok(RunSummary { reports })
+    }
+}
+
+/// Fingerprint of `task`, hashed off the async runtime. Tasks whose inputs
+/// match no file or cannot be read are reported and always run.
+async fn current_fingerprint(
+    task: &Task,
+    root: &std::path::Path,
+    env: &IndexMap<String, String>,
+) -> Option<String> {
+    let (hashed, root, env) = (task.clone(), root.to_path_buf(), env.clone());
+    match tokio::task::spawn_blocking(move || fingerprint(&hashed, &root, &env)).await {
+        Ok(Ok(Fingerprint::Hash(hash))) => Some(hash),
+        Ok(Ok(Fingerprint::NoInputs)) => None,
+        Ok(Ok(Fingerprint::NoMatch)) => {
+            eprintln!(
+                "{} No file matches the inputs of '{}' ({}), it runs every time",
+                "warning:".yellow().bold(),
+                task.name,
+                task.inputs.join(", ")
+            );
+            None
+        }
+        Ok(Err(e)) => {
+            eprintln!("{} {}", "warning:".yellow().bold(), e);
+            None
+        }
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,164 @@
+use crate::parser::devspin_generator::{DevSpinConfig, DevSpinGenerator};
+use crate::parser::interpolate::{self, Layer};
+use crate::prelude::*;
+use crate::secrets::{self, open_store, Secrets};
+use crate::utils::{
+    devspin_finder::find_devspin_yml_parallel, dotenv, root_finder::get_root_no_param,
+};
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+/// Resolve the project root from an explicit `--root` or by scanning upward from the cwd
+pub fn resolve_root(root: Option<&str>) -> Result<PathBuf, ProcessError> {
+    let Some(root_path) = root else {
+        return get_root_no_param().map_err(|e| ProcessError::RootNotFound {
+            error_msg: format!("Could not find project root: {}", e),
+            exit_status: 404,
+        });
+    };
+
+    let root_buf = PathBuf::from(root_path);
+    if !root_buf.exists() {
+        return Err(ProcessError::RootNotFound {
+            error_msg: format!("Specified root directory does not exist: {}", root_path),
+            exit_status: 404,
+        });
+    }
+    if !root_buf.is_dir() {
+        return Err(ProcessError::RootNotFound {
+            error_msg: format!("Specified root path is not a directory: {}", root_path),
+            exit_status: 404,
+        });
+    }
+    Ok(root_buf)
+}
+
+/// Find devspin.yml under `root` and parse it
+pub fn load_config(root: &Path) -> Result<(PathBuf, DevSpinConfig), ProcessError> {
+    let config_path =
+        find_devspin_yml_parallel(root).map_err(|e| ProcessError::DevspinYmlNotFound {
+            error_msg: e,
+            exit_status: 404,
+        })?;
+
+    let config = DevSpinGenerator::parse_file(&config_path)?;
+
+    Ok((config_path, config))
+}
+
+/// `load_config` with the `env_file`s merged, the `secret:` values read from the
+/// secret store and the `${VAR}` references expanded. `overrides` are the
+/// `--env KEY=VALUE` of the command line. The returned `Secrets` must redact
+/// anything printed from the config
+pub fn load_resolved_config(
+    root: &Path,
+    overrides: &[(String, String)],
+) -> Result<(PathBuf, DevSpinConfig, Secrets), ProcessError> {
+    let (config_path, config) = load_config(root)?;
+    let (config, secrets) = resolve_config(&config_path, config, root, overrides)?;
+    Ok((config_path, config, secrets))
+}
+
+/// `load_config` resolved for the files generated from it: like
+/// [`load_resolved_config`], but without the process environment and the
+/// .env files of the root, so the output is the same on every machine and
+/// never holds local values. Only the config, its `env_file`s and `overrides`
+/// are read
+pub fn load_generate_config(
+    root: &Path,
+    overrides: &[(String, String)],
+) -> Result<(PathBuf, DevSpinConfig, Secrets), ProcessError> {
+    let (config_path, config) = load_config(root)?;
+    let layers = vec![Layer::new("--env", overrides.iter().cloned().collect())];
+    let (config, secrets) = resolve_with_layers(&config_path, config, layers)?;
+    Ok((config_path, config, secrets))
+}
+
+/// `config`, read from `config_path`, resolved as in [`load_resolved_config`]
+pub fn resolve_config(
+    config_path: &Path,
+    config: DevSpinConfig,
+    root: &Path,
+    overrides: &[(String, String)],
+) -> Result<(DevSpinConfig, Secrets), ProcessError> {
+    resolve_with_layers(config_path, config, env_layers(root, overrides)?)
+}
+
+/// `config` with its `env_file`s merged, then resolved against `layers`
+fn resolve_with_layers(
+    config_path: &Path,
+    mut config: DevSpinConfig,
+    mut layers: Vec<Layer>,
+) -> Result<(DevSpinConfig, Secrets), ProcessError> {
+    merge_env_files(config_path, &mut config)?;
+    let path = config_path.display().to_string();
+    let secrets = secrets::resolve(&mut config, &mut layers, &*open_store()?, &path)?;
+    let config = interpolate::resolve(&config, &layers, &path)
+        .map_err(|e| secrets.redact_error(e.into()))?;
+    Ok((config, secrets))
+}
+
+/// Sources that override the `env` of the config, highest priority first:
+/// `--env`, the process environment, then the .env.local and .env files of the root
+pub fn env_layers(root: &Path, overrides: &[(String, String)]) -> Result<Vec<Layer>, ProcessError> {
+    let mut layers = vec![
+        Layer::new("--env", overrides.iter().cloned().collect()),
+        Layer::process(),
+    ];
+    for name in [".env.local", ".env"] {
+        let vars = dotenv::load(root.join(name))?;
+        layers.push(Layer::new(name, vars));
+    }
+    Ok(layers)
+}
+
+/// The `env_file`s of the config, in the order they are listed (later files win)
+pub fn env_file_layers(config_path: &Path, files: &[String]) -> Result<Vec<Layer>, ProcessError> {
+    let dir = config_path.parent().unwrap_or(Path::new("."));
+    files
+        .iter()
+        .map(|file| {
+            let path = dir.join(file);
+            if !path.is_file() {
+                return Err(ProcessError::InvalidConfig {
+                    error_msg: format!(
+                        "{}: env_file {} does not exist",
+                        config_path.display(),
+                        path.display()
+                    ),
+                    exit_status: 422,
+                });
+            }
+            let vars = dotenv::load(&path)?;
+            Ok(Layer::new(file.as_str(), vars))
+        })
+        .collect()
+}
+
+/// Add the variables of the `env_file`s to `env` and to the `environment` of each
+/// service, below the ones written in the config
+fn merge_env_files(config_path: &Path, config: &mut DevSpinConfig) -> Result<(), ProcessError> {
+    let merge = |files: &mut Vec<String>, target: &mut IndexMap<String, String>| {
+        let mut vars = IndexMap::new();
+        for layer in env_file_layers(config_path, files)? {
+            vars.extend(layer.vars);
+        }
+        for (key, value) in vars {
+            target.entry(key).or_insert(value);
+        }
+        files.clear();
+        Ok::<(), ProcessError>(())
+    };
+    merge(&mut config.content.env_file, &mut config.content.env)?;
+    for service in &mut config.content.services {
+        merge(&mut service.env_file, &mut service.environment)?;
+    }
+    Ok(())
+}
+
+/// Name given to a new config: the project directory name
+pub fn default_project_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "my-devspin-project".to_string())
+}
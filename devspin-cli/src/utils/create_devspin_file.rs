@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub fn create_cfg_file(root: &str) -> Result<PathBuf, ProcessError> {
     // Create PathBuf for the root directory
@@ -16,3 +16,29 @@ pub fn create_cfg_file(root: &str) -> Result<PathBuf, ProcessError> {
 
     Ok(file_path)
 }
+
+/// Create `devspin.yml` in `root` holding `content`, replacing any previous file
+pub fn write_cfg_file(root: &str, content: &str) -> Result<PathBuf, ProcessError> {
+    let file_path = PathBuf::from(root).join("devspin.yml");
+
+    std::fs::write(&file_path, content).map_err(|e| ProcessError::CreateFileFailed {
+        error_msg: format!("Failed to write {}: {}", file_path.display(), e),
+        exit_status: 400,
+    })?;
+
+    Ok(file_path)
+}
+
+/// Copy `path` next to itself as `<name>.bak` (replacing an older backup) and return the copy
+pub fn backup_cfg_file(path: &Path) -> Result<PathBuf, ProcessError> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+
+    std::fs::copy(path, &backup).map_err(|e| ProcessError::CreateFileFailed {
+        error_msg: format!("Failed to back up {}: {}", path.display(), e),
+        exit_status: 400,
+    })?;
+
+    Ok(backup)
+}
@@ -0,0 +1,84 @@
+use indexmap::IndexMap;
+use std::fmt;
+
+/// Problem found while ordering a dependency graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// A requested node is not declared
+    UnknownNode(String),
+    /// `node` depends on something that is not declared
+    UnknownDependency { node: String, dependency: String },
+    /// Path of the cycle, starting and ending with the same node
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::UnknownNode(node) => write!(f, "'{}' is not declared", node),
+            GraphError::UnknownDependency { node, dependency } => {
+                write!(f, "'{}' depends on unknown '{}'", node, dependency)
+            }
+            GraphError::Cycle(path) => write!(f, "dependency cycle: {}", path.join(" -> ")),
+        }
+    }
+}
+
+/// Order `targets` and everything they depend on so that each node comes after
+/// its dependencies. An empty `targets` orders the whole graph.
+/// `graph` maps each node to the nodes it depends on; declaration order breaks ties.
+pub fn dependency_order(
+    graph: &IndexMap<String, Vec<String>>,
+    targets: &[String],
+) -> Result<Vec<String>, GraphError> {
+    let mut order = Vec::new();
+    let mut done = Vec::new();
+    let mut stack = Vec::new();
+
+    let roots: Vec<&String> = if targets.is_empty() {
+        graph.keys().collect()
+    } else {
+        targets.iter().collect()
+    };
+
+    for root in roots {
+        if !graph.contains_key(root) {
+            return Err(GraphError::UnknownNode(root.clone()));
+        }
+        visit(graph, root, &mut stack, &mut done, &mut order)?;
+    }
+    Ok(order)
+}
+
+fn visit<'g>(
+    graph: &'g IndexMap<String, Vec<String>>,
+    node: &'g String,
+    stack: &mut Vec<&'g String>,
+    done: &mut Vec<&'g String>,
+    order: &mut Vec<String>,
+) -> Result<(), GraphError> {
+    if done.contains(&node) {
+        return Ok(());
+    }
+    if let Some(start) = stack.iter().position(|n| *n == node) {
+        let mut cycle: Vec<String> = stack[start..].iter().map(|n| n.to_string()).collect();
+        cycle.push(node.clone());
+        return Err(GraphError::Cycle(cycle));
+    }
+
+    stack.push(node);
+    for dependency in &graph[node] {
+        let Some((key, _)) = graph.get_key_value(dependency) else {
+            return Err(GraphError::UnknownDependency {
+                node: node.clone(),
+                dependency: dependency.clone(),
+            });
+        };
+        visit(graph, key, stack, done, order)?;
+    }
+    stack.pop();
+
+    done.push(node);
+    order.push(node.clone());
+    Ok(())
+}
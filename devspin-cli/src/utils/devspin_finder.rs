@@ -3,6 +3,15 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
+/// Config file names, in order of preference when a directory has several
+pub const CONFIG_FILE_NAMES: &[&str] = &[
+    "devspin.yml",
+    "devspin.yaml",
+    ".devspin.yml",
+    "devspin.json",
+    "devspin.toml",
+];
+
 /// Find devspin.yml using parallel search with Rayon
 pub fn find_devspin_yml_parallel(root: impl AsRef<Path>) -> Result<PathBuf, String> {
     find_devspin_yml_with_timeout(root, Duration::from_secs(30))
@@ -17,10 +26,11 @@ pub fn find_devspin_yml_with_timeout(
 
     // Quick root check first (common case optimization)
     let root_path = root.as_ref();
-    let root_file = root_path.join("devspin.yml");
-    if root_file.is_file() {
-        println!("Found in root after {:?}", start.elapsed());
-        return Ok(root_file);
+    for name in CONFIG_FILE_NAMES {
+        let root_file = root_path.join(name);
+        if root_file.is_file() {
+            return Ok(root_file);
+        }
     }
 
     // Validate root exists
@@ -35,8 +45,6 @@ pub fn find_devspin_yml_with_timeout(
         return Err(format!("'{}' is not a directory", root.as_ref().display()));
     }
 
-    println!("Starting parallel search in: {}", root.as_ref().display());
-
     // Single parallel search with filter_entry
     let result = WalkDir::new(&root)
         .follow_links(true)
@@ -52,27 +60,22 @@ pub fn find_devspin_yml_with_timeout(
         })
         .find_any(|entry| {
             entry.file_type().is_file()
-                && ["devspin.yml", "devspin.yaml", ".devspin.yml"]
+                && CONFIG_FILE_NAMES
                     .iter()
                     .any(|&name| entry.file_name() == name)
         })
         .map(|entry| entry.path().to_path_buf());
 
     match result {
-        Some(path) => {
-            println!("Found in {:?}: {}", start.elapsed(), path.display());
-            Ok(path)
-        }
+        Some(path) => Ok(path),
         None => {
-            let elapsed = start.elapsed();
-            if elapsed > timeout {
+            if start.elapsed() > timeout {
                 Err(format!(
                     "Search timed out after {:?} in '{}'",
                     timeout,
                     root.as_ref().display()
                 ))
             } else {
-                println!("Not found after {:?}", elapsed);
                 Err(format!(
                     "devspin.yml not found under '{}'",
                     root.as_ref().display()
@@ -82,8 +85,19 @@ pub fn find_devspin_yml_with_timeout(
     }
 }
 
+/// Config file of `dir` or of its closest parent that has one, without walking
+/// down the tree (cheap enough to run at every shell prompt)
+pub fn find_devspin_yml_upward(dir: impl AsRef<Path>) -> Option<PathBuf> {
+    dir.as_ref().ancestors().find_map(|dir| {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
 /// Check if we should skip this entry (and its children)
-fn should_skip_entry(entry: &walkdir::DirEntry) -> bool {
+pub(crate) fn should_skip_entry(entry: &walkdir::DirEntry) -> bool {
     let skip_dirs: [&str; 51] = [
         // Node/JavaScript
         "node_modules",
@@ -0,0 +1,145 @@
+use colored::*;
+
+/// Lines of unchanged context kept around each change
+pub const CONTEXT: usize = 3;
+
+/// One line of a diff between two texts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff of `old` and `new` from their longest common subsequence
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lengths[i][j]: common subsequence length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    lines
+}
+
+/// Unified diff of `old` and `new` labelled `path`, empty when they have the same lines
+pub fn unified(old: &str, new: &str, path: &str) -> String {
+    let lines = diff_lines(old, new);
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Group changes whose context overlaps into hunks of line indexes
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = format!("--- a/{}\n+++ b/{}\n", path, path);
+    for (start, end) in hunks {
+        // Line numbers of the hunk start in the old and new text
+        let (mut old_line, mut new_line) = (1, 1);
+        for line in &lines[..start] {
+            match line {
+                DiffLine::Same(_) => {
+                    old_line += 1;
+                    new_line += 1;
+                }
+                DiffLine::Removed(_) => old_line += 1,
+                DiffLine::Added(_) => new_line += 1,
+            }
+        }
+        let hunk = &lines[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Removed(_)))
+            .count();
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            if old_count == 0 {
+                old_line - 1
+            } else {
+                old_line
+            },
+            old_count,
+            if new_count == 0 {
+                new_line - 1
+            } else {
+                new_line
+            },
+            new_count
+        ));
+        for line in hunk {
+            let (prefix, text) = match line {
+                DiffLine::Same(text) => (' ', text),
+                DiffLine::Removed(text) => ('-', text),
+                DiffLine::Added(text) => ('+', text),
+            };
+            output.push(prefix);
+            output.push_str(text);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Color the lines of a unified diff for the terminal
+pub fn paint(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let painted = if line.starts_with("---") || line.starts_with("+++") {
+                line.bold()
+            } else if line.starts_with("@@") {
+                line.cyan()
+            } else if line.starts_with('-') {
+                line.red()
+            } else if line.starts_with('+') {
+                line.green()
+            } else {
+                line.normal()
+            };
+            format!("{}\n", painted)
+        })
+        .collect()
+}
@@ -0,0 +1,96 @@
+use crate::error::DevSpinError;
+use indexmap::IndexMap;
+use std::path::Path;
+
+/// Read the `KEY=VALUE` lines of a .env file, empty when the file does not exist
+pub fn load(path: impl AsRef<Path>) -> Result<IndexMap<String, String>, DevSpinError> {
+    let path = path.as_ref();
+    let display = path.display().to_string();
+    match std::fs::read_to_string(path) {
+        Ok(source) => parse(&source, &display),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(IndexMap::new()),
+        Err(source) => Err(DevSpinError::Io {
+            path: display,
+            source,
+        }),
+    }
+}
+
+/// Parse .env content: `KEY=VALUE` lines with an optional `export`, `#` comments,
+/// 'literal' values and "quoted" values with `\n`, `\t`, `\"` and `\\` escapes
+pub fn parse(source: &str, path: &str) -> Result<IndexMap<String, String>, DevSpinError> {
+    let mut vars = IndexMap::new();
+    for (index, line) in source.lines().enumerate() {
+        let error = |column: usize, message: String| {
+            DevSpinError::parse(path, line, index + 1, column, message)
+        };
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let (offset, assignment) = match trimmed.strip_prefix("export ") {
+            Some(rest) => (
+                indent + 7 + (rest.len() - rest.trim_start().len()),
+                rest.trim_start(),
+            ),
+            None => (indent, trimmed),
+        };
+
+        let Some((key, value)) = assignment.split_once('=') else {
+            return Err(error(offset + 1, "expected `KEY=VALUE`".to_string()));
+        };
+        let key = key.trim_end();
+        if !is_name(key) {
+            return Err(error(
+                offset + 1,
+                format!("`{}` is not a valid variable name", key),
+            ));
+        }
+        let value_column = offset + assignment.len() - value.trim_start().len() + 1;
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => unquote(&value[1..], quote)
+                .ok_or_else(|| error(value_column, format!("unterminated {} quote", quote)))?,
+            // An unquoted value ends at the first ` #`
+            _ => value
+                .split_once(" #")
+                .map_or(value, |(value, _)| value)
+                .trim_end()
+                .to_string(),
+        };
+        vars.insert(key.to_string(), value);
+    }
+    Ok(vars)
+}
+
+/// The text up to the closing `quote`; escapes are only read in double quotes
+fn unquote(text: &str, quote: char) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == quote => return Some(value),
+            '\\' if quote == '"' => match chars.next()? {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                other @ ('"' | '\\' | '$') => value.push(other),
+                other => {
+                    value.push('\\');
+                    value.push(other);
+                }
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+fn is_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
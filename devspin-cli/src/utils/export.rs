@@ -0,0 +1,86 @@
+use crate::parser::validator::is_valid_env_name;
+use crate::prelude::*;
+use crate::utils::shell::{fish_quote, powershell_quote, sh_quote};
+use indexmap::IndexMap;
+
+/// How `devspin env` prints variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// `export KEY='value'`, for `eval "$(devspin env)"` in sh, bash and zsh
+    Sh,
+    /// `set -gx KEY 'value'`, for `devspin env --format fish | source`
+    Fish,
+    /// `$env:KEY = 'value'`, for `devspin env --format powershell | Invoke-Expression`
+    Powershell,
+    /// `KEY=value` lines, quoted when needed, as read back by `.env` and `env_file`
+    Dotenv,
+    /// One JSON object
+    Json,
+}
+
+/// `vars` in `format`, one line per variable (a pretty object for JSON).
+/// Fails on a name that is not a valid variable name: only values are quoted,
+/// so such a name would be run as code by the shell evaluating the output.
+pub fn render(
+    vars: &IndexMap<String, String>,
+    format: ExportFormat,
+) -> Result<String, ProcessError> {
+    if let Some(key) = vars.keys().find(|key| !is_valid_env_name(key)) {
+        return Err(ProcessError::InvalidConfig {
+            error_msg: format!(
+                "invalid environment variable name `{}` (use letters, digits and `_`, not starting with a digit)",
+                key.escape_debug()
+            ),
+            exit_status: 422,
+        });
+    }
+    if format == ExportFormat::Json {
+        let json = serde_json::to_string_pretty(vars).expect("a map of strings serializes");
+        return Ok(json + "\n");
+    }
+    let lines = vars
+        .iter()
+        .map(|(key, value)| {
+            let line = match format {
+                ExportFormat::Sh => format!("export {}={}", key, sh_quote(value)),
+                ExportFormat::Fish => format!("set -gx {} {}", key, fish_quote(value)),
+                ExportFormat::Powershell => format!("$env:{} = {}", key, powershell_quote(value)),
+                ExportFormat::Dotenv => format!("{}={}", key, dotenv_quote(value)),
+                ExportFormat::Json => unreachable!("rendered above"),
+            };
+            line + "\n"
+        })
+        .collect();
+    Ok(lines)
+}
+
+/// Lines removing `keys` from the environment, empty for the formats that only
+/// describe variables (dotenv, JSON). Invalid names are skipped.
+pub fn render_unset<'a>(keys: impl IntoIterator<Item = &'a str>, format: ExportFormat) -> String {
+    keys.into_iter()
+        .filter(|key| is_valid_env_name(key))
+        .filter_map(|key| match format {
+            ExportFormat::Sh => Some(format!("unset {}\n", key)),
+            ExportFormat::Fish => Some(format!("set -e {}\n", key)),
+            ExportFormat::Powershell => Some(format!(
+                "Remove-Item Env:{} -ErrorAction SilentlyContinue\n",
+                key
+            )),
+            ExportFormat::Dotenv | ExportFormat::Json => None,
+        })
+        .collect()
+}
+
+/// `value` as the .env parser reads it: bare when it is plain, otherwise "double quoted"
+fn dotenv_quote(value: &str) -> String {
+    let plain = value.trim() == value && !value.contains([' ', '\t', '#', '"', '\'', '\\', '\n']);
+    if plain {
+        return value.to_string();
+    }
+    let escaped = value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+        .replace('\t', r"\t");
+    format!("\"{}\"", escaped)
+}
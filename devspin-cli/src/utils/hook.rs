@@ -0,0 +1,326 @@
+use crate::parser::validator::is_valid_env_name;
+use crate::prelude::*;
+use crate::utils::export::{self, ExportFormat};
+use crate::utils::shell::{fish_quote, sh_quote};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory of the devspin.yml the hook loaded
+pub const ROOT_VAR: &str = "DEVSPIN_HOOK_ROOT";
+/// Stamp of the files the loaded variables come from
+pub const STAMP_VAR: &str = "DEVSPIN_HOOK_STAMP";
+/// Values the loaded variables had before, as a JSON object (`null` when unset)
+pub const BACKUP_VAR: &str = "DEVSPIN_HOOK_BACKUP";
+/// Overrides the directory devspin caches data in
+pub const CACHE_VAR: &str = "DEVSPIN_CACHE_DIR";
+/// Overrides the directory devspin keeps the configs allowed by `devspin allow` in
+pub const DATA_VAR: &str = "DEVSPIN_DATA_DIR";
+
+/// Shells `devspin hook` supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HookShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl HookShell {
+    /// Format the hook evaluates
+    pub fn export_format(&self) -> ExportFormat {
+        match self {
+            HookShell::Bash | HookShell::Zsh => ExportFormat::Sh,
+            HookShell::Fish => ExportFormat::Fish,
+        }
+    }
+
+    /// Hook running `<devspin> hook <shell> --apply` before each prompt, and
+    /// on `cd` where the shell can tell
+    pub fn script(&self, devspin: &Path) -> String {
+        let devspin = devspin.display().to_string();
+        match self {
+            HookShell::Bash => format!(
+                r#"_devspin_hook() {{
+  local previous_exit_status=$?
+  eval "$({} hook bash --apply)"
+  return $previous_exit_status
+}}
+if [[ ";${{PROMPT_COMMAND[*]:-}};" != *";_devspin_hook;"* ]]; then
+  PROMPT_COMMAND="_devspin_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi
+"#,
+                sh_quote(&devspin)
+            ),
+            HookShell::Zsh => format!(
+                r#"_devspin_hook() {{
+  eval "$({} hook zsh --apply)"
+}}
+typeset -ag precmd_functions chpwd_functions
+if (( ! ${{precmd_functions[(I)_devspin_hook]}} )); then
+  precmd_functions=(_devspin_hook $precmd_functions)
+fi
+if (( ! ${{chpwd_functions[(I)_devspin_hook]}} )); then
+  chpwd_functions=(_devspin_hook $chpwd_functions)
+fi
+"#,
+                sh_quote(&devspin)
+            ),
+            HookShell::Fish => format!(
+                r#"function __devspin_hook --on-event fish_prompt --on-variable PWD
+    {} hook fish --apply | source
+end
+"#,
+                fish_quote(&devspin)
+            ),
+        }
+    }
+}
+
+/// What the hook loaded in the current shell, read from its variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookState {
+    pub root: Option<PathBuf>,
+    pub stamp: String,
+    /// Loaded variables and the value each one had before
+    pub backup: IndexMap<String, Option<String>>,
+}
+
+impl HookState {
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    /// State from the variables `get` returns; a damaged backup is treated as
+    /// empty and names that are not valid variable names are dropped from it
+    pub fn from_vars(get: impl Fn(&str) -> Option<String>) -> Self {
+        let backup: IndexMap<String, Option<String>> = get(BACKUP_VAR)
+            .and_then(|backup| serde_json::from_str(&backup).ok())
+            .unwrap_or_default();
+        Self {
+            root: get(ROOT_VAR)
+                .filter(|root| !root.is_empty())
+                .map(PathBuf::from),
+            stamp: get(STAMP_VAR).unwrap_or_default(),
+            backup: backup
+                .into_iter()
+                .filter(|(key, _)| is_valid_env_name(key))
+                .collect(),
+        }
+    }
+
+    /// Value `key` has without the loaded variables: its backup, or `current`
+    pub fn original(&self, key: &str, current: Option<String>) -> Option<String> {
+        match self.backup.get(key) {
+            Some(previous) => previous.clone(),
+            None => current,
+        }
+    }
+
+    /// Commands putting back the variables the hook changed, and forgetting the state
+    pub fn unload(&self, format: ExportFormat) -> Result<String, ProcessError> {
+        let mut script = restore(&self.backup, format)?;
+        script.push_str(&export::render_unset(
+            [ROOT_VAR, STAMP_VAR, BACKUP_VAR],
+            format,
+        ));
+        Ok(script)
+    }
+
+    /// Commands replacing what is loaded with the variables of `entry`, with
+    /// `current` giving the value of a variable in the shell
+    pub fn load(
+        &self,
+        entry: &CacheEntry,
+        format: ExportFormat,
+        current: impl Fn(&str) -> Option<String>,
+    ) -> Result<String, ProcessError> {
+        let backup: IndexMap<String, Option<String>> = entry
+            .vars
+            .keys()
+            .map(|key| (key.clone(), self.original(key, current(key))))
+            .collect();
+        // Variables of the previous project this one does not set again
+        let stale: IndexMap<String, Option<String>> = self
+            .backup
+            .iter()
+            .filter(|(key, _)| !entry.vars.contains_key(*key))
+            .map(|(key, previous)| (key.clone(), previous.clone()))
+            .collect();
+
+        let root = entry.config.parent().unwrap_or(Path::new("."));
+        let state = IndexMap::from([
+            (ROOT_VAR.to_string(), root.display().to_string()),
+            (STAMP_VAR.to_string(), entry.stamp()),
+            (
+                BACKUP_VAR.to_string(),
+                serde_json::to_string(&backup).expect("a map of strings serializes"),
+            ),
+        ]);
+        let mut script = restore(&stale, format)?;
+        script.push_str(&export::render(&entry.vars, format)?);
+        script.push_str(&export::render(&state, format)?);
+        Ok(script)
+    }
+}
+
+/// Commands setting each variable back to its previous value, or unsetting it
+fn restore(
+    backup: &IndexMap<String, Option<String>>,
+    format: ExportFormat,
+) -> Result<String, ProcessError> {
+    let set: IndexMap<String, String> = backup
+        .iter()
+        .filter_map(|(key, previous)| Some((key.clone(), previous.clone()?)))
+        .collect();
+    let unset = backup
+        .iter()
+        .filter(|(_, previous)| previous.is_none())
+        .map(|(key, _)| key.as_str());
+    Ok(export::render_unset(unset, format) + &export::render(&set, format)?)
+}
+
+/// Variables of a project as loaded by the hook, valid as long as the files
+/// they come from keep their modification time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub config: PathBuf,
+    /// Modification time of each source file, `None` for a file that does not exist
+    pub sources: IndexMap<PathBuf, Option<u128>>,
+    pub vars: IndexMap<String, String>,
+    /// Variables left out because they hold a secret
+    pub withheld: Vec<String>,
+    /// Names left out because they are not valid variable names
+    #[serde(default)]
+    pub invalid: Vec<String>,
+}
+
+impl CacheEntry {
+    /// Entry for `config` with the current modification time of `sources`
+    pub fn new(config: &Path, sources: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            config: config.to_path_buf(),
+            sources: sources
+                .into_iter()
+                .map(|path| {
+                    let modified = modified(&path);
+                    (path, modified)
+                })
+                .collect(),
+            vars: IndexMap::new(),
+            withheld: Vec::new(),
+            invalid: Vec::new(),
+        }
+    }
+
+    /// Whether every source still has the modification time it was read with
+    pub fn is_fresh(&self) -> bool {
+        self.sources
+            .iter()
+            .all(|(path, modified_at)| modified(path) == *modified_at)
+    }
+
+    /// Short hash of the sources and their modification times
+    pub fn stamp(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (path, modified_at) in &self.sources {
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update(format!("={:?};", modified_at).as_bytes());
+        }
+        format!("{:x}", hasher.finalize())[..16].to_string()
+    }
+}
+
+/// Modification time of `path` in nanoseconds, `None` when it does not exist
+pub fn modified(path: &Path) -> Option<u128> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+/// `DEVSPIN_CACHE_DIR`, or `devspin` in the user cache dir, with `hook` appended
+pub fn cache_dir() -> Option<PathBuf> {
+    let dir = match std::env::var_os(CACHE_VAR).filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::cache_dir()?.join("devspin"),
+    };
+    Some(dir.join("hook"))
+}
+
+/// Cache file of `config` in `dir`
+pub fn cache_path(dir: &Path, config: &Path) -> PathBuf {
+    dir.join(format!("{}.json", path_hash(config)))
+}
+
+/// Short hash of `path`, naming the files devspin keeps about it
+fn path_hash(path: &Path) -> String {
+    let hash = format!("{:x}", Sha256::digest(path.to_string_lossy().as_bytes()));
+    hash[..16].to_string()
+}
+
+/// Entry at `path`, `None` when it is missing or unreadable, or holds a name
+/// that is not a valid variable name
+pub fn read_cache(path: &Path) -> Option<CacheEntry> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+    entry
+        .vars
+        .keys()
+        .all(|key| is_valid_env_name(key))
+        .then_some(entry)
+}
+
+pub fn write_cache(path: &Path, entry: &CacheEntry) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_string_pretty(entry).expect("a cache entry serializes");
+    std::fs::write(path, content + "\n")
+}
+
+/// `DEVSPIN_DATA_DIR`, or `devspin` in the user data dir, with `allow` appended
+pub fn allow_dir() -> Option<PathBuf> {
+    let dir = match std::env::var_os(DATA_VAR).filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::data_dir()?.join("devspin"),
+    };
+    Some(dir.join("allow"))
+}
+
+/// File in `dir` recording the content `config` was allowed with
+fn allow_path(dir: &Path, config: &Path) -> PathBuf {
+    let config = config
+        .canonicalize()
+        .unwrap_or_else(|_| config.to_path_buf());
+    dir.join(path_hash(&config))
+}
+
+fn content_hash(config: &Path) -> std::io::Result<String> {
+    Ok(format!("{:x}", Sha256::digest(std::fs::read(config)?)))
+}
+
+/// Whether `config` was allowed in `dir` and has not changed since
+pub fn is_allowed(dir: &Path, config: &Path) -> bool {
+    match (
+        std::fs::read_to_string(allow_path(dir, config)),
+        content_hash(config),
+    ) {
+        (Ok(allowed), Ok(current)) => allowed.trim() == current,
+        _ => false,
+    }
+}
+
+/// Let the hook load `config` as long as its content stays the same
+pub fn allow(dir: &Path, config: &Path) -> std::io::Result<()> {
+    let hash = content_hash(config)?;
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(allow_path(dir, config), hash + "\n")
+}
+
+/// Stop the hook from loading `config`
+pub fn revoke(dir: &Path, config: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(allow_path(dir, config)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
@@ -1,3 +1,13 @@
+pub mod config_loader;
 pub mod create_devspin_file;
+pub mod dep_graph;
 pub mod devspin_finder;
+pub mod diff;
+pub mod dotenv;
+pub mod export;
+pub mod hook;
+pub mod prompt;
 pub mod root_finder;
+pub mod shell;
+pub mod stack_detector;
+pub mod templates;
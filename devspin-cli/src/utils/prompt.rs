@@ -0,0 +1,32 @@
+use std::io::{BufRead, Write};
+
+/// Ask `question` on stdin and return the answer, or `default` when it is empty
+/// (or stdin is closed)
+pub fn ask(question: &str, default: &str) -> String {
+    if default.is_empty() {
+        print!("{}: ", question);
+    } else {
+        print!("{} [{}]: ", question, default);
+    }
+    let answer = read_answer();
+    if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer
+    }
+}
+
+/// Ask a yes/no question on stdin; anything but `y` / `yes` (or no input) is a no
+pub fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    matches!(read_answer().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn read_answer() -> String {
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return String::new();
+    }
+    answer.trim().to_string()
+}
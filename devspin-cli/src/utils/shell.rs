@@ -0,0 +1,152 @@
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+
+/// Shells devspin knows how to put a prompt marker in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    Cmd,
+    /// Any other shell, told about the marker through `PS1` only
+    Posix,
+}
+
+impl ShellKind {
+    /// Kind of the shell started by `program`, from its file name (`/usr/bin/zsh`, `pwsh.exe`)
+    pub fn detect(program: &str) -> Self {
+        // Windows paths are split here too, `Path` only knows the platform's separator
+        let file_name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+        let stem = file_name.to_lowercase();
+        match stem.strip_suffix(".exe").unwrap_or(&stem) {
+            "bash" => ShellKind::Bash,
+            "zsh" => ShellKind::Zsh,
+            "fish" => ShellKind::Fish,
+            "pwsh" | "powershell" => ShellKind::PowerShell,
+            "cmd" => ShellKind::Cmd,
+            _ => ShellKind::Posix,
+        }
+    }
+
+    /// Arguments, environment and init files that start this shell
+    /// interactively with `(marker) ` in front of the user's own prompt. Init
+    /// files are placed in `dir`; the user's rc files are still read.
+    pub fn launch(&self, marker: &str, dir: &Path) -> Launch {
+        let prefix = format!("({}) ", marker);
+        let mut launch = Launch::default();
+        match self {
+            ShellKind::Bash => {
+                let rcfile = dir.join("bashrc");
+                launch.files.push((
+                    rcfile.clone(),
+                    format!(
+                        "[ -f ~/.bashrc ] && . ~/.bashrc\nPS1={}\"$PS1\"\n",
+                        sh_quote(&prefix)
+                    ),
+                ));
+                launch.args = vec![
+                    "--rcfile".to_string(),
+                    rcfile.display().to_string(),
+                    "-i".to_string(),
+                ];
+            }
+            ShellKind::Zsh => {
+                // zsh reads its rc files from ZDOTDIR; ours read the user's and restore it
+                let user_dir = "${DEVSPIN_ZDOTDIR:-$HOME}";
+                launch.files.push((
+                    dir.join(".zshenv"),
+                    format!("[ -f \"{0}/.zshenv\" ] && . \"{0}/.zshenv\"\n", user_dir),
+                ));
+                launch.files.push((
+                    dir.join(".zshrc"),
+                    format!(
+                        "ZDOTDIR=\"{}\"\nunset DEVSPIN_ZDOTDIR\n[ -f \"$ZDOTDIR/.zshrc\" ] && . \"$ZDOTDIR/.zshrc\"\nPROMPT={}\"$PROMPT\"\n",
+                        user_dir,
+                        sh_quote(&prefix)
+                    ),
+                ));
+                if let Ok(user_dir) = std::env::var("ZDOTDIR") {
+                    launch.env.insert("DEVSPIN_ZDOTDIR".to_string(), user_dir);
+                }
+                launch
+                    .env
+                    .insert("ZDOTDIR".to_string(), dir.display().to_string());
+                launch.args = vec!["-i".to_string()];
+            }
+            ShellKind::Fish => {
+                launch.args = vec![
+                    "--init-command".to_string(),
+                    format!(
+                        "functions --copy fish_prompt __devspin_fish_prompt; function fish_prompt; printf '%s' {}; __devspin_fish_prompt; end",
+                        fish_quote(&prefix)
+                    ),
+                ];
+            }
+            ShellKind::PowerShell => {
+                launch.args = vec![
+                    "-NoExit".to_string(),
+                    "-Command".to_string(),
+                    format!(
+                        "$__devspinPrompt = $function:prompt; function global:prompt {{ {} + (& $__devspinPrompt) }}",
+                        powershell_quote(&prefix)
+                    ),
+                ];
+            }
+            ShellKind::Cmd => {
+                launch.env.insert(
+                    "PROMPT".to_string(),
+                    format!("{}$P$G", prefix.replace('$', "$$")),
+                );
+            }
+            ShellKind::Posix => {
+                launch
+                    .env
+                    .insert("PS1".to_string(), format!("{}$ ", prefix));
+            }
+        }
+        launch
+    }
+}
+
+/// How to start a shell: extra arguments, environment and the init files they refer to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Launch {
+    pub args: Vec<String>,
+    pub env: IndexMap<String, String>,
+    /// Files to write before starting the shell, as (path, content)
+    pub files: Vec<(PathBuf, String)>,
+}
+
+impl Launch {
+    /// Write the init files into their directory, which should be new and
+    /// private to the user: the shell runs them. A file already there is an
+    /// error rather than overwritten or followed.
+    pub fn write_files(&self) -> std::io::Result<()> {
+        use std::io::Write;
+
+        for (path, content) in &self.files {
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)?
+                .write_all(content.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` as one word for sh, bash and zsh
+pub fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `value` as one word for fish
+pub fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// `value` as a PowerShell string literal
+pub fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
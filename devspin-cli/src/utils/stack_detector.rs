@@ -0,0 +1,263 @@
+use crate::parser::devspin_generator::{
+    DevSpinConfig, DevSpinConfigContent, DevSpinConfigHeader, SpinFeature, Task,
+};
+use crate::parser::migrate::CURRENT_VERSION;
+use indexmap::IndexMap;
+use std::path::Path;
+
+/// Base image of the `devspin init` template, replaced by the detected one
+pub const PLACEHOLDER_BASE: &str = "ubuntu:latest";
+
+/// Language ecosystems recognised from the manifests `root_finder` looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stack {
+    Rust,
+    Node,
+    Python,
+    Ruby,
+    Go,
+}
+
+impl Stack {
+    /// Every stack, in the order `root_finder` checks their manifests
+    pub const ALL: [Stack; 5] = [
+        Stack::Rust,
+        Stack::Node,
+        Stack::Python,
+        Stack::Ruby,
+        Stack::Go,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stack::Rust => "rust",
+            Stack::Node => "node",
+            Stack::Python => "python",
+            Stack::Ruby => "ruby",
+            Stack::Go => "go",
+        }
+    }
+
+    /// Files whose presence in the project root marks the stack
+    pub fn indicators(&self) -> &'static [&'static str] {
+        match self {
+            Stack::Rust => &["Cargo.toml"],
+            Stack::Node => &["package.json"],
+            Stack::Python => &["requirements.txt", "setup.py", "pyproject.toml"],
+            Stack::Ruby => &["Gemfile"],
+            Stack::Go => &["go.mod"],
+        }
+    }
+
+    /// Image used as `base` when this is the main stack
+    pub fn base_image(&self) -> &'static str {
+        match self {
+            Stack::Rust => "rust:1",
+            Stack::Node => "node:20",
+            Stack::Python => "python:3.12",
+            Stack::Ruby => "ruby:3.3",
+            Stack::Go => "golang:1.22",
+        }
+    }
+
+    /// Packages the stack needs on top of its base image
+    fn system_packages(&self) -> &'static [&'static str] {
+        match self {
+            Stack::Rust => &["pkg-config", "libssl-dev"],
+            Stack::Python | Stack::Ruby => &["build-essential"],
+            Stack::Node | Stack::Go => &[],
+        }
+    }
+
+    /// Packages installing the toolchain when another stack provides the base
+    fn toolchain_packages(&self) -> &'static [&'static str] {
+        match self {
+            Stack::Rust => &["cargo"],
+            Stack::Node => &["nodejs", "npm"],
+            Stack::Python => &["python3", "python3-pip"],
+            Stack::Ruby => &["ruby-full"],
+            Stack::Go => &["golang"],
+        }
+    }
+
+    /// Whether the lint and format spin-features have tools for this stack
+    fn has_spin_tools(&self) -> bool {
+        !matches!(self, Stack::Ruby)
+    }
+
+    /// `build`, `test` and `run` commands of the stack in `root`
+    fn commands(&self, root: &Path) -> Vec<(&'static str, String)> {
+        let exists = |name: &str| root.join(name).exists();
+        let mut commands = Vec::new();
+        match self {
+            Stack::Rust => {
+                commands.push(("build", "cargo build".to_string()));
+                commands.push(("test", "cargo test".to_string()));
+                commands.push(("run", "cargo run".to_string()));
+            }
+            Stack::Node => {
+                let manager = if exists("pnpm-lock.yaml") {
+                    "pnpm"
+                } else if exists("yarn.lock") {
+                    "yarn"
+                } else if exists("bun.lockb") {
+                    "bun"
+                } else {
+                    "npm"
+                };
+                let build = match manager {
+                    "npm" | "bun" => format!("{} run build", manager),
+                    _ => format!("{} build", manager),
+                };
+                commands.push(("build", build));
+                commands.push(("test", format!("{} test", manager)));
+                commands.push(("run", format!("{} start", manager)));
+            }
+            Stack::Python => {
+                let poetry = std::fs::read_to_string(root.join("pyproject.toml"))
+                    .is_ok_and(|manifest| manifest.contains("[tool.poetry]"));
+                let (install, prefix) = if poetry {
+                    ("poetry install".to_string(), "poetry run ")
+                } else if exists("requirements.txt") {
+                    ("pip install -r requirements.txt".to_string(), "")
+                } else {
+                    ("pip install -e .".to_string(), "")
+                };
+                commands.push(("build", install));
+                commands.push(("test", format!("{}python -m pytest", prefix)));
+                if exists("manage.py") {
+                    commands.push(("run", format!("{}python manage.py runserver", prefix)));
+                } else if let Some(entry) = ["main.py", "app.py"].into_iter().find(|f| exists(f)) {
+                    commands.push(("run", format!("{}python {}", prefix, entry)));
+                }
+            }
+            Stack::Ruby => {
+                commands.push(("build", "bundle install".to_string()));
+                let test = if exists("spec") {
+                    "bundle exec rspec"
+                } else {
+                    "bundle exec rake test"
+                };
+                commands.push(("test", test.to_string()));
+                if exists("bin/rails") {
+                    commands.push(("run", "bin/rails server".to_string()));
+                } else if exists("config.ru") {
+                    commands.push(("run", "bundle exec rackup".to_string()));
+                }
+            }
+            Stack::Go => {
+                commands.push(("build", "go build ./...".to_string()));
+                commands.push(("test", "go test ./...".to_string()));
+                commands.push(("run", "go run .".to_string()));
+            }
+        }
+        commands
+    }
+}
+
+/// What `devspin config` inferred from the project files
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Detection {
+    /// Detected stacks, the main one (providing `base`) first
+    pub stacks: Vec<Stack>,
+    pub base: Option<String>,
+    pub system: Vec<String>,
+    pub tasks: Vec<Task>,
+    pub spin_features: IndexMap<String, SpinFeature>,
+}
+
+/// Detect the stacks of the project in `root` and the config they suggest.
+/// The main stack names its tasks `build`, `test` and `run`; the others are
+/// prefixed with the stack name (`node-build`).
+pub fn detect(root: &Path) -> Detection {
+    let stacks: Vec<Stack> = Stack::ALL
+        .into_iter()
+        .filter(|stack| stack.indicators().iter().any(|f| root.join(f).is_file()))
+        .collect();
+
+    let mut detection = Detection {
+        base: stacks.first().map(|stack| stack.base_image().to_string()),
+        ..Detection::default()
+    };
+    for (index, stack) in stacks.iter().enumerate() {
+        let main = index == 0;
+        let toolchain: &[&str] = if main {
+            &[]
+        } else {
+            stack.toolchain_packages()
+        };
+        for package in stack.system_packages().iter().chain(toolchain) {
+            if !detection.system.iter().any(|p| p == package) {
+                detection.system.push(package.to_string());
+            }
+        }
+
+        for (name, command) in stack.commands(root) {
+            detection.tasks.push(Task {
+                name: if main {
+                    name.to_string()
+                } else {
+                    format!("{}-{}", stack.name(), name)
+                },
+                command,
+                ..Task::default()
+            });
+        }
+    }
+
+    if stacks.iter().any(Stack::has_spin_tools) {
+        for feature in ["lint", "format"] {
+            detection.spin_features.insert(
+                feature.to_string(),
+                SpinFeature {
+                    enabled: true,
+                    modes: vec!["all".to_string()],
+                },
+            );
+        }
+    }
+    detection.stacks = stacks;
+    detection
+}
+
+impl Detection {
+    /// `config` completed with the detection. Values already set by the user
+    /// win: `base` is only replaced while it is the init placeholder, and
+    /// existing packages, tasks and features are kept as they are.
+    pub fn merge_into(&self, config: &DevSpinConfig) -> DevSpinConfig {
+        let mut merged = config.clone();
+        if let Some(base) = &self.base {
+            if merged.header.base.is_empty() || merged.header.base == PLACEHOLDER_BASE {
+                merged.header.base = base.clone();
+            }
+        }
+
+        let content = &mut merged.content;
+        for package in &self.system {
+            if !content.system.contains(package) {
+                content.system.push(package.clone());
+            }
+        }
+        for task in &self.tasks {
+            if !content.tasks.iter().any(|t| t.name == task.name) {
+                content.tasks.push(task.clone());
+            }
+        }
+        for (name, feature) in &self.spin_features {
+            if !content.spin_features.contains_key(name) {
+                content.spin_features.insert(name.clone(), feature.clone());
+            }
+        }
+        merged
+    }
+
+    /// A new config named `name` holding only the detection
+    pub fn to_config(&self, name: &str) -> DevSpinConfig {
+        let header = DevSpinConfigHeader::new(
+            CURRENT_VERSION,
+            name,
+            self.base.as_deref().unwrap_or(PLACEHOLDER_BASE),
+        );
+        self.merge_into(&DevSpinConfig::new(header, DevSpinConfigContent::default()))
+    }
+}
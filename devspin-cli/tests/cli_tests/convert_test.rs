@@ -0,0 +1,100 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use devspin_cli::parser::devspin_generator::DevSpinGenerator;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Project with a .git marker and a devspin.yml
+fn project() -> TempDir {
+    common::project_with(
+        "version: 1.0\nname: convert-test\nbase: alpine:3.19\nenv:\n  MODE: dev\n\ntasks:\n  - name: build\n    command: make\n",
+    )
+}
+
+#[test]
+fn test_convert_writes_next_to_the_config() {
+    let temp_dir = project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("convert")
+        .arg("--to")
+        .arg("toml")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Converted"))
+        .stdout(predicate::str::contains("is still read first"));
+
+    let original = DevSpinGenerator::parse_file(temp_dir.path().join("devspin.yml")).unwrap();
+    let converted = DevSpinGenerator::parse_file(temp_dir.path().join("devspin.toml")).unwrap();
+    assert_eq!(converted, original);
+}
+
+#[test]
+fn test_convert_to_stdout() {
+    let temp_dir = project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("convert")
+        .arg("--to")
+        .arg("json")
+        .arg("--stdout")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\"name\": \"convert-test\""));
+
+    assert!(!temp_dir.path().join("devspin.json").exists());
+}
+
+#[test]
+fn test_convert_refuses_to_overwrite() {
+    let temp_dir = project();
+    fs::write(temp_dir.path().join("devspin.json"), "{}").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("convert")
+        .arg("--to")
+        .arg("json")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("devspin.json")).unwrap(),
+        "{}"
+    );
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("convert")
+        .arg("--to")
+        .arg("json")
+        .arg("--force")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert().success();
+    assert!(fs::read_to_string(temp_dir.path().join("devspin.json"))
+        .unwrap()
+        .contains("convert-test"));
+}
+
+#[test]
+fn test_convert_to_the_same_file_fails() {
+    let temp_dir = project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("convert")
+        .arg("--to")
+        .arg("yaml")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("already the config file"));
+}
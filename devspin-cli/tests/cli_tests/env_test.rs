@@ -0,0 +1,201 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Project with a .git marker, a devspin.yml, an env_file and both .env files
+fn project() -> TempDir {
+    let temp_dir = common::project_with(
+        "version: 1.0\nname: env-test\nbase: node:20\nenv_file: config/base.env\nenv:\n  HOST: localhost\n  URL: http://${HOST}:${PORT}\n",
+    );
+    fs::create_dir(temp_dir.path().join("config")).unwrap();
+    fs::write(
+        temp_dir.path().join("config/base.env"),
+        "HOST=from-env-file\nPORT=3000\nLOG=info\n",
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join(".env"), "HOST=from-dotenv\n").unwrap();
+    fs::write(temp_dir.path().join(".env.local"), "PORT=4000\n").unwrap();
+    temp_dir
+}
+
+fn env_cmd(temp_dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("env").arg("--root").arg(temp_dir.path());
+    cmd.env_remove("HOST").env_remove("PORT").env_remove("LOG");
+    cmd
+}
+
+#[test]
+fn test_env_follows_precedence() {
+    let temp_dir = project();
+
+    env_cmd(&temp_dir)
+        .args(["--format", "dotenv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("HOST=from-dotenv\n"))
+        .stdout(predicate::str::contains("PORT=4000\n"))
+        .stdout(predicate::str::contains("LOG=info\n"))
+        .stdout(predicate::str::contains("URL=http://from-dotenv:4000\n"));
+
+    env_cmd(&temp_dir)
+        .env("PORT", "5000")
+        .args(["--format", "dotenv", "--env", "HOST=from-cli"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("URL=http://from-cli:5000\n"));
+}
+
+#[test]
+fn test_env_formats() {
+    let temp_dir = project();
+    fs::write(
+        temp_dir.path().join("devspin.yml"),
+        "version: 1.0\nname: env-test\nbase: node:20\nenv:\n  GREETING: it's here\nservices:\n  - name: db\n    image: postgres:16\n    ports: [\"5433:5432\"]\n",
+    )
+    .unwrap();
+    fs::remove_file(temp_dir.path().join(".env")).unwrap();
+    fs::remove_file(temp_dir.path().join(".env.local")).unwrap();
+
+    let cases = [
+        (
+            "sh",
+            "export DB_HOST='localhost'\nexport DB_PORT='5433'\nexport GREETING='it'\\''s here'\n",
+        ),
+        (
+            "fish",
+            "set -gx DB_HOST 'localhost'\nset -gx DB_PORT '5433'\nset -gx GREETING 'it\\'s here'\n",
+        ),
+        (
+            "powershell",
+            "$env:DB_HOST = 'localhost'\n$env:DB_PORT = '5433'\n$env:GREETING = 'it''s here'\n",
+        ),
+        (
+            "dotenv",
+            "DB_HOST=localhost\nDB_PORT=5433\nGREETING=\"it's here\"\n",
+        ),
+        (
+            "json",
+            "{\n  \"DB_HOST\": \"localhost\",\n  \"DB_PORT\": \"5433\",\n  \"GREETING\": \"it's here\"\n}\n",
+        ),
+    ];
+    for (format, expected) in cases {
+        // Nothing but the variables on stdout, so the output can be evaluated
+        env_cmd(&temp_dir)
+            .args(["--format", format])
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+
+    env_cmd(&temp_dir)
+        .args(["--explain", "DB_PORT"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DB_PORT=5433"))
+        .stdout(predicate::str::contains("address of a service"));
+}
+
+#[test]
+fn test_env_diff() {
+    let temp_dir = project();
+
+    // The process environment wins, so only `--env` can set another value
+    env_cmd(&temp_dir)
+        .env("HOST", "from-process")
+        .env("PORT", "3000")
+        .args(["--diff", "--env", "PORT=9000"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~ PORT: 3000 → 9000"))
+        .stdout(predicate::str::contains("+ LOG=info"))
+        .stdout(predicate::str::contains("+ URL=http://from-process:9000"))
+        .stdout(predicate::str::contains("HOST").not());
+
+    env_cmd(&temp_dir)
+        .env("HOST", "from-dotenv")
+        .env("PORT", "4000")
+        .env("LOG", "info")
+        .env("URL", "http://from-dotenv:4000")
+        .arg("--diff")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("matches the project"));
+}
+
+#[test]
+fn test_env_explain() {
+    let temp_dir = project();
+
+    let output = env_cmd(&temp_dir)
+        .args(["--explain", "HOST"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let sources: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("  "))
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert!(stdout.contains("HOST=from-dotenv"), "{}", stdout);
+    // Highest priority first, the winner marked
+    assert_eq!(sources, ["✔", "devspin.yml", "config/base.env"]);
+    assert!(stdout.contains("✔ .env "), "{}", stdout);
+    assert!(stdout.contains("localhost  (overridden)"), "{}", stdout);
+
+    env_cmd(&temp_dir)
+        .args(["--explain", "NOPE"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("NOPE is not set by any source"));
+
+    env_cmd(&temp_dir)
+        .env("ONLY_IN_PROCESS", "1")
+        .args(["--explain", "ONLY_IN_PROCESS"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "only set in the process environment",
+        ));
+}
+
+#[test]
+fn test_env_errors() {
+    let temp_dir = project();
+
+    env_cmd(&temp_dir)
+        .args(["--env", "NO_VALUE"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected KEY=VALUE"));
+
+    fs::remove_file(temp_dir.path().join("config/base.env")).unwrap();
+    env_cmd(&temp_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("env_file"))
+        .stderr(predicate::str::contains("does not exist"));
+}
+
+#[test]
+fn test_env_rejects_invalid_names() {
+    let temp_dir = project();
+    fs::write(
+        temp_dir.path().join("devspin.yml"),
+        "version: 1.0\nname: env-test\nbase: node:20\nenv:\n  GOOD: yes\n  \"X=1; touch pwned; Y\": value\n",
+    )
+    .unwrap();
+
+    env_cmd(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "invalid environment variable name `X=1; touch pwned; Y`",
+        ));
+}
@@ -0,0 +1,217 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Two projects side by side, `shop` with a service and a secret
+fn projects() -> TempDir {
+    let temp_dir = tempdir().unwrap();
+    for dir in ["shop/src", "cache"] {
+        fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+    }
+    common::write_config(
+        &temp_dir.path().join("shop"),
+        "version: 1.0\nname: shop\nbase: node:20\nenv:\n  SHARED: from-shop\n  SHOP_ONLY: yes\n  PG_PASSWORD: secret:pg\nservices:\n  - name: db\n    image: postgres:16\n    ports: [\"5433:5432\"]\n",
+    );
+    common::write_config(
+        &temp_dir.path().join("blog"),
+        "version: 1.0\nname: blog\nbase: node:20\nenv:\n  SHARED: from-blog\n",
+    );
+    fs::write(temp_dir.path().join("secrets.json"), r#"{"pg": "hunter2"}"#).unwrap();
+    for project in ["shop", "blog"] {
+        allow(&temp_dir, project).assert().success();
+    }
+    temp_dir
+}
+
+/// `devspin allow` for the project in `dir`
+fn allow(temp_dir: &TempDir, dir: &str) -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("allow")
+        .current_dir(temp_dir.path().join(dir))
+        .env("DEVSPIN_DATA_DIR", temp_dir.path().join("data"));
+    cmd
+}
+
+fn apply(temp_dir: &TempDir, dir: &str) -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.args(["hook", "bash", "--apply"])
+        .current_dir(temp_dir.path().join(dir))
+        .env("DEVSPIN_CACHE_DIR", temp_dir.path().join("cache"))
+        .env("DEVSPIN_DATA_DIR", temp_dir.path().join("data"))
+        .env("DEVSPIN_SECRETS_FILE", temp_dir.path().join("secrets.json"))
+        .env_remove("DEVSPIN_HOOK_ROOT")
+        .env_remove("DEVSPIN_HOOK_STAMP")
+        .env_remove("DEVSPIN_HOOK_BACKUP")
+        .env_remove("SHARED");
+    cmd
+}
+
+#[test]
+fn test_hook_prints_script() {
+    cargo_bin_cmd!("devspin-cli")
+        .args(["hook", "zsh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hook zsh --apply"))
+        .stderr(predicate::str::contains("Hello").not());
+}
+
+#[test]
+fn test_apply_loads_and_caches() {
+    let temp_dir = projects();
+
+    apply(&temp_dir, "shop/src")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export SHARED='from-shop'\n"))
+        .stdout(predicate::str::contains("export DB_PORT='5433'\n"))
+        .stdout(predicate::str::contains("hunter2").not())
+        .stdout(predicate::str::contains("PG_PASSWORD").not())
+        .stderr(predicate::str::contains("PG_PASSWORD holds a secret"));
+
+    let cached: Vec<_> = fs::read_dir(temp_dir.path().join("cache/hook"))
+        .unwrap()
+        .collect();
+    assert_eq!(cached.len(), 1);
+    let content = fs::read_to_string(cached[0].as_ref().unwrap().path()).unwrap();
+    assert!(!content.contains("hunter2"));
+
+    // Once the cache exists, a broken secret store does not matter
+    apply(&temp_dir, "shop")
+        .env("DEVSPIN_SECRETS_FILE", temp_dir.path().join("missing.json"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export SHOP_ONLY='yes'\n"));
+
+    // Editing the config invalidates it
+    let config = temp_dir.path().join("shop/devspin.yml");
+    let edited = fs::read_to_string(&config)
+        .unwrap()
+        .replace("from-shop", "edited");
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    fs::write(&config, edited).unwrap();
+    allow(&temp_dir, "shop").assert().success();
+    apply(&temp_dir, "shop")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export SHARED='edited'\n"));
+}
+
+#[test]
+fn test_apply_needs_allow() {
+    let temp_dir = projects();
+    let config = temp_dir.path().join("shop/devspin.yml");
+    let edited = fs::read_to_string(&config)
+        .unwrap()
+        .replace("from-shop", "edited");
+    fs::write(&config, edited).unwrap();
+
+    // A changed config is blocked, and what was loaded is unloaded
+    apply(&temp_dir, "shop")
+        .env("DEVSPIN_HOOK_ROOT", temp_dir.path().join("shop"))
+        .env("DEVSPIN_HOOK_BACKUP", r#"{"SHARED":null}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("unset SHARED\n"))
+        .stdout(predicate::str::contains("edited").not())
+        .stderr(predicate::str::contains(
+            "is not allowed, run `devspin allow` to load it",
+        ));
+
+    allow(&temp_dir, "shop/src")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The hook loads"));
+    apply(&temp_dir, "shop")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export SHARED='edited'\n"));
+
+    allow(&temp_dir, "blog").arg("--revoke").assert().success();
+    apply(&temp_dir, "blog")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("is not allowed"));
+}
+
+#[test]
+fn test_apply_skips_invalid_names() {
+    let temp_dir = projects();
+    fs::write(
+        temp_dir.path().join("blog/devspin.yml"),
+        "version: 1.0\nname: blog\nbase: node:20\nenv:\n  SHARED: from-blog\n  \"X=1; touch pwned; Y\": value\n",
+    )
+    .unwrap();
+    allow(&temp_dir, "blog").assert().success();
+
+    apply(&temp_dir, "blog")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export SHARED='from-blog'\n"))
+        .stdout(predicate::str::contains("touch").not())
+        .stderr(predicate::str::contains(
+            "`X=1; touch pwned; Y` is not a valid variable name and is not exported",
+        ));
+}
+
+#[test]
+fn test_apply_unloads_outside_projects() {
+    let temp_dir = projects();
+
+    apply(&temp_dir, "cache").assert().success().stdout("");
+    apply(&temp_dir, "cache")
+        .env("DEVSPIN_HOOK_ROOT", temp_dir.path().join("shop"))
+        .env(
+            "DEVSPIN_HOOK_BACKUP",
+            r#"{"SHARED":"mine","SHOP_ONLY":null}"#,
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "unset SHOP_ONLY\nexport SHARED='mine'\n",
+        ))
+        .stdout(predicate::str::contains("unset DEVSPIN_HOOK_ROOT\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bash_hook_follows_cd() {
+    let Some(bash) = devspin_cli::runtime::find_in_path("bash") else {
+        return;
+    };
+    let temp_dir = projects();
+    let root = temp_dir.path().display();
+    let devspin = assert_cmd::cargo::cargo_bin!("devspin-cli");
+    // PROMPT_COMMAND only runs in interactive shells, call the hook by hand
+    let script = format!(
+        r#"eval "$('{devspin}' hook bash)"
+cd {root}/shop/src; _devspin_hook; echo "shop: $SHARED $SHOP_ONLY $DB_HOST"
+cd {root}/blog; _devspin_hook; echo "blog: $SHARED [$SHOP_ONLY]"
+cd {root}; _devspin_hook; echo "none: $SHARED [$DEVSPIN_HOOK_ROOT]"
+"#,
+        devspin = Path::new(devspin).display(),
+    );
+
+    let output = std::process::Command::new(bash)
+        .args(["-c", &script])
+        .env("DEVSPIN_CACHE_DIR", temp_dir.path().join("cache"))
+        .env("DEVSPIN_DATA_DIR", temp_dir.path().join("data"))
+        .env("DEVSPIN_SECRETS_FILE", temp_dir.path().join("secrets.json"))
+        .env("SHARED", "mine")
+        .env_remove("DEVSPIN_HOOK_ROOT")
+        .env_remove("DEVSPIN_HOOK_BACKUP")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // A variable of the shell wins over `env`, as for every devspin command
+    assert!(stdout.contains("shop: mine yes localhost\n"), "{}", stdout);
+    assert!(stdout.contains("blog: mine []\n"), "{}", stdout);
+    assert!(stdout.contains("none: mine []\n"), "{}", stdout);
+}
@@ -0,0 +1,157 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+use common::empty_project;
+
+#[test]
+fn test_init_writes_default_template() {
+    let temp_dir = empty_project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--yes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("from the default template"));
+
+    let written = fs::read_to_string(temp_dir.path().join("devspin.yml")).unwrap();
+    assert!(written.contains("base: ubuntu:latest\n"));
+    assert!(written.contains("    command: cargo build\n"));
+}
+
+#[test]
+fn test_init_with_template() {
+    let temp_dir = empty_project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--template")
+        .arg("fullstack")
+        .arg("--yes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert().success();
+
+    let written = fs::read_to_string(temp_dir.path().join("devspin.yml")).unwrap();
+    assert!(written.contains("base: node:20\n"));
+    assert!(written.contains("  - name: postgres\n"));
+    assert!(written.contains("  - name: redis\n"));
+}
+
+#[test]
+fn test_init_refuses_to_overwrite() {
+    let temp_dir = empty_project();
+    fs::write(temp_dir.path().join("devspin.yml"), "version: 1.0\n").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--yes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    let written = fs::read_to_string(temp_dir.path().join("devspin.yml")).unwrap();
+    assert_eq!(written, "version: 1.0\n");
+}
+
+#[test]
+fn test_init_force_keeps_backup() {
+    let temp_dir = empty_project();
+    fs::write(temp_dir.path().join("devspin.yml"), "version: 1.0\n").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--force")
+        .arg("--template")
+        .arg("go")
+        .arg("--yes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Previous config saved to"));
+
+    let backup = fs::read_to_string(temp_dir.path().join("devspin.yml.bak")).unwrap();
+    assert_eq!(backup, "version: 1.0\n");
+    let written = fs::read_to_string(temp_dir.path().join("devspin.yml")).unwrap();
+    assert!(written.contains("base: golang:1.22\n"));
+}
+
+#[test]
+fn test_init_ignores_nested_configs() {
+    let temp_dir = empty_project();
+    let nested = temp_dir.path().join("examples/demo/devspin.yml");
+    fs::create_dir_all(nested.parent().unwrap()).unwrap();
+    fs::write(&nested, "version: 1.0\nname: demo\n").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--yes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Previous config saved to").not());
+
+    // --force only replaces the config of the root
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .args(["--yes", "--force"])
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert().success();
+    assert!(temp_dir.path().join("devspin.yml.bak").is_file());
+
+    assert_eq!(
+        fs::read_to_string(&nested).unwrap(),
+        "version: 1.0\nname: demo\n"
+    );
+    assert!(!nested.with_extension("yml.bak").exists());
+    assert!(temp_dir.path().join("devspin.yml").is_file());
+}
+
+#[test]
+fn test_init_refuses_to_overwrite_other_formats() {
+    let temp_dir = empty_project();
+    fs::write(temp_dir.path().join("devspin.json"), "{}").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--yes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "devspin.json already exists in this directory",
+    ));
+}
+
+#[test]
+fn test_init_interactive_answers() {
+    let temp_dir = empty_project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("init")
+        .arg("--interactive")
+        .arg("--template")
+        .arg("python")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.write_stdin("shop\npython:3.11-slim\nmongo, kafka\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Project name ["))
+        .stderr(predicate::str::contains("no service preset named 'kafka'"));
+
+    let written = fs::read_to_string(temp_dir.path().join("devspin.yml")).unwrap();
+    assert!(written.contains("name: shop\nbase: python:3.11-slim\n"));
+    assert!(written.contains("  - name: mongo\n"));
+    assert!(written.contains("  MONGODB_URL: mongodb://localhost:27017/app\n"));
+}
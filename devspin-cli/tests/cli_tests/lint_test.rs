@@ -0,0 +1,149 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Python project with a .git marker and `spin_features` as its spin-features
+fn project(spin_features: &str) -> TempDir {
+    let temp_dir = common::project_with(&format!(
+        "version: 1.0\nname: app\nbase: python:3.12\nspin-features:\n{}",
+        spin_features
+    ));
+    fs::write(temp_dir.path().join("pyproject.toml"), "").unwrap();
+    fs::write(temp_dir.path().join("app.py"), "print('hi')\n").unwrap();
+    temp_dir
+}
+
+/// Directory holding a fake `program` that logs its arguments to `<program>.log`
+/// and exits with `code`
+#[cfg(unix)]
+fn fake_tool(bin: &Path, program: &str, code: i32) {
+    use std::os::unix::fs::PermissionsExt;
+    let path = bin.join(program);
+    fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> \"{}\"\nexit {}\n",
+            bin.join(format!("{}.log", program)).display(),
+            code
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+/// `devspin <args>` in `temp_dir` with only the tools of `bin` in PATH
+fn devspin(temp_dir: &TempDir, bin: &Path, args: &[&str]) -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.args(args)
+        .arg("--root")
+        .arg(temp_dir.path())
+        .current_dir(temp_dir.path())
+        .env("PATH", bin);
+    cmd
+}
+
+#[test]
+fn test_lint_disabled_feature() {
+    let temp_dir = project("  lint:\n    enabled: false\n    modes: [all]\n");
+
+    devspin(&temp_dir, temp_dir.path(), &["lint"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "`lint` is not enabled in the spin-features of devspin.yml",
+        ));
+    devspin(&temp_dir, temp_dir.path(), &["format"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("`format` is not enabled"));
+}
+
+#[test]
+fn test_lint_mode_not_enabled() {
+    let temp_dir = project("  lint:\n    enabled: true\n    modes: [all]\n");
+
+    devspin(&temp_dir, temp_dir.path(), &["lint", "app.py"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "`lint` does not run on app.py (currFile is not in its modes: all)",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_lint_project() {
+    let temp_dir = project("  lint:\n    enabled: true\n    modes: [all]\n");
+    let bin = tempdir().unwrap();
+    fake_tool(bin.path(), "pylint", 0);
+
+    devspin(&temp_dir, bin.path(), &["lint"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("$ pylint --recursive=y ."))
+        .stdout(predicate::str::contains("No lint errors in the project"));
+    let log = fs::read_to_string(bin.path().join("pylint.log")).unwrap();
+    assert_eq!(log, "--recursive=y .\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_format_check_file_fails() {
+    let temp_dir = project("  format:\n    enabled: true\n    modes: [currFile, all]\n");
+    let bin = tempdir().unwrap();
+    fake_tool(bin.path(), "black", 1);
+
+    devspin(&temp_dir, bin.path(), &["format", "--check", "app.py"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("format failed: black"));
+    let log = fs::read_to_string(bin.path().join("black.log")).unwrap();
+    assert_eq!(log, "--check --diff app.py\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_format_writes_file() {
+    let temp_dir = project("  format:\n    enabled: true\n");
+    let bin = tempdir().unwrap();
+    fake_tool(bin.path(), "black", 0);
+
+    // No modes: every target is allowed
+    devspin(&temp_dir, bin.path(), &["format", "app.py"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Formatted app.py"));
+    let log = fs::read_to_string(bin.path().join("black.log")).unwrap();
+    assert_eq!(log, "app.py\n");
+}
+
+#[test]
+fn test_lint_missing_tool() {
+    let temp_dir = project("  lint:\n    enabled: true\n    modes: [all]\n");
+    let bin = tempdir().unwrap();
+
+    devspin(&temp_dir, bin.path(), &["lint"])
+        .assert()
+        .code(127)
+        .stderr(predicate::str::contains(
+            "`pylint` is not installed or not in PATH",
+        ));
+}
+
+#[test]
+fn test_lint_unsupported_file() {
+    let temp_dir = project("  lint:\n    enabled: true\n    modes: [currFile]\n");
+    fs::write(temp_dir.path().join("README.md"), "# app\n").unwrap();
+
+    devspin(&temp_dir, temp_dir.path(), &["lint", "README.md"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "No linter for README.md (no supported language found)",
+        ));
+}
@@ -0,0 +1,144 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Project with a .git marker and a devspin.yml declaring `tasks`
+fn project(tasks: &str) -> TempDir {
+    common::project_with(&format!(
+            "version: 1.0\nname: run-test\nbase: ubuntu:latest\n\nenv:\n  GREETING: hello-from-env\n\ntasks:\n{}",
+            tasks
+        ))
+}
+
+#[test]
+fn test_run_executes_task_command() {
+    let temp_dir = project("  - name: hello\n    command: echo task-output\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("hello")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("task-output"));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_run_injects_env_and_uses_project_root() {
+    let temp_dir = project("  - name: show\n    command: |\n      echo $GREETING\n      ls\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("show")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("hello-from-env"))
+        .stdout(predicate::str::contains("devspin.yml"));
+}
+
+#[test]
+fn test_run_forwards_task_exit_code() {
+    let temp_dir = project("  - name: fail\n    command: exit 3\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("fail")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .code(3)
+        .stderr(predicate::str::contains("exited with code 3"));
+}
+
+#[test]
+fn test_run_unknown_task_lists_available_tasks() {
+    let temp_dir = project("  - name: build\n    command: echo build\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("deploy")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No task named 'deploy'"))
+        .stderr(predicate::str::contains("available: build"));
+}
+
+#[test]
+fn test_run_reports_config_errors_with_location() {
+    let temp_dir = project("  - name: build\n    comand: echo build\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("build")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("devspin.yml:10:5"))
+        .stderr(predicate::str::contains("did you mean `command`?"));
+}
+
+#[test]
+fn test_run_multiple_tasks_prints_summary() {
+    let temp_dir = project(
+        "  - name: build\n    command: echo building\n  - name: test\n    command: echo testing\n    depends_on: [build]\n",
+    );
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("test")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[build] building"))
+        .stdout(predicate::str::contains("[test] testing"))
+        .stdout(predicate::str::contains("TASK"))
+        .stdout(predicate::str::contains("DURATION"));
+}
+
+#[test]
+fn test_run_skips_up_to_date_task() {
+    let temp_dir =
+        project("  - name: build\n    command: echo compiling\n    inputs: [devspin.yml]\n");
+
+    let run = || {
+        let mut cmd = cargo_bin_cmd!("devspin-cli");
+        cmd.arg("run")
+            .arg("build")
+            .arg("--root")
+            .arg(temp_dir.path());
+        cmd.assert().success()
+    };
+    run().stdout(predicate::str::contains("compiling"));
+    run()
+        .stdout(predicate::str::contains("compiling").not())
+        .stdout(predicate::str::contains("build is up to date"));
+}
+
+#[test]
+fn test_run_keeps_forwarding_after_invalid_utf8() {
+    let temp_dir = project(
+        "  - name: build\n    command: printf 'bad \\377 byte\\n'; echo after\n  - name: test\n    command: echo testing\n    depends_on: [build]\n",
+    );
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("run")
+        .arg("test")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[build] bad \u{fffd} byte"))
+        .stdout(predicate::str::contains("[build] after"))
+        .stdout(predicate::str::contains("[test] testing"));
+}
@@ -0,0 +1,32 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_schema_prints_json() {
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("schema");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"$schema\": \"http://json-schema.org/draft-07/schema#\"",
+        ))
+        .stdout(predicate::str::contains("\"spin-features\""));
+}
+
+#[test]
+fn test_schema_writes_output_file() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("devspin.schema.json");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("schema").arg("--output").arg(&path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Generated"));
+
+    let json = fs::read_to_string(&path).unwrap();
+    assert!(json.starts_with('{'));
+    assert!(json.contains("\"definitions\""));
+}
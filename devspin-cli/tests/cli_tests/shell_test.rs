@@ -0,0 +1,116 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Project with a .git marker and a devspin.yml declaring a published service
+fn project() -> TempDir {
+    common::project_with(
+        "version: 1.0\nname: shop\nbase: node:20\nenv:\n  GREETING: hello\n  CACHE_HOST: cache.internal\nservices:\n  - name: pg-db\n    image: postgres:16\n    ports: [\"5433:5432\"]\n  - name: cache\n    image: redis:7\n    ports: [\"6379:6379\"]\n",
+    )
+}
+
+/// `devspin shell --shell <shell>` reading `script` from stdin
+fn shell_cmd(temp_dir: &TempDir, shell: &str, script: &str) -> assert_cmd::Command {
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("shell")
+        .args(["--shell", shell])
+        .arg("--root")
+        .arg(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .env_remove("DEVSPIN_PROJECT")
+        .write_stdin(script);
+    cmd
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_exports_project_env() {
+    let temp_dir = project();
+    let script = "echo \"$GREETING $PG_DB_HOST:$PG_DB_PORT $CACHE_HOST:$CACHE_PORT $DEVSPIN_PROJECT\"\npwd\n";
+
+    let output = shell_cmd(&temp_dir, "sh", script).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).into_owned();
+
+    // `env` wins over the service address of the same name
+    assert!(stdout.contains("hello localhost:5433 cache.internal:6379 shop\n"));
+    let root = temp_dir.path().canonicalize().unwrap();
+    assert!(stdout.contains(&format!("{}\n", root.display())));
+    assert!(stdout.contains("Left the shop shell"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_marks_the_prompt() {
+    let temp_dir = project();
+    fs::write(
+        temp_dir.path().join(".bashrc"),
+        "PS1='user> '\nUSER_RC=read\n",
+    )
+    .unwrap();
+
+    shell_cmd(&temp_dir, "sh", "echo \"[$PS1]\"\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[(shop) $ ]"));
+
+    if devspin_cli::runtime::find_in_path("bash").is_some() {
+        shell_cmd(&temp_dir, "bash", "echo \"[$PS1] $USER_RC\"\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[(shop) user> ] read"));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_warns_when_nested() {
+    let temp_dir = project();
+
+    shell_cmd(&temp_dir, "sh", "exit\n")
+        .env("DEVSPIN_PROJECT", "other")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "already in the devspin shell of other",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_passes_on_its_exit_code() {
+    let temp_dir = project();
+
+    shell_cmd(&temp_dir, "sh", "exit 3\n")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "The shop shell exited with code 3",
+        ));
+
+    // The init files live in a private directory removed on exit
+    if devspin_cli::runtime::find_in_path("bash").is_some() {
+        let tmp = temp_dir.path().join("tmp");
+        fs::create_dir(&tmp).unwrap();
+        shell_cmd(&temp_dir, "bash", "exit\n")
+            .env("TMPDIR", &tmp)
+            .assert()
+            .success();
+        assert_eq!(fs::read_dir(&tmp).unwrap().count(), 0);
+    }
+}
+
+#[test]
+fn test_shell_reports_missing_program() {
+    let temp_dir = project();
+
+    shell_cmd(&temp_dir, "/nonexistent/devspin-shell", "")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to start /nonexistent/devspin-shell",
+        ));
+}
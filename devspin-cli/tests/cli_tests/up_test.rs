@@ -0,0 +1,94 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+/// Project declaring a postgres service, with a stub `docker` binary in `bin/`
+/// that logs its arguments to `docker.log` and reports created containers as running
+#[cfg(unix)]
+fn project() -> TempDir {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = common::project_with(
+        "version: 1.0\nname: shop\nbase: ubuntu:latest\n\nservices:\n  - name: db\n    image: postgres:15\n    ports: [\"5432:5432\"]\n",
+    );
+
+    let bin = temp_dir.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let script = format!(
+        "#!/bin/sh\necho \"$@\" >> \"{dir}/docker.log\"\ncase \"$1\" in\n  create) : > \"{dir}/created\"; echo id-db ;;\n  inspect) printf 'id-db\\t/shop-db\\trunning\\t0\\n' ;;\n  ps) [ -f \"{dir}/created\" ] && printf 'id-db\\tshop-db\\trunning\\n' ;;\nesac\nexit 0\n",
+        dir = temp_dir.path().display()
+    );
+    fs::write(bin.join("docker"), script).unwrap();
+    fs::set_permissions(bin.join("docker"), fs::Permissions::from_mode(0o755)).unwrap();
+    temp_dir
+}
+
+#[test]
+#[cfg(unix)]
+fn test_up_starts_services_with_project_label() {
+    let temp_dir = project();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.env("PATH", temp_dir.path().join("bin"))
+        .env_remove("DEVSPIN_RUNTIME")
+        .arg("up")
+        .arg("--no-pull")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("db ready"));
+
+    let log = fs::read_to_string(temp_dir.path().join("docker.log")).unwrap();
+    assert!(log.contains(
+        "create --name shop-db --label devspin.project=shop --label devspin.service=db --publish 5432:5432 --network shop_default --network-alias db postgres:15"
+    ));
+    assert!(log.contains("network create --label devspin.project=shop shop_default"));
+    assert!(log.contains("start id-db"));
+    assert!(!log.contains("pull"));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_down_removes_project_containers_and_volumes() {
+    let temp_dir = project();
+    fs::write(temp_dir.path().join("created"), "").unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.env("PATH", temp_dir.path().join("bin"))
+        .env_remove("DEVSPIN_RUNTIME")
+        .arg("down")
+        .arg("--volumes")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Removed shop-db"));
+
+    let log = fs::read_to_string(temp_dir.path().join("docker.log")).unwrap();
+    assert!(log.contains("ps --all --filter label=devspin.project=shop"));
+    assert!(log.contains("stop id-db"));
+    assert!(log.contains("rm --force --volumes id-db"));
+    assert!(log.contains("volume ls --filter label=devspin.project=shop"));
+}
+
+#[test]
+fn test_up_without_runtime_fails() {
+    let temp_dir = common::project_with(
+        "version: 1.0\nname: shop\nbase: ubuntu:latest\nservices:\n  - name: db\n    image: postgres:15\n",
+    );
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.env("PATH", temp_dir.path().join("empty"))
+        .env_remove("DEVSPIN_RUNTIME")
+        .arg("up")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No container runtime found"));
+}
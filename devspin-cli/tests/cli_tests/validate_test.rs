@@ -0,0 +1,117 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use predicates::prelude::*;
+use std::fs;
+
+#[path = "../common/mod.rs"]
+mod common;
+
+use common::project_with;
+
+#[test]
+fn test_validate_valid_config() {
+    let temp_dir = project_with("version: 1.0\nname: shop\nbase: node:20\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate").arg("--root").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("is valid"));
+}
+
+#[test]
+fn test_validate_fails_on_errors() {
+    let temp_dir = project_with(
+        "version: 1.0\nname: shop\nbase: node:20\ntasks:\n  - name: a\n    command: x\n  - name: a\n    command: y\n",
+    );
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate").arg("--root").arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("devspin.yml:7:11: "))
+        .stdout(predicate::str::contains("DS002"))
+        .stderr(predicate::str::contains("1 error, 0 warnings"));
+}
+
+#[test]
+fn test_validate_warnings_pass_unless_strict() {
+    let config =
+        "version: 1.0\nname: shop\nbase: node:20\nspin-features:\n  docs:\n    enabled: true\n";
+    let temp_dir = project_with(config);
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate").arg("--root").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("DS007"))
+        .stdout(predicate::str::contains("0 errors, 1 warning"));
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate")
+        .arg("--strict")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_validate_reports_syntax_errors() {
+    let temp_dir = project_with("version: 1.0\nname: [shop\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate").arg("--root").arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("devspin.yml:2:"));
+}
+
+#[test]
+fn test_validate_schema_reports_every_mistake() {
+    let temp_dir =
+        project_with("version: 1.0\nname: shop\nbse: node:20\ntasks:\n  - name: build\n");
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate")
+        .arg("--schema")
+        .arg("--root")
+        .arg(temp_dir.path());
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("DS010"))
+        .stdout(predicate::str::contains("did you mean `base`?"))
+        .stdout(predicate::str::contains(
+            "`tasks[0]` is missing the `command` key",
+        ))
+        .stderr(predicate::str::contains("3 errors, 0 warnings"));
+}
+
+#[test]
+fn test_validate_reads_json_and_toml() {
+    let temp_dir = project_with("");
+    fs::remove_file(temp_dir.path().join("devspin.yml")).unwrap();
+    fs::write(
+        temp_dir.path().join("devspin.json"),
+        r#"{"version": "1.0", "name": "shop", "base": "node:20"}"#,
+    )
+    .unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate").arg("--root").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("devspin.json is valid"));
+
+    fs::remove_file(temp_dir.path().join("devspin.json")).unwrap();
+    fs::write(
+        temp_dir.path().join("devspin.toml"),
+        "version = \"1.0\"\nname = \"shop\"\nbase = \"node:20\"\n\n[spin-features.docs]\nenabled = true\n",
+    )
+    .unwrap();
+
+    let mut cmd = cargo_bin_cmd!("devspin-cli");
+    cmd.arg("validate").arg("--root").arg(temp_dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("devspin.toml (as YAML):"))
+        .stdout(predicate::str::contains("DS007"));
+}
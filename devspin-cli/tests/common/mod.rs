@@ -0,0 +1,28 @@
+//! Fixtures shared by the CLI tests, included with `#[path]` by each test target
+
+// Every target uses its own subset
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use tempfile::{tempdir, TempDir};
+
+/// Project with a .git marker and no config
+pub fn empty_project() -> TempDir {
+    let temp_dir = tempdir().unwrap();
+    fs::create_dir(temp_dir.path().join(".git")).unwrap();
+    temp_dir
+}
+
+/// Project with a .git marker and the given devspin.yml
+pub fn project_with(devspin_yml: &str) -> TempDir {
+    let temp_dir = empty_project();
+    write_config(temp_dir.path(), devspin_yml);
+    temp_dir
+}
+
+/// `devspin_yml` written to `dir`, which is created if needed
+pub fn write_config(dir: &Path, devspin_yml: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(dir.join("devspin.yml"), devspin_yml).unwrap();
+}
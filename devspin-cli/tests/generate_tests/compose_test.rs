@@ -0,0 +1,175 @@
+use devspin_cli::generate::compose::*;
+use devspin_cli::parser::devspin_generator::DevSpinGenerator;
+use devspin_cli::parser::yaml;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_snapshot() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: My Shop",
+            "base: rust:1.75",
+            "env:",
+            "  DATABASE_URL: postgres://db:5432/shop",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    ports: [\"5432:5432\"]",
+            "    environment:",
+            "      POSTGRES_PASSWORD: pa$$word",
+            "  - name: cache",
+            "    image: redis:7",
+        ])
+        .unwrap();
+
+        let expected = "\
+# Generated by devspin from devspin.yml (My Shop). Do not edit by hand.
+name: my-shop
+
+services:
+  app:
+    build:
+      context: .
+      dockerfile: Dockerfile
+    environment:
+      DATABASE_URL: \"postgres://db:5432/shop\"
+    volumes:
+      - .:/app
+    depends_on:
+      - db
+      - cache
+
+  db:
+    image: \"postgres:15\"
+    ports:
+      - \"5432:5432\"
+    environment:
+      POSTGRES_PASSWORD: \"pa$$$$word\"
+
+  cache:
+    image: \"redis:7\"
+";
+        assert_eq!(render(&config), expected);
+    }
+
+    #[test]
+    fn test_render_is_valid_yaml() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: web",
+            "base: node:20",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    ports:",
+            "      - 5432:5432",
+        ])
+        .unwrap();
+
+        let compose = yaml::parse(&render(&config), "docker-compose.yml").unwrap();
+        let services = compose.get("services").unwrap();
+        let db = services.get("db").unwrap();
+        assert_eq!(db.get("image").unwrap().as_str(), Some("postgres:15"));
+        let ports = db.get("ports").unwrap().as_sequence().unwrap();
+        assert_eq!(ports[0].as_str(), Some("5432:5432"));
+        let app = services.get("app").unwrap();
+        assert_eq!(
+            app.get("build")
+                .unwrap()
+                .get("dockerfile")
+                .unwrap()
+                .as_str(),
+            Some("Dockerfile")
+        );
+    }
+
+    #[test]
+    fn test_app_service_name_avoids_declared_services() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: shop",
+            "base: node:20",
+            "services:",
+            "  - name: app",
+            "    image: nginx",
+        ])
+        .unwrap();
+
+        assert_eq!(app_service_name(&config), "shop-app");
+        assert!(render(&config).contains("\n  shop-app:\n    build:"));
+    }
+
+    #[test]
+    fn test_project_name_is_sanitized() {
+        assert_eq!(project_name("My Project.v2"), "my-project-v2");
+        assert_eq!(project_name("__"), "devspin");
+    }
+
+    #[test]
+    fn test_render_service_runtime_fields() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: shop",
+            "base: node:20",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    restart: always",
+            "    volumes: [\"pgdata:/var/lib/postgresql/data\"]",
+            "    networks: [backend]",
+            "  - name: api",
+            "    image: shop-api",
+            "    command: npm run serve",
+            "    depends_on: [db]",
+            "    networks: [backend]",
+        ])
+        .unwrap();
+
+        let compose = render(&config);
+        assert!(compose.contains("    restart: \"always\"\n    networks:\n      - backend\n"));
+        assert!(compose.contains(
+            "    depends_on:\n      - db\n    command:\n      - \"npm\"\n      - \"run\"\n      - \"serve\"\n"
+        ));
+        assert!(compose.contains("\nvolumes:\n  pgdata: {}\n"));
+        assert!(compose.contains("\nnetworks:\n  backend: {}\n"));
+        assert!(compose.contains("    networks:\n      - default\n      - backend\n"));
+
+        let parsed = yaml::parse(&compose, "docker-compose.yml").unwrap();
+        assert!(parsed.get("volumes").unwrap().get("pgdata").is_some());
+    }
+
+    #[test]
+    fn test_secrets_are_read_from_the_environment() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: shop",
+            "base: node:20",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    environment:",
+            "      POSTGRES_USER: devspin",
+            "      POSTGRES_PASSWORD: secret:pg_password",
+        ])
+        .unwrap();
+
+        let compose = render(&config);
+        assert!(compose.contains(
+            "      POSTGRES_USER: \"devspin\"\n      POSTGRES_PASSWORD: # holds secret `pg_password`, read from the environment\n"
+        ));
+        let parsed = yaml::parse(&compose, "docker-compose.yml").unwrap();
+        let environment = parsed
+            .get("services")
+            .and_then(|services| services.get("db"))
+            .and_then(|db| db.get("environment"))
+            .unwrap();
+        assert_eq!(
+            environment.get("POSTGRES_USER").unwrap().as_str(),
+            Some("devspin")
+        );
+        assert_eq!(environment.get("POSTGRES_PASSWORD").unwrap().as_str(), None);
+    }
+}
@@ -0,0 +1,102 @@
+use devspin_cli::generate::dockerfile::*;
+use devspin_cli::parser::devspin_generator::DevSpinGenerator;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_package_manager() {
+        assert_eq!(PackageManager::detect("ubuntu:latest"), PackageManager::Apt);
+        assert_eq!(PackageManager::detect("rust:1.75"), PackageManager::Apt);
+        assert_eq!(PackageManager::detect("alpine:3.19"), PackageManager::Apk);
+        assert_eq!(
+            PackageManager::detect("node:20-alpine"),
+            PackageManager::Apk
+        );
+        assert_eq!(PackageManager::detect("fedora:40"), PackageManager::Dnf);
+        assert_eq!(
+            PackageManager::detect("registry.access.redhat.com/ubi9/ubi:latest"),
+            PackageManager::Dnf
+        );
+    }
+
+    #[test]
+    fn test_render_snapshot() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: shop",
+            "base: ubuntu:22.04",
+            "system: [git, curl, git]",
+            "env:",
+            "  RUST_LOG: debug",
+            "  GREETING: \"hello $USER\"",
+        ])
+        .unwrap();
+
+        let expected = "\
+# Generated by devspin from devspin.yml (shop). Do not edit by hand.
+FROM ubuntu:22.04
+
+RUN apt-get update \\
+    && DEBIAN_FRONTEND=noninteractive apt-get install -y --no-install-recommends \\
+        git \\
+        curl \\
+    && rm -rf /var/lib/apt/lists/*
+
+ENV RUST_LOG=\"debug\"
+ENV GREETING=\"hello \\$USER\"
+
+WORKDIR /app
+COPY . .
+";
+        assert_eq!(render(&config), expected);
+        assert_eq!(render(&config), render(&config));
+    }
+
+    #[test]
+    fn test_render_alpine_without_env() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: web",
+            "base: node:20-alpine",
+            "system:",
+            "  - python3",
+        ])
+        .unwrap();
+
+        let dockerfile = render(&config);
+        assert!(dockerfile.contains("FROM node:20-alpine\n"));
+        assert!(dockerfile.contains("RUN apk add --no-cache \\\n        python3\n"));
+        assert!(!dockerfile.contains("ENV"));
+    }
+
+    #[test]
+    fn test_render_without_packages() {
+        let config =
+            DevSpinGenerator::generate(&["version: 1.0", "name: bare", "base: fedora:40"]).unwrap();
+
+        let dockerfile = render(&config);
+        assert!(!dockerfile.contains("RUN"));
+        assert!(dockerfile.ends_with("FROM fedora:40\n\nWORKDIR /app\nCOPY . .\n"));
+    }
+
+    #[test]
+    fn test_secrets_stay_out_of_the_image() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: shop",
+            "base: node:20",
+            "env:",
+            "  NODE_ENV: development",
+            "  API_TOKEN: secret:api_token",
+        ])
+        .unwrap();
+
+        let dockerfile = render(&config);
+        assert!(dockerfile.contains(
+            "ENV NODE_ENV=\"development\"\n# API_TOKEN holds secret `api_token`, pass it when running the container\n"
+        ));
+        assert!(!dockerfile.contains("ENV API_TOKEN"));
+    }
+}
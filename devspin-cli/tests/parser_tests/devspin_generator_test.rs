@@ -1,5 +1,10 @@
 mod tests {
-    use devspin_cli::parser::devspin_generator::{DevSpinGenerator, DEFAULT_DEVSPIN_CONFIG};
+    use devspin_cli::error::DevSpinError;
+    use devspin_cli::parser::devspin_generator::{
+        format_duration, parse_duration, split_command, DevSpinConfig, DevSpinGenerator,
+        Healthcheck, PortMapping, Probe, VolumeMount, DEFAULT_DEVSPIN_CONFIG,
+    };
+    use std::time::Duration;
 
     #[test]
     fn test_generate_default_config() {
@@ -71,4 +76,450 @@ mod tests {
         let result = DevSpinGenerator::generate(input);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_service_environment_does_not_swallow_top_level_keys() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "services:",
+            "  - name: postgres",
+            "    image: postgres:15",
+            "    ports:",
+            "      - \"5432:5432\"",
+            "    environment:",
+            "      POSTGRES_DB: mydb",
+            "env:",
+            "  PORT: 8080",
+        ];
+
+        let config = DevSpinGenerator::generate(input).expect("Failed to parse services");
+        let service = &config.content.services[0];
+
+        assert_eq!(service.ports, vec!["5432:5432".to_string()]);
+        assert_eq!(service.environment.len(), 1);
+        assert_eq!(config.content.env.get("PORT"), Some(&"8080".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_key_reports_location_and_suggestion() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "tasks:",
+            "  - name: test",
+            "    comand: cargo test",
+        ];
+
+        match DevSpinGenerator::generate(input) {
+            Err(DevSpinError::Parse {
+                path,
+                line,
+                column,
+                message,
+                snippet,
+            }) => {
+                assert_eq!(path, "<input>");
+                assert_eq!((line, column), (6, 5));
+                assert!(message.contains("did you mean `command`?"));
+                assert!(snippet.contains("    comand: cargo test"));
+            }
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wrong_value_type_is_reported() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "system: git",
+        ];
+
+        let err = DevSpinGenerator::generate(input).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("expected `system` to be a sequence, found a scalar"));
+    }
+
+    #[test]
+    fn test_multi_line_command_block_scalar() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "tasks:",
+            "  - name: build",
+            "    command: |",
+            "      echo \"Building...\"",
+            "      cargo build",
+            "      npm install",
+            "",
+            "  - name: test",
+            "    command: cargo test",
+        ];
+
+        let config = DevSpinGenerator::generate(input).expect("Failed to parse block scalar");
+
+        assert_eq!(
+            config.content.tasks[0].command,
+            "echo \"Building...\"\ncargo build\nnpm install\n"
+        );
+        assert_eq!(config.content.tasks[1].command, "cargo test");
+    }
+
+    #[test]
+    fn test_write_round_trips_multi_line_commands() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "tasks:",
+            "  - name: build",
+            "    command: |-",
+            "      cargo build",
+            "      cargo doc",
+        ];
+
+        let config = DevSpinGenerator::generate(input).expect("Failed to parse config");
+        let written = config.write();
+
+        assert!(written.contains("    command: |-\n      cargo build\n      cargo doc\n"));
+        let reparsed = DevSpinGenerator::parse_str(&written, "written.yml")
+            .expect("Failed to parse written config");
+        assert_eq!(reparsed, config);
+    }
+
+    #[test]
+    fn test_task_depends_on() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "tasks:",
+            "  - name: build",
+            "    command: cargo build",
+            "  - name: test",
+            "    command: cargo test",
+            "    depends_on: [build]",
+        ];
+
+        let config = DevSpinGenerator::generate(input).expect("Failed to parse depends_on");
+
+        assert!(config.content.tasks[0].depends_on.is_empty());
+        assert_eq!(
+            config.content.tasks[1].depends_on,
+            vec!["build".to_string()]
+        );
+        assert!(config.write().contains("    depends_on: [build]\n"));
+    }
+
+    #[test]
+    fn test_task_inputs_and_outputs() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "tasks:",
+            "  - name: build",
+            "    command: cargo build",
+            "    inputs: [\"src/**/*.rs\", Cargo.toml]",
+            "    outputs:",
+            "      - target/debug/app",
+        ];
+
+        let config = DevSpinGenerator::generate(input).expect("Failed to parse inputs");
+
+        let task = &config.content.tasks[0];
+        assert_eq!(task.inputs, vec!["src/**/*.rs", "Cargo.toml"]);
+        assert_eq!(task.outputs, vec!["target/debug/app"]);
+        let written = config.write();
+        assert!(written.contains("    inputs: [src/**/*.rs, Cargo.toml]\n"));
+        assert!(written.contains("    outputs: [target/debug/app]\n"));
+        assert_eq!(
+            DevSpinGenerator::parse_str(&written, "<written>").unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_task_depends_on_unknown_task() {
+        let input = &[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "tasks:",
+            "  - name: build",
+            "    command: cargo build",
+            "  - name: test",
+            "    command: cargo test",
+            "    depends_on:",
+            "      - biuld",
+        ];
+
+        let err = DevSpinGenerator::generate(input).unwrap_err().to_string();
+        assert!(err.starts_with("<input>:10:9: unknown task `biuld` in depends_on"));
+        assert!(err.contains("did you mean `build`?"));
+    }
+
+    fn service_config(healthcheck: &[&str]) -> Result<DevSpinConfig, DevSpinError> {
+        let mut input = vec![
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    ports: [\"5433:5432\"]",
+            "    healthcheck:",
+        ];
+        input.extend_from_slice(healthcheck);
+        DevSpinGenerator::generate(&input)
+    }
+
+    #[test]
+    fn test_service_healthcheck() {
+        let config = service_config(&[
+            "      command: pg_isready -U postgres",
+            "      interval: 500ms",
+            "      retries: 5",
+        ])
+        .expect("Failed to parse healthcheck");
+
+        let service = &config.content.services[0];
+        let healthcheck = service.healthcheck.as_ref().unwrap();
+        assert_eq!(
+            healthcheck.probe,
+            Probe::Command("pg_isready -U postgres".to_string())
+        );
+        assert_eq!(healthcheck.interval, Duration::from_millis(500));
+        assert_eq!(healthcheck.timeout, Healthcheck::DEFAULT_TIMEOUT);
+        assert_eq!(healthcheck.retries, 5);
+        assert_eq!(service.host_port(5432), Some(5433));
+
+        let written = config.write();
+        assert!(written.contains(
+            "    healthcheck:\n      command: pg_isready -U postgres\n      interval: 500ms\n      retries: 5\n"
+        ));
+        assert_eq!(
+            DevSpinGenerator::parse_str(&written, "<written>").unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_http_healthcheck_defaults_to_first_port() {
+        let config = service_config(&["      http: /health"]).unwrap();
+
+        assert_eq!(
+            config.content.services[0]
+                .healthcheck
+                .as_ref()
+                .unwrap()
+                .probe,
+            Probe::Http {
+                path: "/health".to_string(),
+                port: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_healthcheck_errors() {
+        let err = service_config(&["      tcp: 6379"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("<input>:9:12: port 6379 is not published on a host port"));
+
+        let err = service_config(&["      tcp: 5432", "      command: true"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with(
+            "<input>:10:7: a healthcheck needs exactly one of `command`, `tcp` or `http`"
+        ));
+
+        let err = service_config(&["      command: true", "      interval: soon"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("<input>:10:17: expected `interval` to be a duration"));
+    }
+
+    #[test]
+    fn test_duration_round_trip() {
+        for text in ["250ms", "2s", "90s", "5m", "1h"] {
+            assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+        }
+        assert_eq!(parse_duration("3"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_duration("3 days"), None);
+    }
+
+    #[test]
+    fn test_service_runtime_fields() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    command: postgres -c 'shared_buffers=256MB'",
+            "    restart: on-failure:3",
+            "    volumes:",
+            "      - pgdata:/var/lib/postgresql/data",
+            "      - ./init:/docker-entrypoint-initdb.d:ro",
+            "    networks: [backend]",
+            "  - name: api",
+            "    image: shop-api",
+            "    entrypoint: [/bin/sh, -c]",
+            "    depends_on: [db]",
+        ])
+        .expect("Failed to parse service fields");
+
+        let db = &config.content.services[0];
+        assert_eq!(db.command, ["postgres", "-c", "shared_buffers=256MB"]);
+        assert_eq!(db.restart.as_deref(), Some("on-failure:3"));
+        assert_eq!(db.networks, ["backend"]);
+        assert_eq!(db.volumes.len(), 2);
+        let api = &config.content.services[1];
+        assert_eq!(api.entrypoint, ["/bin/sh", "-c"]);
+        assert_eq!(api.depends_on, ["db"]);
+
+        let written = config.write();
+        assert!(written.contains("    command: [postgres, -c, shared_buffers=256MB]\n"));
+        assert!(written.contains("      - ./init:/docker-entrypoint-initdb.d:ro\n"));
+        assert_eq!(
+            DevSpinGenerator::parse_str(&written, "<written>").unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_env_file() {
+        let config = DevSpinGenerator::generate(&[
+            "version: 1.0",
+            "name: test-project",
+            "base: rust:1.75",
+            "env_file: config/base.env",
+            "env:",
+            "  RUST_LOG: debug",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    env_file: [db.env, db.local.env]",
+        ])
+        .expect("Failed to parse env_file");
+
+        assert_eq!(config.content.env_file, ["config/base.env"]);
+        assert_eq!(
+            config.content.services[0].env_file,
+            ["db.env", "db.local.env"]
+        );
+
+        let written = config.write();
+        assert!(written.contains("env_file: [config/base.env]\n"));
+        assert!(written.contains("    env_file: [db.env, db.local.env]\n"));
+        assert_eq!(
+            DevSpinGenerator::parse_str(&written, "<written>").unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_service_field_errors() {
+        let service = |lines: &[&str]| {
+            let mut input = vec![
+                "version: 1.0",
+                "name: test-project",
+                "base: rust:1.75",
+                "services:",
+                "  - name: db",
+                "    image: postgres:15",
+            ];
+            input.extend_from_slice(lines);
+            DevSpinGenerator::generate(&input).unwrap_err().to_string()
+        };
+
+        let err = service(&["    volumes: [\"data:relative\"]"]);
+        assert!(err.starts_with("<input>:7:15: volume target `relative` must be an absolute path"));
+
+        let err = service(&["    restart: sometimes"]);
+        assert!(err.starts_with("<input>:7:14: unknown restart policy `sometimes`"));
+
+        let err = service(&["    depends_on: [dbb]"]);
+        assert!(err.starts_with("<input>:7:18: unknown service `dbb` in depends_on"));
+        assert!(err.contains("did you mean `db`?"));
+
+        let err = service(&["    command: echo 'unterminated"]);
+        assert!(err.starts_with("<input>:7:14: invalid `command`: unterminated quote"));
+    }
+
+    #[test]
+    fn test_volume_mounts() {
+        assert_eq!(
+            VolumeMount::parse("pgdata:/data"),
+            Ok(VolumeMount::Named {
+                name: "pgdata".to_string(),
+                target: "/data".to_string(),
+                mode: None
+            })
+        );
+        assert_eq!(
+            VolumeMount::parse("~/cache:/cache:ro"),
+            Ok(VolumeMount::Bind {
+                source: "~/cache".to_string(),
+                target: "/cache".to_string(),
+                mode: Some("ro".to_string())
+            })
+        );
+        assert_eq!(
+            VolumeMount::parse("/tmp/scratch"),
+            Ok(VolumeMount::Anonymous {
+                target: "/tmp/scratch".to_string()
+            })
+        );
+        assert!(VolumeMount::parse("my data:/data").is_err());
+        assert!(VolumeMount::parse("data:/data:rx").is_err());
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command(r#"sh -c "echo \"hi\" && exit 0" ''"#).unwrap(),
+            ["sh", "-c", "echo \"hi\" && exit 0", ""]
+        );
+        assert!(split_command("echo \"open").is_err());
+    }
+
+    #[test]
+    fn test_port_mappings() {
+        assert_eq!(
+            PortMapping::parse("127.0.0.1:5433:5432"),
+            Ok(PortMapping {
+                ip: Some("127.0.0.1".to_string()),
+                host: Some((5433, 5433)),
+                container: (5432, 5432),
+                protocol: "tcp".to_string(),
+            })
+        );
+        assert_eq!(
+            PortMapping::parse("[::1]:53:53/udp"),
+            Ok(PortMapping {
+                ip: Some("::1".to_string()),
+                host: Some((53, 53)),
+                container: (53, 53),
+                protocol: "udp".to_string(),
+            })
+        );
+        let range = PortMapping::parse("8000-8010:9000-9010").unwrap();
+        assert_eq!(range.host, Some((8000, 8010)));
+        assert_eq!(PortMapping::parse("3000").unwrap().host, None);
+
+        assert!(PortMapping::parse("0:80").is_err());
+        assert!(PortMapping::parse("9-8:9-8").is_err());
+        assert!(PortMapping::parse("8000-8001:80-82").is_err());
+        assert!(PortMapping::parse("a:b:c:d").is_err());
+    }
 }
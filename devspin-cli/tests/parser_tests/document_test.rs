@@ -0,0 +1,229 @@
+use devspin_cli::parser::devspin_generator::{DevSpinGenerator, Service, Task};
+use devspin_cli::parser::document::*;
+
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    const SOURCE: &str = "\
+version: 1.0
+name: my-app
+base: rust:latest # pinned later
+
+# Spin Features
+spin-features:
+  lint:
+    enabled: true
+    modes: [currFile, all]
+
+env:
+  RUST_LOG: debug
+  PORT: 8080
+
+# Sidecar containers
+services:
+  - name: postgres
+    image: postgres:15
+    ports:
+      - \"5432:5432\"
+
+tasks:
+  - name: build
+    command: |
+      cargo build
+      npm install
+
+  - name: test
+    command: cargo test
+";
+
+    fn document() -> Document {
+        Document::parse(SOURCE, "devspin.yml").expect("Failed to parse document")
+    }
+
+    /// Lines of `after` that are not in `before`, and the other way around.
+    fn changed_lines(before: &str, after: &str) -> (Vec<String>, Vec<String>) {
+        let before: Vec<&str> = before.lines().collect();
+        let after: Vec<&str> = after.lines().collect();
+        let added = after
+            .iter()
+            .filter(|line| !before.contains(line))
+            .map(|line| line.to_string())
+            .collect();
+        let removed = before
+            .iter()
+            .filter(|line| !after.contains(line))
+            .map(|line| line.to_string())
+            .collect();
+        (added, removed)
+    }
+
+    #[test]
+    fn test_unmodified_document_is_identical() {
+        assert_eq!(document().to_string(), SOURCE);
+    }
+
+    #[test]
+    fn test_set_scalar_keeps_trailing_comment() {
+        let mut doc = document();
+        doc.set("base", "rust:1.80").unwrap();
+
+        let (added, removed) = changed_lines(SOURCE, &doc.to_string());
+        assert_eq!(added, ["base: rust:1.80 # pinned later"]);
+        assert_eq!(removed, ["base: rust:latest # pinned later"]);
+        assert_eq!(doc.config().unwrap().header.base, "rust:1.80");
+    }
+
+    #[test]
+    fn test_set_adds_missing_key_at_end_of_mapping() {
+        let mut doc = document();
+        doc.set("env.DATABASE_URL", "postgres://localhost/db")
+            .unwrap();
+
+        let expected = SOURCE.replace(
+            "  PORT: 8080\n",
+            "  PORT: 8080\n  DATABASE_URL: postgres://localhost/db\n",
+        );
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    #[test]
+    fn test_set_creates_missing_sections() {
+        let mut doc = document();
+        doc.set("system", Item::Sequence(vec!["git".into(), "curl".into()]))
+            .unwrap();
+
+        let config = doc.config().unwrap();
+        assert_eq!(config.content.system, ["git", "curl"]);
+        assert!(doc.to_string().starts_with(SOURCE));
+    }
+
+    #[test]
+    fn test_push_service_appends_after_last_service() {
+        let mut doc = document();
+        let redis = Service {
+            name: "redis".to_string(),
+            image: "redis:alpine".to_string(),
+            ports: vec!["6379:6379".to_string()],
+            environment: IndexMap::new(),
+            ..Default::default()
+        };
+        doc.push("services", &redis).unwrap();
+
+        let expected = SOURCE.replace(
+            "      - \"5432:5432\"\n",
+            "      - \"5432:5432\"\n  - name: redis\n    image: redis:alpine\n    ports:\n      - 6379:6379\n",
+        );
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(doc.config().unwrap().content.services[1], redis);
+    }
+
+    #[test]
+    fn test_push_into_flow_sequence_keeps_flow_style() {
+        let mut doc = document();
+        doc.push("spin-features.lint.modes", "currDir").unwrap();
+
+        let (added, _) = changed_lines(SOURCE, &doc.to_string());
+        assert_eq!(added, ["    modes: [currFile, all, currDir]"]);
+    }
+
+    #[test]
+    fn test_remove_entry_and_sequence_item() {
+        let mut doc = document();
+        doc.remove("env.RUST_LOG").unwrap();
+        doc.remove("tasks[0]").unwrap();
+
+        let (added, removed) = changed_lines(SOURCE, &doc.to_string());
+        assert!(added.is_empty());
+        assert_eq!(
+            removed,
+            [
+                "  RUST_LOG: debug",
+                "  - name: build",
+                "    command: |",
+                "      cargo build",
+                "      npm install",
+            ]
+        );
+        let config = doc.config().unwrap();
+        assert_eq!(config.content.tasks.len(), 1);
+        assert_eq!(config.content.tasks[0].name, "test");
+    }
+
+    #[test]
+    fn test_remove_first_key_of_sequence_entry_moves_dash() {
+        let mut doc = document();
+        doc.remove("tasks[1].name").unwrap();
+
+        assert!(doc.to_string().ends_with("\n  - command: cargo test\n"));
+    }
+
+    #[test]
+    fn test_replace_block_scalar_command() {
+        let mut doc = document();
+        doc.set("tasks[0].command", "cargo build --release")
+            .unwrap();
+
+        let expected = SOURCE.replace(
+            "    command: |\n      cargo build\n      npm install\n",
+            "    command: cargo build --release\n",
+        );
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    #[test]
+    fn test_update_only_touches_changed_nodes() {
+        let mut doc = document();
+        let mut config = doc.config().unwrap();
+        config.header.base = "rust:1.80".to_string();
+        config
+            .content
+            .env
+            .insert("PORT".to_string(), "9090".to_string());
+        config.content.tasks.push(Task {
+            name: "lint".to_string(),
+            command: "cargo clippy".to_string(),
+            depends_on: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        });
+        doc.update(&config).unwrap();
+
+        let (added, removed) = changed_lines(SOURCE, &doc.to_string());
+        assert_eq!(
+            added,
+            [
+                "base: rust:1.80 # pinned later",
+                "  PORT: 9090",
+                "  - name: lint",
+                "    command: cargo clippy",
+            ]
+        );
+        assert_eq!(
+            removed,
+            ["base: rust:latest # pinned later", "  PORT: 8080"]
+        );
+        assert_eq!(doc.config().unwrap(), config);
+    }
+
+    #[test]
+    fn test_invalid_paths_are_rejected() {
+        let mut doc = document();
+
+        assert!(doc.set("tasks[9].command", "x").is_err());
+        assert!(doc.set("base.image", "x").is_err());
+        assert!(doc.set("env[", "x").is_err());
+        assert_eq!(doc.to_string(), SOURCE);
+    }
+
+    #[test]
+    fn test_write_preserves_key_order() {
+        let config = DevSpinGenerator::parse_str(SOURCE, "devspin.yml").unwrap();
+        let written = config.write();
+
+        let rust_log = written.find("RUST_LOG").unwrap();
+        let port = written.find("PORT").unwrap();
+        assert!(rust_log < port);
+        assert_eq!(written, config.write());
+    }
+}
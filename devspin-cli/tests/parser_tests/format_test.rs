@@ -0,0 +1,250 @@
+use devspin_cli::error::DevSpinError;
+use devspin_cli::parser::devspin_generator::{DevSpinConfig, DevSpinGenerator, Probe};
+use devspin_cli::parser::format::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    const FULL_CONFIG: &str = r#"version: 1.0
+name: shop
+base: node:20
+
+spin-features:
+  lint:
+    enabled: true
+    modes: [all]
+
+system:
+  - git
+
+env:
+  NODE_ENV: development
+  PORT: "8080"
+
+services:
+  - name: db
+    image: postgres:16
+    ports: ["5432:5432"]
+    environment:
+      POSTGRES_USER: devspin
+    healthcheck:
+      command: pg_isready -U devspin
+      interval: 2s
+      retries: 5
+    volumes: ["pgdata:/var/lib/postgresql/data"]
+    restart: on-failure:3
+  - name: api
+    image: shop-api
+    ports: ["8000:80"]
+    healthcheck:
+      http: /health
+      port: 80
+    depends_on: [db]
+    command: [serve, --port, "80"]
+    networks: [backend]
+
+tasks:
+  - name: install
+    command: npm install
+    inputs: [package.json]
+    outputs: [node_modules]
+  - name: build
+    command: npm run build
+    depends_on: [install]
+"#;
+
+    fn full_config() -> DevSpinConfig {
+        DevSpinGenerator::parse_str(FULL_CONFIG, "devspin.yml").unwrap()
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("devspin.yml"), Some(Format::Yaml));
+        assert_eq!(Format::from_path("a/devspin.yaml"), Some(Format::Yaml));
+        assert_eq!(Format::from_path("devspin.json"), Some(Format::Json));
+        assert_eq!(Format::from_path("devspin.toml"), Some(Format::Toml));
+        assert_eq!(Format::from_path("devspin"), None);
+        assert_eq!(Format::Toml.file_name(), "devspin.toml");
+    }
+
+    #[test]
+    fn test_round_trip_every_format() {
+        let config = full_config();
+        for format in [Format::Yaml, Format::Json, Format::Toml] {
+            let written = format.write(&config);
+            let parsed = format
+                .parse(&written, "devspin")
+                .unwrap_or_else(|e| panic!("{:?} output should parse: {}\n{}", format, e, written));
+            assert_eq!(parsed, config, "{:?} round trip", format);
+        }
+    }
+
+    #[test]
+    fn test_json_layout() {
+        let value = serde_json::to_value(full_config()).unwrap();
+        assert_eq!(value["version"], "1.0");
+        assert_eq!(value["spin-features"]["lint"]["modes"][0], "all");
+        assert_eq!(
+            value["services"][0]["healthcheck"]["command"],
+            "pg_isready -U devspin"
+        );
+        assert_eq!(value["services"][0]["healthcheck"]["retries"], 5);
+        // Defaults and empty fields are left out
+        assert!(value["services"][0]["healthcheck"].get("timeout").is_none());
+        assert!(value["services"][0].get("depends_on").is_none());
+        assert_eq!(value["services"][1]["command"][2], "80");
+    }
+
+    #[test]
+    fn test_json_accepts_yaml_scalars() {
+        let source = r#"{
+  "version": 1.0,
+  "name": "shop",
+  "base": "node:20",
+  "env": { "PORT": 8080, "DEBUG": true },
+  "services": [
+    { "name": "db", "image": "postgres", "ports": [5432], "command": "postgres -c 'fsync=off'" }
+  ]
+}"#;
+        let config = Format::Json.parse(source, "devspin.json").unwrap();
+        assert_eq!(config.header.version, "1.0");
+        assert_eq!(config.content.env["PORT"], "8080");
+        assert_eq!(config.content.env["DEBUG"], "true");
+        assert_eq!(config.content.services[0].ports, ["5432"]);
+        assert_eq!(
+            config.content.services[0].command,
+            ["postgres", "-c", "fsync=off"]
+        );
+    }
+
+    #[test]
+    fn test_toml() {
+        let source = r#"version = "1.0"
+name = "shop"
+base = "rust:1"
+
+[[services]]
+name = "db"
+image = "postgres:16"
+ports = ["5432:5432"]
+healthcheck = { tcp = 5432, timeout = "500ms" }
+
+[[tasks]]
+name = "build"
+command = "cargo build"
+"#;
+        let config = Format::Toml.parse(source, "devspin.toml").unwrap();
+        let healthcheck = config.content.services[0].healthcheck.as_ref().unwrap();
+        assert_eq!(healthcheck.probe, Probe::Tcp(5432));
+        assert_eq!(healthcheck.timeout, Duration::from_millis(500));
+        assert_eq!(config.content.tasks[0].command, "cargo build");
+    }
+
+    #[test]
+    fn test_toml_keeps_key_order() {
+        let sample =
+            fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/src/.sample.yml")).unwrap();
+        let config = DevSpinGenerator::parse_str(&sample, ".sample.yml").unwrap();
+        let written = Format::Toml.write(&config);
+        let parsed = Format::Toml.parse(&written, "devspin.toml").unwrap();
+
+        let keys = |config: &DevSpinConfig| {
+            let content = &config.content;
+            (
+                content.spin_features.keys().cloned().collect::<Vec<_>>(),
+                content.env.keys().cloned().collect::<Vec<_>>(),
+                content.services[0]
+                    .environment
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(keys(&parsed), keys(&config));
+        assert_eq!(
+            keys(&parsed).1,
+            ["RUST_LOG", "PORT", "PG_PASSWORD", "DATABASE_URL"]
+        );
+        assert_eq!(keys(&parsed).0, ["lint", "format", "test"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let source = "{\n  \"version\": \"1.0\",\n  \"name\": \"shop\",\n  \"bse\": \"node:20\"\n}";
+        let err = Format::Json.parse(source, "devspin.json").unwrap_err();
+        assert!(
+            matches!(err, DevSpinError::Parse { line: 4, .. }),
+            "{:?}",
+            err
+        );
+        assert!(err.to_string().contains("unknown field `bse`"));
+
+        // Errors at the end of the input point past the last line
+        let err = Format::Json
+            .parse("{\n  \"version\": \"1.0\",\n", "devspin.json")
+            .unwrap_err();
+        assert!(
+            matches!(err, DevSpinError::Parse { line: 3, .. }),
+            "{:?}",
+            err
+        );
+
+        let source = "version = \"1.0\"\nname = \"shop\"\nbase = \"rust:1\"\n\n[[tasks]]\nname = \"build\"\ncommand = 3\n";
+        let err = Format::Toml.parse(source, "devspin.toml").unwrap_err();
+        assert!(
+            err.to_string().starts_with("devspin.toml:7:11: "),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_serde_checks_match_the_yaml_reader() {
+        let header = r#""version": "1.0", "name": "shop", "base": "node:20""#;
+        let cases = [
+            (
+                r#"{"name": "db", "healthcheck": {"tcp": 5432, "command": "true"}}"#,
+                "a healthcheck needs exactly one of `command`, `tcp` or `http`",
+            ),
+            (
+                r#"{"name": "db", "restart": "sometimes"}"#,
+                "unknown restart policy `sometimes`",
+            ),
+            (
+                r#"{"name": "db", "healthcheck": {"tcp": 5432}}"#,
+                "service `db`: port 5432 is not published on a host port",
+            ),
+            (
+                r#"{"name": "db", "depends_on": ["cache"]}"#,
+                "unknown service `cache` in depends_on",
+            ),
+            (
+                r#"{"name": "db", "volumes": ["data:relative"]}"#,
+                "service `db`: ",
+            ),
+        ];
+        for (service, expected) in cases {
+            let source = format!(r#"{{{}, "services": [{}]}}"#, header, service);
+            let err = Format::Json.parse(&source, "devspin.json").unwrap_err();
+            assert!(
+                err.to_string().contains(expected),
+                "{} should fail with {:?}, got {}",
+                service,
+                expected,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_file_uses_the_extension() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("devspin.json");
+        fs::write(&path, Format::Json.write(&full_config())).unwrap();
+        assert_eq!(DevSpinGenerator::parse_file(&path).unwrap(), full_config());
+    }
+}
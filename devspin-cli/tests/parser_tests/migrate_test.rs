@@ -0,0 +1,180 @@
+use devspin_cli::error::DevSpinError;
+use devspin_cli::parser::devspin_generator::DevSpinGenerator;
+use devspin_cli::parser::document::Document;
+use devspin_cli::parser::format::Format;
+use devspin_cli::parser::migrate::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "\
+version: 0.8 # first draft
+name: shop
+base: node:20
+
+# Installed in the container
+packages: [git, curl]
+";
+
+    /// 0.8 called `system` `packages`
+    fn rename_packages(document: &mut Document) -> Result<(), DevSpinError> {
+        let packages: Vec<String> = document
+            .root()
+            .get("packages")
+            .and_then(|node| node.as_sequence())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|node| node.as_str().map(str::to_string))
+            .collect();
+        document.remove("packages")?;
+        document.set("system", packages.as_slice())
+    }
+
+    fn add_lint(document: &mut Document) -> Result<(), DevSpinError> {
+        document.set("spin-features.lint.enabled", "true")
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration {
+            from: "0.8",
+            to: "0.9",
+            summary: "rename `packages` to `system`",
+            apply: rename_packages,
+        },
+        Migration {
+            from: "0.9",
+            to: "1.0",
+            summary: "enable the lint spin feature",
+            apply: add_lint,
+        },
+    ];
+
+    #[test]
+    fn test_support() {
+        assert_eq!(known_versions().last(), Some(&CURRENT_VERSION));
+        assert_eq!(support(CURRENT_VERSION), Support::Current);
+        assert_eq!(support("1"), Support::Current);
+        assert_eq!(support("1.10"), Support::Newer);
+        assert_eq!(support("2.0"), Support::Newer);
+        assert_eq!(support("0.1"), Support::Unknown);
+        assert_eq!(support("latest"), Support::Unknown);
+        assert!(unsupported("2.0").starts_with("version `2.0` is newer than this devspin supports"));
+    }
+
+    #[test]
+    fn test_migrate_step_by_step() {
+        let mut document = Document::parse(OLD, "devspin.yml").unwrap();
+        let steps = migrate_with(&mut document, STEPS).unwrap();
+        let names: Vec<(&str, &str)> = steps.iter().map(|s| (s.from, s.to)).collect();
+        assert_eq!(names, [("0.8", "0.9"), ("0.9", "1.0")]);
+
+        let text = document.to_string();
+        assert!(text.starts_with("version: 1.0 # first draft\n"), "{}", text);
+        assert!(text.contains("# Installed in the container\n"));
+        assert!(!text.contains("packages"));
+
+        let config = document.config().unwrap();
+        assert_eq!(config.content.system, ["git", "curl"]);
+        assert!(config.content.spin_features["lint"].enabled);
+    }
+
+    #[test]
+    fn test_migrate_from_the_middle() {
+        let source = OLD.replace("0.8", "0.9").replace("packages", "system");
+        let mut document = Document::parse(&source, "devspin.yml").unwrap();
+        let steps = migrate_with(&mut document, STEPS).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].from, "0.9");
+    }
+
+    #[test]
+    fn test_migrate_current_is_a_no_op() {
+        let source = format!("version: {}\nname: shop\nbase: node:20\n", CURRENT_VERSION);
+        let mut document = Document::parse(&source, "devspin.yml").unwrap();
+        assert!(migrate(&mut document).unwrap().is_empty());
+        assert_eq!(document.to_string(), source);
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_versions() {
+        for (version, expected) in [
+            (
+                "1.1",
+                "version `1.1` is newer than this devspin supports (up to 1.0)",
+            ),
+            (
+                "0.5",
+                "unknown version `0.5` (known versions: 0.8, 0.9, 1.0)",
+            ),
+        ] {
+            let source = format!("version: {}\nname: shop\nbase: node:20\n", version);
+            let mut document = Document::parse(&source, "devspin.yml").unwrap();
+            let err = migrate_with(&mut document, STEPS).unwrap_err();
+            let message = err.to_string();
+            assert!(
+                message.starts_with(&format!("devspin.yml: {}", expected)),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_parser_rejects_newer_versions() {
+        let err = DevSpinGenerator::generate(&["version: 9.0", "name: shop", "base: node:20"])
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                DevSpinError::Parse {
+                    line: 1,
+                    column: 10,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+        assert!(err.to_string().contains("update devspin to read this file"));
+
+        let err = Format::Json
+            .parse(
+                r#"{"version": "9.0", "name": "shop", "base": "node:20"}"#,
+                "devspin.json",
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("is newer than this devspin supports"));
+    }
+
+    #[test]
+    fn test_parser_rejects_unknown_versions() {
+        let err = DevSpinGenerator::generate(&["version: 0.9", "name: shop", "base: node:20"])
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                DevSpinError::Parse {
+                    line: 1,
+                    column: 10,
+                    ..
+                }
+            ),
+            "{:?}",
+            err
+        );
+        assert!(err
+            .to_string()
+            .contains("unknown version `0.9` (known versions: 1.0)"));
+
+        let err = Format::Toml
+            .parse(
+                "version = \"latest\"\nname = \"shop\"\nbase = \"node:20\"\n",
+                "devspin.toml",
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown version `latest`"));
+    }
+}
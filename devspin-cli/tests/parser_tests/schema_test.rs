@@ -0,0 +1,108 @@
+use devspin_cli::parser::schema::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn keys(value: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .expect("properties should be an object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_schema_header() {
+        let schema = schema().to_value();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["title"], "devspin.yml");
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["version", "name", "base"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_schema_keys_follow_the_config() {
+        let schema = schema().to_value();
+        assert_eq!(
+            keys(&schema["properties"]),
+            [
+                "base",
+                "env",
+                "env_file",
+                "name",
+                "services",
+                "spin-features",
+                "system",
+                "tasks",
+                "version"
+            ]
+        );
+        let definitions = &schema["definitions"];
+        assert_eq!(
+            keys(&definitions["Service"]["properties"]),
+            [
+                "command",
+                "depends_on",
+                "entrypoint",
+                "env_file",
+                "environment",
+                "healthcheck",
+                "image",
+                "name",
+                "networks",
+                "ports",
+                "restart",
+                "volumes"
+            ]
+        );
+        assert_eq!(
+            keys(&definitions["Task"]["properties"]),
+            ["command", "depends_on", "inputs", "name", "outputs"]
+        );
+        assert_eq!(
+            keys(&definitions["SpinFeature"]["properties"]),
+            ["enabled", "modes"]
+        );
+        assert_eq!(
+            keys(&definitions["Healthcheck"]["properties"]),
+            ["command", "http", "interval", "port", "retries", "tcp", "timeout"]
+        );
+        assert_eq!(
+            definitions["Service"]["required"],
+            serde_json::json!(["name"])
+        );
+        assert_eq!(
+            definitions["Task"]["required"],
+            serde_json::json!(["name", "command"])
+        );
+    }
+
+    #[test]
+    fn test_schema_descriptions_come_from_doc_comments() {
+        let schema = schema().to_value();
+        assert_eq!(
+            schema["definitions"]["Task"]["properties"]["depends_on"]["description"],
+            "Tasks that must succeed before this one starts."
+        );
+        assert_eq!(
+            schema["definitions"]["Healthcheck"]["properties"]["interval"]["default"],
+            "1s"
+        );
+    }
+
+    #[test]
+    fn test_schema_json() {
+        let json = schema_json();
+        assert!(json.ends_with("}\n"));
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, schema().to_value());
+    }
+}
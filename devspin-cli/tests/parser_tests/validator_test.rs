@@ -0,0 +1,302 @@
+use devspin_cli::parser::validator::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(lines: &[&str]) -> Vec<Diagnostic> {
+        validate_str(&lines.join("\n"), "devspin.yml").expect("config should parse")
+    }
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&'static str> {
+        diagnostics.iter().map(Diagnostic::code).collect()
+    }
+
+    const HEADER: [&str; 3] = ["version: 1.0", "name: shop", "base: node:20"];
+
+    #[test]
+    fn test_valid_config_has_no_diagnostics() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "spin-features:",
+            "  lint:",
+            "    enabled: true",
+            "    modes: [currFile, currDir]",
+            "env:",
+            "  _PRIVATE: x",
+            "  NODE_ENV: dev",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    ports: [\"5432:5432\", \"127.0.0.1::9000\", \"8000-8002:8000-8002\"]",
+            "  - name: dns",
+            "    image: coredns",
+            "    ports: [\"5432:5432/udp\", \"127.0.0.1:53:53\"]",
+            "tasks:",
+            "  - name: build",
+            "    command: npm run build",
+        ]);
+        assert_eq!(validate(&lines), []);
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let err =
+            validate_str("version: 0.9\nname: shop\nbase: node:20", "devspin.yml").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("devspin.yml:1:10: unknown version `0.9` (known versions: 1.0)"));
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "  - name: db",
+            "    image: mysql:8",
+            "tasks:",
+            "  - name: build",
+            "    command: make",
+            "  - name: build",
+            "    command: make all",
+        ]);
+        let diagnostics = validate(&lines);
+        assert_eq!(codes(&diagnostics), ["DS003", "DS002"]);
+        assert_eq!(
+            diagnostics[0].message,
+            "duplicate service name `db` (first declared on line 5)"
+        );
+        assert_eq!(diagnostics[1].line, 12);
+    }
+
+    #[test]
+    fn test_ports() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    ports: [\"5432:5432\", \"70000:80\", \"80/icmp\", \"8000-8003:80-81\"]",
+            "  - name: replica",
+            "    image: postgres:15",
+            "    ports: [\"0.0.0.0:5432:5432\"]",
+        ]);
+        let diagnostics = validate(&lines);
+        assert_eq!(codes(&diagnostics), ["DS004", "DS004", "DS004", "DS005"]);
+        assert_eq!(
+            diagnostics[0].message,
+            "`70000` is not a port number (1-65535)"
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "unknown protocol `icmp` (expected tcp, udp or sctp)"
+        );
+        assert_eq!(
+            diagnostics[3].message,
+            "`0.0.0.0:5432:5432` publishes a host port already used by service `db` on line 7"
+        );
+    }
+
+    #[test]
+    fn test_port_ranges_collide() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "services:",
+            "  - name: web",
+            "    image: nginx",
+            "    ports: [\"8000-8010:8000-8010\", \"8005:80\"]",
+        ]);
+        assert_eq!(codes(&validate(&lines)), ["DS005"]);
+    }
+
+    #[test]
+    fn test_interpolated_ports_are_skipped() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    ports: [\"${PG_PORT:-5432}:5432\", \"${PG_PORT:-5432}:5432\"]",
+            "    healthcheck:",
+            "      tcp: 5432",
+        ]);
+        assert_eq!(validate(&lines), []);
+    }
+
+    #[test]
+    fn test_empty_image() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "services:",
+            "  - name: db",
+            "  - name: cache",
+            "    image: \"\"",
+        ]);
+        let diagnostics = validate(&lines);
+        assert_eq!(codes(&diagnostics), ["DS006", "DS006"]);
+        assert_eq!(diagnostics[0].message, "service `db` has no `image`");
+        assert_eq!((diagnostics[0].line, diagnostics[1].line), (5, 7));
+    }
+
+    #[test]
+    fn test_spin_features() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "spin-features:",
+            "  lnt:",
+            "    enabled: true",
+            "    modes: [all, everything]",
+        ]);
+        let diagnostics = validate(&lines);
+        assert_eq!(codes(&diagnostics), ["DS007", "DS008"]);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[0].message,
+            "unknown spin feature `lnt`, did you mean `lint`?"
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "unknown mode `everything` (expected one of currFile, currDir, all)"
+        );
+    }
+
+    #[test]
+    fn test_env_names() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "env:",
+            "  1ST: x",
+            "services:",
+            "  - name: db",
+            "    image: postgres:15",
+            "    environment:",
+            "      POSTGRES.USER: x",
+        ]);
+        let diagnostics = validate(&lines);
+        assert_eq!(codes(&diagnostics), ["DS009", "DS009"]);
+        assert_eq!((diagnostics[1].line, diagnostics[1].column), (10, 7));
+        assert!(is_valid_env_name("_A1"));
+        assert!(!is_valid_env_name(""));
+    }
+
+    #[test]
+    fn test_syntax_errors_are_not_diagnostics() {
+        let err = validate_str("version: 1.0\nname: [shop\n", "devspin.yml").unwrap_err();
+        assert!(err.to_string().starts_with("devspin.yml:2:"));
+    }
+
+    fn check_schema(lines: &[&str]) -> Vec<Diagnostic> {
+        validate_schema(&lines.join("\n"), "devspin.yml").expect("YAML should parse")
+    }
+
+    #[test]
+    fn test_schema_accepts_every_key() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "spin-features:",
+            "  lint:",
+            "    enabled: true",
+            "    modes: [all]",
+            "system: [git]",
+            "env:",
+            "  PORT: 8080",
+            "  DEBUG: true",
+            "services:",
+            "  - name: db",
+            "    image: postgres:16",
+            "    ports: [\"5432:5432\", 6379]",
+            "    environment:",
+            "      POSTGRES_USER: devspin",
+            "    healthcheck:",
+            "      command: pg_isready",
+            "      interval: 2s",
+            "      timeout: 1",
+            "      retries: 5",
+            "    depends_on: []",
+            "    volumes: [\"pgdata:/var/lib/postgresql/data\"]",
+            "    command: postgres -c fsync=off",
+            "    entrypoint: [docker-entrypoint.sh]",
+            "    restart: on-failure:3",
+            "    networks: [backend]",
+            "tasks:",
+            "  - name: build",
+            "    command: npm run build",
+            "    depends_on: []",
+            "    inputs: [src]",
+            "    outputs: [dist]",
+        ]);
+        assert_eq!(check_schema(&lines), []);
+        assert_eq!(validate(&lines), []);
+    }
+
+    #[test]
+    fn test_schema_reports_every_mistake() {
+        let diagnostics = check_schema(&[
+            "version: 1.0",
+            "name: shop",
+            "bse: node:20",
+            "spin-features:",
+            "  lint:",
+            "    enabled: yes",
+            "    modes: [everything]",
+            "env:",
+            "  LIST: [a]",
+            "tasks:",
+            "  - name: build",
+        ]);
+        assert!(diagnostics.iter().all(|d| d.code() == "DS010"));
+        let messages: Vec<(usize, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (1, "the document is missing the `base` key"),
+                (3, "unknown key `bse` in the document, did you mean `base`?"),
+                (6, "`spin-features.lint.enabled` should be a boolean, found a string"),
+                (
+                    7,
+                    "`spin-features.lint.modes[0]` should be one of `currFile`, `currDir`, `all`, found `everything`"
+                ),
+                (
+                    9,
+                    "`env.LIST` should be a string, a number or a boolean, found a sequence"
+                ),
+                (11, "`tasks[0]` is missing the `command` key"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_healthchecks() {
+        let mut lines = HEADER.to_vec();
+        lines.extend([
+            "services:",
+            "  - name: db",
+            "    ports: [\"5432:5432\"]",
+            "    healthcheck:",
+            "      tcp: 5432",
+            "      command: pg_isready",
+            "  - name: web",
+            "    healthcheck:",
+            "      tcp: 70000",
+            "      intervl: 2s",
+        ]);
+        let messages: Vec<String> = check_schema(&lines)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "`services[0].healthcheck` should be only one of: a mapping with `command`, a mapping with `tcp`, a mapping with `http`",
+                "`services[1].healthcheck.tcp` should be between 1 and 65535, found 70000",
+                "unknown key `intervl` in `services[1].healthcheck`, did you mean `interval`?",
+            ]
+        );
+    }
+}
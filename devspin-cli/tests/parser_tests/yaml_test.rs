@@ -0,0 +1,245 @@
+use devspin_cli::error::DevSpinError;
+use devspin_cli::parser::yaml::*;
+
+mod tests {
+    use super::*;
+
+    fn parse_ok(source: &str) -> Node {
+        parse(source, "devspin.yml").expect("Failed to parse yaml")
+    }
+
+    fn parse_err(source: &str) -> (usize, usize, String) {
+        match parse(source, "devspin.yml") {
+            Err(DevSpinError::Parse {
+                line,
+                column,
+                message,
+                ..
+            }) => (line, column, message),
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_mappings_follow_indentation() {
+        let root = parse_ok("a:\n  b: 1\n  c:\n    d: 2\ne: 3\n");
+
+        assert_eq!(root.get("a").unwrap().get("b").unwrap().as_str(), Some("1"));
+        assert_eq!(
+            root.get("a")
+                .unwrap()
+                .get("c")
+                .unwrap()
+                .get("d")
+                .unwrap()
+                .as_str(),
+            Some("2")
+        );
+        assert_eq!(root.get("e").unwrap().as_str(), Some("3"));
+        assert!(root.get("a").unwrap().get("e").is_none());
+    }
+
+    #[test]
+    fn test_sequence_of_mappings() {
+        let root = parse_ok("items:\n  - name: a\n    value: 1\n  - name: b\n");
+        let items = root.get("items").unwrap().as_sequence().unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].get("value").unwrap().as_str(), Some("1"));
+        assert_eq!(items[1].get("name").unwrap().as_str(), Some("b"));
+    }
+
+    #[test]
+    fn test_compact_sequence_under_key() {
+        let root = parse_ok("list:\n- a\n- b\nnext: c\n");
+
+        assert_eq!(root.get("list").unwrap().as_sequence().unwrap().len(), 2);
+        assert_eq!(root.get("next").unwrap().as_str(), Some("c"));
+    }
+
+    #[test]
+    fn test_flow_collections() {
+        let root = parse_ok("modes: [currFile, 'cur dir', \"all\"]\nmap: {a: 1, b: [x]}\n");
+        let modes = root.get("modes").unwrap();
+
+        assert!(modes.flow);
+        let modes: Vec<_> = modes
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|node| node.as_str().unwrap())
+            .collect();
+        assert_eq!(modes, ["currFile", "cur dir", "all"]);
+        assert_eq!(
+            root.get("map").unwrap().get("a").unwrap().as_str(),
+            Some("1")
+        );
+    }
+
+    #[test]
+    fn test_quoted_scalars_and_comments() {
+        let root = parse_ok(
+            "# header\nport: \"5432:5432\" # mapped\nsingle: 'it''s'\nescaped: \"a\\tb\"\nurl: http://x#y\n",
+        );
+
+        assert_eq!(root.get("port").unwrap().as_str(), Some("5432:5432"));
+        assert_eq!(root.get("single").unwrap().as_str(), Some("it's"));
+        assert_eq!(root.get("escaped").unwrap().as_str(), Some("a\tb"));
+        assert_eq!(root.get("url").unwrap().as_str(), Some("http://x#y"));
+    }
+
+    #[test]
+    fn test_empty_values_are_null() {
+        let root = parse_ok("a:\nb: 1\n");
+
+        assert!(root.get("a").unwrap().is_null());
+    }
+
+    #[test]
+    fn test_spans_point_at_values() {
+        let root = parse_ok("a:\n  b: value\n");
+        let span = root.get("a").unwrap().get("b").unwrap().span;
+
+        assert_eq!((span.line, span.column, span.end_column), (2, 6, 11));
+    }
+
+    #[test]
+    fn test_unexpected_indentation_is_reported() {
+        let (line, column, message) = parse_err("env:\n  A: 1\n    B: 2\n");
+
+        assert_eq!((line, column), (3, 5));
+        assert!(message.contains("unexpected indentation"));
+    }
+
+    #[test]
+    fn test_duplicate_keys_are_reported() {
+        let (line, _, message) = parse_err("a: 1\na: 2\n");
+
+        assert_eq!(line, 2);
+        assert!(message.contains("duplicate key `a`"));
+    }
+
+    #[test]
+    fn test_unterminated_flow_sequence_is_reported() {
+        let (line, column, message) = parse_err("modes: [a, b\n");
+
+        assert_eq!((line, column), (1, 8));
+        assert!(message.contains("expected `]`"));
+    }
+
+    #[test]
+    fn test_tabs_are_rejected() {
+        let (line, column, message) = parse_err("a:\n\tb: 1\n");
+
+        assert_eq!((line, column), (2, 1));
+        assert!(message.contains("tabs"));
+    }
+
+    #[test]
+    fn test_tabs_inside_block_scalars_are_text() {
+        let root =
+            parse_ok("script: |\n  make\n  \tindented\nlist:\n  - |-\n    \ta\n    b\nnext: x\n");
+
+        assert_eq!(
+            root.get("script").unwrap().as_str(),
+            Some("make\n\tindented\n")
+        );
+        assert_eq!(
+            root.get("list").unwrap().as_sequence().unwrap()[0].as_str(),
+            Some("\ta\nb")
+        );
+        assert_eq!(root.get("next").unwrap().as_str(), Some("x"));
+
+        // Once the block ends the tab is indentation again
+        let (line, column, _) = parse_err("script: |\n  make\n\tnext: x\n");
+        assert_eq!((line, column), (3, 1));
+    }
+
+    #[test]
+    fn test_error_display_includes_path_and_caret() {
+        let err = parse("a: [b\n", "conf/devspin.yml").unwrap_err();
+        let rendered = err.to_string();
+
+        assert!(rendered.starts_with("conf/devspin.yml:1:4:"));
+        assert!(rendered.contains("1 | a: [b"));
+        assert!(rendered.contains("  |    ^"));
+    }
+
+    #[test]
+    fn test_literal_block_scalar_keeps_lines() {
+        let root = parse_ok("command: |\n  echo a\n\n  echo b\nnext: x\n");
+
+        assert_eq!(
+            root.get("command").unwrap().as_str(),
+            Some("echo a\n\necho b\n")
+        );
+        assert_eq!(root.get("next").unwrap().as_str(), Some("x"));
+    }
+
+    #[test]
+    fn test_block_scalar_chomping_indicators() {
+        let root = parse_ok("strip: |-\n  a\n\nkeep: |+\n  a\n\nclip: |\n  a\n\n");
+
+        assert_eq!(root.get("strip").unwrap().as_str(), Some("a"));
+        assert_eq!(root.get("keep").unwrap().as_str(), Some("a\n\n"));
+        assert_eq!(root.get("clip").unwrap().as_str(), Some("a\n"));
+    }
+
+    #[test]
+    fn test_folded_block_scalar_joins_lines() {
+        let root = parse_ok("text: >\n  one\n  two\n\n  three\n    indented\n");
+
+        assert_eq!(
+            root.get("text").unwrap().as_str(),
+            Some("one two\nthree\n  indented\n")
+        );
+    }
+
+    #[test]
+    fn test_block_scalar_in_sequence_entry() {
+        let root = parse_ok(
+            "tasks:\n  - name: a\n    command: |\n      x # not a comment\n      y\n  - name: b\n",
+        );
+        let tasks = root.get("tasks").unwrap().as_sequence().unwrap();
+
+        assert_eq!(
+            tasks[0].get("command").unwrap().as_str(),
+            Some("x # not a comment\ny\n")
+        );
+        assert_eq!(tasks[1].get("name").unwrap().as_str(), Some("b"));
+    }
+
+    #[test]
+    fn test_block_scalar_explicit_indentation() {
+        let root = parse_ok("a: |2\n    leading\n  b\n");
+
+        assert_eq!(root.get("a").unwrap().as_str(), Some("  leading\nb\n"));
+    }
+
+    #[test]
+    fn test_format_value_round_trips() {
+        let values = [
+            "plain",
+            "needs: quotes",
+            "- dash",
+            "",
+            " padded ",
+            "line one\nline two\n",
+            "no trailing\nnewline",
+            "kept\n\n",
+            "  indented first\nsecond\n",
+        ];
+
+        for value in values {
+            let source = format!("key: {}\n", format_value(value, 0));
+            let root = parse_ok(&source);
+            assert_eq!(
+                root.get("key").unwrap().as_str(),
+                Some(value),
+                "round trip of {:?} through {:?}",
+                value,
+                source
+            );
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use devspin_cli::parser::devspin_generator::Service;
+use devspin_cli::runtime::cli::{create_args, run_args};
+use devspin_cli::runtime::*;
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    /// Stub runtime binary logging its arguments to `args.log` and printing `stdout`
+    fn stub_binary(dir: &Path, name: &str, stdout: &str) -> PathBuf {
+        let path = dir.join(name);
+        let script = format!(
+            "#!/bin/sh\necho \"$@\" >> \"{}\"\nprintf '%s' '{}'\n",
+            dir.join("args.log").display(),
+            stdout
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_create_args() {
+        let service = Service {
+            name: "db".to_string(),
+            image: "postgres:15".to_string(),
+            ports: vec!["5432:5432".to_string()],
+            environment: IndexMap::from([("PGUSER".to_string(), "dev".to_string())]),
+            ..Default::default()
+        };
+        let args = create_args(
+            &ContainerSpec::for_service("shop", &service),
+            Some(Path::new("/tmp/devspin-env-1")),
+        );
+
+        assert_eq!(
+            args,
+            [
+                "create",
+                "--name",
+                "shop-db",
+                "--label",
+                "devspin.project=shop",
+                "--label",
+                "devspin.service=db",
+                "--publish",
+                "5432:5432",
+                "--env-file",
+                "/tmp/devspin-env-1",
+                "--network",
+                "shop_default",
+                "--network-alias",
+                "db",
+                "postgres:15"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_args_with_overrides() {
+        let service = Service {
+            name: "db".to_string(),
+            image: "postgres:15".to_string(),
+            volumes: vec!["pgdata:/data".to_string()],
+            entrypoint: vec!["docker-entrypoint.sh".to_string(), "-v".to_string()],
+            command: vec![
+                "postgres".to_string(),
+                "-c".to_string(),
+                "fsync=off".to_string(),
+            ],
+            restart: Some("always".to_string()),
+            ..Default::default()
+        };
+        let args = create_args(&ContainerSpec::for_service("shop", &service), None).join(" ");
+
+        assert!(args.ends_with(
+            "--volume shop_pgdata:/data --network shop_default --network-alias db --restart always --entrypoint docker-entrypoint.sh postgres:15 -v postgres -c fsync=off"
+        ));
+    }
+
+    #[test]
+    fn test_run_args_pass_env_through_a_file() {
+        let spec = ContainerSpec {
+            name: "shop-shell-1".to_string(),
+            image: "shop-shell".to_string(),
+            env: IndexMap::from([("PGPASSWORD".to_string(), "s3cret".to_string())]),
+            volumes: vec!["/work/shop:/app".to_string()],
+            command: vec!["/bin/sh".to_string()],
+            workdir: Some("/app".to_string()),
+            hostname: Some("shop".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            run_args(&spec, true, Some(Path::new("/tmp/devspin-env-1"))).join(" "),
+            "run --rm --interactive --tty --name shop-shell-1 --env-file /tmp/devspin-env-1 --workdir /app --hostname shop --volume /work/shop:/app shop-shell /bin/sh"
+        );
+        assert!(!run_args(&spec, false, None).contains(&"--tty".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_cli_runtime_builds_from_stdin() {
+        let temp_dir = tempdir().unwrap();
+        let binary = temp_dir.path().join("docker");
+        let script = format!(
+            "#!/bin/sh\necho \"$@\" > \"{0}/args.log\"\ncat > \"{0}/Dockerfile\"\n",
+            temp_dir.path().display()
+        );
+        fs::write(&binary, script).unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        let runtime = CliRuntime::new(RuntimeKind::Docker, binary);
+
+        runtime
+            .build("shop-shell", "FROM rust:1.80\n")
+            .await
+            .unwrap();
+
+        let log = fs::read_to_string(temp_dir.path().join("args.log")).unwrap();
+        assert!(log.starts_with("build --tag shop-shell --file - "));
+        let dockerfile = fs::read_to_string(temp_dir.path().join("Dockerfile")).unwrap();
+        assert_eq!(dockerfile, "FROM rust:1.80\n");
+    }
+
+    #[tokio::test]
+    async fn test_cli_runtime_creates_with_env_values_outside_args() {
+        let temp_dir = tempdir().unwrap();
+        let binary = temp_dir.path().join("docker");
+        // Logs the arguments, its own DOCKER_HOST and the env file while it exists
+        let script = format!(
+            "#!/bin/sh\necho \"$@\" > \"{0}/args.log\"\necho \"[$DOCKER_HOST]\" > \"{0}/process.log\"\nwhile [ $# -gt 0 ]; do\n  if [ \"$1\" = --env-file ]; then cat \"$2\" > \"{0}/env.log\"; ls -l \"$2\" | cut -c1-10 > \"{0}/mode.log\"; fi\n  shift\ndone\necho id1\n",
+            temp_dir.path().display()
+        );
+        fs::write(&binary, script).unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        let runtime = CliRuntime::new(RuntimeKind::Docker, binary);
+        let service = Service {
+            name: "db".to_string(),
+            image: "postgres:16".to_string(),
+            environment: IndexMap::from([
+                ("POSTGRES_PASSWORD".to_string(), "hunter2".to_string()),
+                (
+                    "DOCKER_HOST".to_string(),
+                    "tcp://elsewhere:2375".to_string(),
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        let id = runtime
+            .create(&ContainerSpec::for_service("shop", &service))
+            .await
+            .unwrap();
+
+        assert_eq!(id, "id1");
+        let args = fs::read_to_string(temp_dir.path().join("args.log")).unwrap();
+        assert!(!args.contains("hunter2"), "{}", args);
+        let env_file = args
+            .split_whitespace()
+            .skip_while(|arg| *arg != "--env-file")
+            .nth(1)
+            .unwrap();
+        assert!(!Path::new(env_file).exists());
+        let env = fs::read_to_string(temp_dir.path().join("env.log")).unwrap();
+        assert_eq!(
+            env,
+            "POSTGRES_PASSWORD=hunter2\nDOCKER_HOST=tcp://elsewhere:2375\n"
+        );
+        let mode = fs::read_to_string(temp_dir.path().join("mode.log")).unwrap();
+        assert_eq!(mode, "-rw-------\n");
+        // The CLI itself still talks to its own daemon
+        let process = fs::read_to_string(temp_dir.path().join("process.log")).unwrap();
+        assert_eq!(process, "[]\n");
+
+        let service = Service {
+            environment: IndexMap::from([("CERT".to_string(), "a\nb".to_string())]),
+            ..service
+        };
+        let err = runtime
+            .create(&ContainerSpec::for_service("shop", &service))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("`CERT` holds a line break"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_runtime_parses_output() {
+        let temp_dir = tempdir().unwrap();
+        let binary = stub_binary(temp_dir.path(), "docker", "abc123\t/shop-db\trunning\t0\n");
+        let runtime = CliRuntime::new(RuntimeKind::Docker, binary);
+
+        let state = runtime.inspect("shop-db").await.unwrap();
+        assert_eq!(state.name, "shop-db");
+        assert!(state.is_running());
+
+        let listed = runtime.list(PROJECT_LABEL, "shop").await.unwrap();
+        assert_eq!(listed[0].id, "abc123");
+        runtime.remove("shop-db", true).await.unwrap();
+
+        let log = fs::read_to_string(temp_dir.path().join("args.log")).unwrap();
+        assert!(log.contains("ps --all --filter label=devspin.project=shop"));
+        assert!(log.contains("rm --force --volumes shop-db"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_runtime_reports_failures() {
+        let temp_dir = tempdir().unwrap();
+        let binary = temp_dir.path().join("podman");
+        fs::write(&binary, "#!/bin/sh\necho 'no such image' >&2\nexit 1\n").unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+        let runtime = CliRuntime::new(RuntimeKind::Podman, binary);
+
+        let err = runtime.pull("nope:1").await.unwrap_err().to_string();
+        assert!(err.contains("`podman pull` failed: no such image"));
+    }
+
+    #[test]
+    fn test_detect_runtime_from_path() {
+        let temp_dir = tempdir().unwrap();
+        stub_binary(temp_dir.path(), "podman", "");
+        std::env::set_var("PATH", temp_dir.path());
+        std::env::remove_var("DEVSPIN_RUNTIME");
+
+        assert_eq!(detect_runtime().unwrap().name(), "podman");
+
+        stub_binary(temp_dir.path(), "docker", "");
+        assert_eq!(detect_runtime().unwrap().name(), "docker");
+
+        std::env::set_var("DEVSPIN_RUNTIME", "podman");
+        assert_eq!(detect_runtime().unwrap().name(), "podman");
+
+        std::env::set_var("DEVSPIN_RUNTIME", "containerd");
+        assert!(detect_runtime().is_err());
+
+        std::env::remove_var("DEVSPIN_RUNTIME");
+        std::env::set_var("PATH", temp_dir.path().join("empty"));
+        let err = detect_runtime().err().unwrap().to_string();
+        assert!(err.contains("No container runtime found on PATH"));
+    }
+}
@@ -0,0 +1,89 @@
+use devspin_cli::parser::devspin_generator::Service;
+use devspin_cli::runtime::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn postgres() -> Service {
+        Service {
+            name: "db".to_string(),
+            image: "postgres:15".to_string(),
+            ports: vec!["5432:5432".to_string()],
+            environment: IndexMap::from([("POSTGRES_PASSWORD".to_string(), "dev".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spec_for_service() {
+        let spec = ContainerSpec::for_service("shop", &postgres());
+
+        assert_eq!(spec.name, "shop-db");
+        assert_eq!(spec.image, "postgres:15");
+        assert_eq!(spec.labels[PROJECT_LABEL], "shop");
+        assert_eq!(spec.labels[SERVICE_LABEL], "db");
+        assert_eq!(container_name("My Shop", "db"), "My-Shop-db");
+    }
+
+    #[tokio::test]
+    async fn test_fake_records_lifecycle() {
+        let runtime = FakeRuntime::new().with_logs("shop-db", "one\ntwo\nthree\n");
+        let spec = ContainerSpec::for_service("shop", &postgres());
+
+        runtime.pull(&spec.image).await.unwrap();
+        let id = runtime.create(&spec).await.unwrap();
+        runtime.start(&id).await.unwrap();
+
+        assert_eq!(runtime.running(), vec![spec.clone()]);
+        assert!(runtime.inspect("shop-db").await.unwrap().is_running());
+        assert_eq!(runtime.logs("shop-db", 2).await.unwrap(), "two\nthree\n");
+        let listed = runtime.list(PROJECT_LABEL, "shop").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(runtime
+            .list(PROJECT_LABEL, "other")
+            .await
+            .unwrap()
+            .is_empty());
+
+        runtime.stop("shop-db").await.unwrap();
+        runtime.remove("shop-db", true).await.unwrap();
+        assert!(runtime.container_names().is_empty());
+        assert_eq!(
+            runtime.calls(),
+            vec![
+                RuntimeCall::Pull("postgres:15".to_string()),
+                RuntimeCall::Create("shop-db".to_string()),
+                RuntimeCall::Start(id),
+                RuntimeCall::Inspect("shop-db".to_string()),
+                RuntimeCall::Logs("shop-db".to_string()),
+                RuntimeCall::List {
+                    key: PROJECT_LABEL.to_string(),
+                    value: "shop".to_string()
+                },
+                RuntimeCall::List {
+                    key: PROJECT_LABEL.to_string(),
+                    value: "other".to_string()
+                },
+                RuntimeCall::Stop("shop-db".to_string()),
+                RuntimeCall::Remove {
+                    container: "shop-db".to_string(),
+                    volumes: true
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fake_reports_errors_like_a_runtime() {
+        let runtime = FakeRuntime::new().with_missing_image("nope:1");
+        let spec = ContainerSpec::for_service("shop", &postgres());
+
+        assert!(runtime.pull("nope:1").await.is_err());
+        assert!(runtime.start("missing").await.is_err());
+        runtime.create(&spec).await.unwrap();
+        let conflict = runtime.create(&spec).await.unwrap_err();
+        assert!(conflict.to_string().contains("already in use"));
+    }
+}
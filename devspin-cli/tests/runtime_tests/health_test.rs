@@ -0,0 +1,177 @@
+use devspin_cli::parser::devspin_generator::{Healthcheck, Probe, Service};
+use devspin_cli::runtime::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn service(port: u16, probe: Probe) -> Service {
+        Service {
+            name: "web".to_string(),
+            image: "nginx".to_string(),
+            ports: vec![format!("{}:{}", port, port)],
+            environment: IndexMap::new(),
+            healthcheck: Some(Healthcheck {
+                interval: Duration::from_millis(5),
+                retries: 3,
+                ..Healthcheck::new(probe)
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// HTTP server answering every request with `status`
+    async fn http_server(status: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer).await;
+                let response = format!("HTTP/1.0 {}\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        port
+    }
+
+    fn options() -> UpOptions {
+        UpOptions {
+            pull: false,
+            timeout: Duration::from_millis(50),
+            interval: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_probe_runs_in_container() {
+        let runtime = FakeRuntime::new();
+        let web = service(8080, Probe::Command("curl -f localhost".to_string()));
+
+        Environment::new(&runtime, "shop")
+            .up(std::slice::from_ref(&web), &options())
+            .await
+            .unwrap();
+
+        assert!(runtime.calls().contains(&RuntimeCall::Exec {
+            container: "fake-1".to_string(),
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "curl -f localhost".to_string()
+            ],
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_service_reports_last_failure_and_logs() {
+        let runtime = FakeRuntime::new()
+            .with_exec_result(
+                "shop-web",
+                ExecOutput {
+                    exit_code: 2,
+                    stdout: String::new(),
+                    stderr: "connection refused".to_string(),
+                },
+            )
+            .with_logs("shop-web", "booting\nstill booting\n");
+        let web = service(8080, Probe::Command("curl -f localhost".to_string()));
+
+        let err = Environment::new(&runtime, "shop")
+            .up(&[web], &options())
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Service 'web' did not become healthy after 3 checks"));
+        assert!(err.contains("command exited with 2: connection refused"));
+        assert!(err.contains("still booting"));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let runtime = FakeRuntime::new();
+        let web = service(port, Probe::Tcp(port));
+
+        assert_eq!(
+            health::probe(
+                &runtime,
+                "shop-web",
+                &web,
+                web.healthcheck.as_ref().unwrap()
+            )
+            .await,
+            Ok(())
+        );
+        drop(listener);
+        assert!(health::probe(
+            &runtime,
+            "shop-web",
+            &web,
+            web.healthcheck.as_ref().unwrap()
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_probe_checks_status() {
+        let runtime = FakeRuntime::new();
+        let ok_port = http_server("200 OK").await;
+        let ok = service(
+            ok_port,
+            Probe::Http {
+                path: "/health".to_string(),
+                port: None,
+            },
+        );
+        assert_eq!(
+            health::probe(&runtime, "shop-web", &ok, ok.healthcheck.as_ref().unwrap()).await,
+            Ok(())
+        );
+
+        let failing_port = http_server("503 Service Unavailable").await;
+        let failing = service(
+            failing_port,
+            Probe::Http {
+                path: "/health".to_string(),
+                port: Some(failing_port),
+            },
+        );
+        let err = health::probe(
+            &runtime,
+            "shop-web",
+            &failing,
+            failing.healthcheck.as_ref().unwrap(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, "GET /health returned 503");
+    }
+
+    #[tokio::test]
+    async fn test_wait_ready_lists_services_not_running() {
+        let web = service(8080, Probe::Command("true".to_string()));
+        let runtime = FakeRuntime::new();
+
+        let not_running = Environment::new(&runtime, "shop")
+            .wait_ready(std::slice::from_ref(&web))
+            .await
+            .unwrap();
+        assert_eq!(not_running, ["web"]);
+
+        let runtime =
+            FakeRuntime::new().with_container(ContainerSpec::for_service("shop", &web), true);
+        let not_running = Environment::new(&runtime, "shop")
+            .wait_ready(&[web])
+            .await
+            .unwrap();
+        assert!(not_running.is_empty());
+    }
+}
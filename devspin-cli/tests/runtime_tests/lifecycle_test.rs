@@ -0,0 +1,311 @@
+use devspin_cli::parser::devspin_generator::Service;
+use devspin_cli::runtime::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn service(name: &str, image: &str) -> Service {
+        Service {
+            name: name.to_string(),
+            image: image.to_string(),
+            ports: Vec::new(),
+            environment: IndexMap::new(),
+            ..Default::default()
+        }
+    }
+
+    fn options() -> UpOptions {
+        UpOptions {
+            timeout: Duration::from_millis(50),
+            interval: Duration::from_millis(5),
+            ..UpOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_up_starts_every_service() {
+        let runtime = FakeRuntime::new();
+        let services = [service("db", "postgres:15"), service("cache", "redis:7")];
+
+        let actions = Environment::new(&runtime, "shop")
+            .up(&services, &options())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            actions,
+            [
+                ("db".to_string(), ServiceAction::Started),
+                ("cache".to_string(), ServiceAction::Started)
+            ]
+        );
+        let running: Vec<String> = runtime.running().into_iter().map(|s| s.name).collect();
+        assert_eq!(running, ["shop-db", "shop-cache"]);
+        assert!(runtime
+            .calls()
+            .contains(&RuntimeCall::Pull("postgres:15".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_up_keeps_running_containers() {
+        let db = service("db", "postgres:15");
+        let runtime =
+            FakeRuntime::new().with_container(ContainerSpec::for_service("shop", &db), true);
+
+        let actions = Environment::new(&runtime, "shop")
+            .up(&[db], &options())
+            .await
+            .unwrap();
+
+        assert_eq!(actions, [("db".to_string(), ServiceAction::AlreadyRunning)]);
+        assert!(!runtime
+            .calls()
+            .iter()
+            .any(|call| matches!(call, RuntimeCall::Create(_))));
+    }
+
+    #[tokio::test]
+    async fn test_up_reports_crashed_service_with_logs() {
+        let runtime = FakeRuntime::new()
+            .with_crash("shop-db", 1)
+            .with_logs("shop-db", "FATAL: password not set\n");
+
+        let err = Environment::new(&runtime, "shop")
+            .up(&[service("db", "postgres:15")], &options())
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Service 'db' exited with code 1"));
+        assert!(err.contains("FATAL: password not set"));
+    }
+
+    #[tokio::test]
+    async fn test_down_removes_only_project_containers() {
+        let runtime = FakeRuntime::new()
+            .with_container(
+                ContainerSpec::for_service("shop", &service("db", "postgres:15")),
+                true,
+            )
+            .with_container(
+                ContainerSpec::for_service("blog", &service("db", "mysql:8")),
+                true,
+            )
+            .with_network("shop_default", project_labels("shop"))
+            .with_volume("shop_pgdata", project_labels("shop"))
+            .with_volume("blog_data", project_labels("blog"));
+
+        let removed = Environment::new(&runtime, "shop").down(true).await.unwrap();
+
+        assert_eq!(
+            removed,
+            Removed {
+                containers: vec!["shop-db".to_string()],
+                networks: vec!["shop_default".to_string()],
+                volumes: vec!["shop_pgdata".to_string()],
+            }
+        );
+        assert_eq!(runtime.container_names(), ["blog-db"]);
+        assert_eq!(runtime.volumes(), ["blog_data"]);
+        assert!(runtime.calls().contains(&RuntimeCall::Remove {
+            container: "fake-1".to_string(),
+            volumes: true
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_down_keeps_volumes_by_default() {
+        let runtime = FakeRuntime::new().with_volume("shop_pgdata", project_labels("shop"));
+
+        let removed = Environment::new(&runtime, "shop")
+            .down(false)
+            .await
+            .unwrap();
+
+        assert!(removed.volumes.is_empty());
+        assert_eq!(runtime.volumes(), ["shop_pgdata"]);
+    }
+
+    #[tokio::test]
+    async fn test_up_starts_dependencies_first() {
+        let runtime = FakeRuntime::new();
+        let api = Service {
+            depends_on: vec!["db".to_string(), "cache".to_string()],
+            ..service("api", "shop-api")
+        };
+        let services = [
+            api,
+            service("db", "postgres:15"),
+            service("cache", "redis:7"),
+        ];
+
+        Environment::new(&runtime, "shop")
+            .up(&services, &options())
+            .await
+            .unwrap();
+
+        let running: Vec<String> = runtime.running().into_iter().map(|s| s.name).collect();
+        assert_eq!(running, ["shop-db", "shop-cache", "shop-api"]);
+    }
+
+    #[tokio::test]
+    async fn test_up_rejects_dependency_cycles() {
+        let runtime = FakeRuntime::new();
+        let services = [
+            Service {
+                depends_on: vec!["b".to_string()],
+                ..service("a", "img")
+            },
+            Service {
+                depends_on: vec!["a".to_string()],
+                ..service("b", "img")
+            },
+        ];
+
+        let err = Environment::new(&runtime, "shop")
+            .up(&services, &options())
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Cannot start services: dependency cycle: a -> b -> a"));
+        assert!(runtime.running().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_up_creates_networks_and_volumes() {
+        let runtime = FakeRuntime::new();
+        let db = Service {
+            volumes: vec![
+                "pgdata:/var/lib/postgresql/data".to_string(),
+                "./init:/docker-entrypoint-initdb.d:ro".to_string(),
+            ],
+            networks: vec!["backend".to_string(), "metrics".to_string()],
+            restart: Some("unless-stopped".to_string()),
+            ..service("db", "postgres:15")
+        };
+
+        Environment::new(&runtime, "shop")
+            .with_root(Path::new("/work/shop"))
+            .up(&[db, service("cache", "redis:7")], &options())
+            .await
+            .unwrap();
+
+        assert_eq!(runtime.volumes(), ["shop_pgdata"]);
+        assert_eq!(
+            runtime.networks(),
+            ["shop_backend", "shop_metrics", "shop_default"]
+        );
+        let db = &runtime.running()[0];
+        assert_eq!(
+            db.volumes,
+            [
+                "shop_pgdata:/var/lib/postgresql/data",
+                "/work/shop/init:/docker-entrypoint-initdb.d:ro"
+            ]
+        );
+        assert_eq!(db.restart.as_deref(), Some("unless-stopped"));
+        assert!(runtime.calls().contains(&RuntimeCall::ConnectNetwork {
+            network: "shop_metrics".to_string(),
+            container: "fake-1".to_string(),
+            alias: "db".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_connection_env() {
+        let db = Service {
+            ports: vec!["5433:5432".to_string()],
+            ..service("pg-db", "postgres:15")
+        };
+        let cache = Service {
+            ports: vec!["6379".to_string()],
+            ..service("cache", "redis:7")
+        };
+        let services = [db, cache, service("worker", "busybox")];
+
+        assert_eq!(
+            connection_env(&services, false),
+            IndexMap::from([
+                ("PG_DB_HOST".to_string(), "localhost".to_string()),
+                ("PG_DB_PORT".to_string(), "5433".to_string()),
+            ])
+        );
+        assert_eq!(
+            connection_env(&services, true),
+            IndexMap::from([
+                ("PG_DB_HOST".to_string(), "pg-db".to_string()),
+                ("PG_DB_PORT".to_string(), "5432".to_string()),
+                ("CACHE_HOST".to_string(), "cache".to_string()),
+                ("CACHE_PORT".to_string(), "6379".to_string()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shell_builds_image_and_joins_project_network() {
+        let runtime = FakeRuntime::new()
+            .with_network("shop_default", project_labels("shop"))
+            .with_attached_exit_code(3);
+        let env = IndexMap::from([("DB_HOST".to_string(), "db".to_string())]);
+
+        let code = Environment::new(&runtime, "Shop")
+            .with_root(Path::new("/work/shop"))
+            .shell("FROM rust:1.80\n", env.clone(), vec!["/bin/sh".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(code, 3);
+        assert_eq!(
+            runtime.calls()[0],
+            RuntimeCall::Build {
+                tag: "shop-shell".to_string(),
+                dockerfile: "FROM rust:1.80\n".to_string(),
+            }
+        );
+        let spec = &runtime.attached()[0];
+        assert_eq!(spec.image, "shop-shell");
+        assert_eq!(spec.env, env);
+        assert_eq!(spec.volumes, ["/work/shop:/app"]);
+        assert_eq!(spec.workdir.as_deref(), Some(SHELL_WORKDIR));
+        assert_eq!(spec.hostname.as_deref(), Some("shop"));
+        assert_eq!(spec.command, ["/bin/sh"]);
+    }
+
+    #[tokio::test]
+    async fn test_shell_without_services_up() {
+        let runtime = FakeRuntime::new();
+
+        Environment::new(&runtime, "shop")
+            .shell(
+                "FROM alpine\n",
+                IndexMap::new(),
+                vec!["/bin/sh".to_string()],
+            )
+            .await
+            .unwrap();
+
+        assert!(runtime.attached()[0].networks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shell_fails_when_build_fails() {
+        let runtime = FakeRuntime::new().with_missing_image("nope:1");
+
+        let err = Environment::new(&runtime, "shop")
+            .shell(
+                "FROM nope:1\n",
+                IndexMap::new(),
+                vec!["/bin/sh".to_string()],
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("build"));
+        assert!(runtime.attached().is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use devspin_cli::secrets::encrypted::*;
+use devspin_cli::secrets::SecretStore;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let store = AgeStore::new(temp_dir.path().join("devspin"));
+        assert!(store.load().unwrap().is_empty());
+        // Reading an empty store creates nothing
+        assert!(!store.identity_path().exists());
+
+        store.set("pg_password", "hunter2").unwrap();
+        store.set("api_token", "t0k3n").unwrap();
+        assert_eq!(
+            store.get("pg_password").unwrap().as_deref(),
+            Some("hunter2")
+        );
+
+        let encrypted = fs::read(store.secrets_path()).unwrap();
+        assert!(encrypted.starts_with(b"age-encryption.org/v1\n"));
+        let text = String::from_utf8_lossy(&encrypted);
+        assert!(!text.contains("hunter2") && !text.contains("pg_password"));
+
+        // A new handle on the same dir reads the same secrets
+        let reopened = AgeStore::new(temp_dir.path().join("devspin"));
+        let names: Vec<String> = reopened.load().unwrap().into_keys().collect();
+        assert_eq!(names, ["pg_password", "api_token"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempdir().unwrap();
+        let store = AgeStore::new(temp_dir.path());
+        store.set("a", "b").unwrap();
+        for path in [store.identity_path(), store.secrets_path()] {
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let temp_dir = tempdir().unwrap();
+        let store = AgeStore::new(temp_dir.path());
+        store.set("a", "b").unwrap();
+
+        // Another key cannot decrypt the file
+        fs::remove_file(store.identity_path()).unwrap();
+        let err = store.load().unwrap_err().to_string();
+        assert!(err.contains("identity.txt: missing"), "{}", err);
+        let other = AgeStore::new(temp_dir.path().join("other"));
+        other.set("c", "d").unwrap();
+        fs::copy(other.identity_path(), store.identity_path()).unwrap();
+        let err = store.load().unwrap_err().to_string();
+        assert!(err.contains("secrets.age: cannot decrypt"), "{}", err);
+
+        fs::write(store.identity_path(), "# nothing\n").unwrap();
+        let err = store.load().unwrap_err().to_string();
+        assert!(err.contains("no age identity"), "{}", err);
+    }
+}
@@ -0,0 +1,186 @@
+use devspin_cli::error::DevSpinError;
+use devspin_cli::parser::devspin_generator::{DevSpinConfig, DevSpinGenerator};
+use devspin_cli::parser::interpolate::{self, Layer};
+use devspin_cli::secrets::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use tempfile::{tempdir, TempDir};
+
+    fn config(body: &str) -> DevSpinConfig {
+        let source = format!("version: 1.0\nname: shop\nbase: node:20\n{}", body);
+        DevSpinGenerator::parse_str(&source, "devspin.yml").unwrap()
+    }
+
+    /// File store holding `secrets`, in a dir kept alive by the returned guard
+    fn store(secrets: &[(&str, &str)]) -> (TempDir, FileStore) {
+        let temp_dir = tempdir().unwrap();
+        let store = FileStore::new(temp_dir.path().join("secrets.json"));
+        for (name, value) in secrets {
+            store.set(name, value).unwrap();
+        }
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_reference() {
+        assert_eq!(reference("secret:pg_password"), Some("pg_password"));
+        assert_eq!(reference("secret:prod.api-key"), Some("prod.api-key"));
+        assert_eq!(reference("secret:"), None);
+        assert_eq!(reference("secret:two words"), None);
+        assert_eq!(reference("my secret:x"), None);
+    }
+
+    #[test]
+    fn test_file_store() {
+        let (_temp_dir, store) = store(&[]);
+        assert!(store.load().unwrap().is_empty());
+
+        store.set("b", "2").unwrap();
+        store.set("a", "line\n\"quoted\"").unwrap();
+        assert_eq!(store.get("a").unwrap().as_deref(), Some("line\n\"quoted\""));
+        let names: Vec<String> = store.load().unwrap().into_keys().collect();
+        assert_eq!(names, ["b", "a"]);
+
+        assert!(store.remove("b").unwrap());
+        assert!(!store.remove("b").unwrap());
+        assert_eq!(store.get("b").unwrap(), None);
+    }
+
+    #[test]
+    fn test_resolve_before_interpolation() {
+        let (_temp_dir, store) = store(&[("pg", "pa${ss}"), ("token", "t0k3n")]);
+        let mut config = config(
+            "env:\n  PG_PASSWORD: secret:pg\n  URL: postgres://devspin:${PG_PASSWORD}@db/app\n  NOT_A_REFERENCE: a secret:pg\nservices:\n  - name: db\n    image: postgres\n    environment:\n      POSTGRES_PASSWORD: secret:pg\n",
+        );
+        let mut layers = vec![
+            Layer::process(),
+            Layer::new(
+                ".env",
+                IndexMap::from([("TOKEN".to_string(), "secret:token".to_string())]),
+            ),
+        ];
+        let secrets = resolve(&mut config, &mut layers, &store, "devspin.yml").unwrap();
+        let names: Vec<&str> = secrets.names().collect();
+        assert_eq!(names, ["pg", "token"]);
+
+        let resolved = interpolate::resolve(&config, &layers, "devspin.yml").unwrap();
+        let env = &resolved.content.env;
+        // A `${` in a secret is kept as written
+        assert_eq!(env["PG_PASSWORD"], "pa${ss}");
+        assert_eq!(env["URL"], "postgres://devspin:pa${ss}@db/app");
+        assert_eq!(env["NOT_A_REFERENCE"], "a secret:pg");
+        assert_eq!(env["TOKEN"], "t0k3n");
+        assert_eq!(
+            resolved.content.services[0].environment["POSTGRES_PASSWORD"],
+            "pa${ss}"
+        );
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let (_temp_dir, store) = store(&[]);
+        let mut missing = config(
+            "services:\n  - name: db\n    image: postgres\n    environment:\n      POSTGRES_PASSWORD: secret:pg\n",
+        );
+        let err = resolve(&mut missing, &mut [], &store, "devspin.yml")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with(
+                "devspin.yml: service `db` environment `POSTGRES_PASSWORD`: secret `pg` is not in "
+            ),
+            "{}",
+            err
+        );
+        assert!(
+            err.ends_with("add it with `devspin secret set pg`"),
+            "{}",
+            err
+        );
+
+        // A store that cannot be read is a store failure, not a config mistake
+        std::fs::write(store.location(), "not json").unwrap();
+        let err = resolve(&mut missing, &mut [], &store, "devspin.yml").unwrap_err();
+        assert!(matches!(err, DevSpinError::Process(_)), "{:?}", err);
+
+        // Nothing refers to the store, so it is not read
+        let mut plain = config("env:\n  A: b\n");
+        assert!(resolve(&mut plain, &mut [], &store, "devspin.yml")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_redact() {
+        let (_temp_dir, store) = store(&[("short", "abc"), ("long", "abcdef")]);
+        let mut config = config("env:\n  A: secret:short\n  B: secret:long\n");
+        let secrets = resolve(&mut config, &mut [], &store, "devspin.yml").unwrap();
+
+        assert_eq!(
+            secrets.redact("a=abc b=abcdef c=xyz"),
+            format!("a={} b={} c=xyz", REDACTED, REDACTED)
+        );
+        assert_eq!(secrets.find("x abcdef"), Some("short"));
+        assert_eq!(secrets.find("xyz"), None);
+        // Debug output names the secrets without their values
+        assert_eq!(format!("{:?}", secrets), "{\"short\", \"long\"}");
+    }
+
+    #[test]
+    fn test_withhold() {
+        let (_temp_dir, store) = store(&[("pg", "hunter2")]);
+        let mut config = config(
+            "env:\n  PG_PASSWORD: secret:pg\n  URL: postgres://u:${PG_PASSWORD}@db/app\n  MODE: dev\nservices:\n  - name: db\n    image: postgres\n    environment:\n      POSTGRES_PASSWORD: secret:pg\n",
+        );
+        let secrets = resolve(&mut config, &mut [], &store, "devspin.yml").unwrap();
+        let mut resolved = interpolate::resolve(&config, &[], "devspin.yml").unwrap();
+
+        secrets.withhold(&mut resolved).unwrap();
+        let env = &resolved.content.env;
+        assert_eq!(env["PG_PASSWORD"], "secret:pg");
+        assert_eq!(env["URL"], "secret:postgres://u:${PG_PASSWORD}@db/app");
+        assert_eq!(
+            template(&env["URL"]),
+            Some("postgres://u:${PG_PASSWORD}@db/app")
+        );
+        assert_eq!(template(&env["PG_PASSWORD"]), None);
+        assert_eq!(env["MODE"], "dev");
+        assert_eq!(
+            resolved.content.services[0].environment["POSTGRES_PASSWORD"],
+            "secret:pg"
+        );
+        assert_eq!(secrets.find(&resolved.write()), None);
+    }
+
+    #[test]
+    fn test_withhold_needs_a_variable_holding_the_secret() {
+        let (_temp_dir, store) = store(&[("pg", "hunter2")]);
+        let mut config = config(
+            "services:\n  - name: db\n    image: postgres\n    environment:\n      POSTGRES_PASSWORD: secret:pg\n",
+        );
+        let secrets = resolve(&mut config, &mut [], &store, "devspin.yml").unwrap();
+        let mut resolved = interpolate::resolve(&config, &[], "devspin.yml").unwrap();
+        resolved.content.services[0]
+            .environment
+            .insert("URL".to_string(), "postgres://u:hunter2@db $5".to_string());
+
+        let err = secrets.withhold(&mut resolved.clone()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("service `db` environment `URL` is built from secret `pg`"));
+
+        // With a variable holding it, other `$` are doubled as compose expects
+        resolved
+            .content
+            .env
+            .insert("PG".to_string(), "hunter2".to_string());
+        secrets.withhold(&mut resolved).unwrap();
+        assert_eq!(
+            resolved.content.services[0].environment["URL"],
+            "secret:postgres://u:${PG}@db $$5"
+        );
+    }
+}
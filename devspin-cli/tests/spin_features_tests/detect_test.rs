@@ -0,0 +1,147 @@
+use devspin_cli::spin_features::detect::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn touch(root: &Path, files: &[&str]) {
+        for file in files {
+            fs::write(root.join(file), "").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_detect_project_files() {
+        let cases = [
+            ("v.mod", ConfigType::VModule),
+            ("go.mod", ConfigType::GoModule),
+            ("Cargo.toml", ConfigType::RustCrate),
+            ("pyproject.toml", ConfigType::PythonProject),
+            ("requirements.txt", ConfigType::PythonProject),
+            ("tsconfig.json", ConfigType::TypescriptConfig),
+            ("package.json", ConfigType::PackageConfig),
+            ("package-lock.json", ConfigType::PackageConfig),
+        ];
+        for (name, expected) in cases {
+            let path = Path::new("/project").join(name);
+            assert_eq!(ConfigType::detect(&path), Some(expected), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_detect_other_files() {
+        assert_eq!(
+            ConfigType::detect(Path::new("app.config.json")),
+            Some(ConfigType::AppConfig)
+        );
+        assert_eq!(
+            ConfigType::detect(Path::new(".vscode/settings.json")),
+            Some(ConfigType::AppConfig)
+        );
+        assert_eq!(
+            ConfigType::detect(Path::new("data.json")),
+            Some(ConfigType::GenericJson)
+        );
+        assert_eq!(
+            ConfigType::detect(Path::new("devspin.yml")),
+            Some(ConfigType::Unknown("yml".to_string()))
+        );
+        // Extensions the tools know nothing about
+        assert_eq!(ConfigType::detect(Path::new("README.md")), None);
+        assert_eq!(ConfigType::detect(Path::new("Makefile")), None);
+    }
+
+    #[test]
+    fn test_config_type_language() {
+        assert_eq!(ConfigType::RustCrate.language(), Some(Language::Rust));
+        assert_eq!(
+            ConfigType::PackageConfig.language(),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            ConfigType::TypescriptConfig.language(),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(ConfigType::GenericJson.language(), Some(Language::Json));
+        assert_eq!(
+            ConfigType::Unknown("py".to_string()).language(),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            ConfigType::Unknown("yml".to_string()).language(),
+            Some(Language::Yaml)
+        );
+        assert_eq!(ConfigType::Unknown("txt".to_string()).language(), None);
+    }
+
+    #[test]
+    fn test_language_of_file() {
+        let cases = [
+            ("main.v", Some(Language::V)),
+            ("App.jsx", Some(Language::JavaScript)),
+            ("index.tsx", Some(Language::TypeScript)),
+            ("app.py", Some(Language::Python)),
+            ("main.go", Some(Language::Go)),
+            ("lib.rs", Some(Language::Rust)),
+            ("Cargo.toml", Some(Language::Toml)),
+            ("ci.yaml", Some(Language::Yaml)),
+            ("notes.txt", None),
+            ("Dockerfile", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(Language::of_file(Path::new(name)), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_project_languages_ignore_loose_files() {
+        let temp_dir = tempdir().unwrap();
+        touch(
+            temp_dir.path(),
+            &[
+                "Cargo.toml",
+                "package.json",
+                "devspin.yml",
+                "data.json",
+                "main.py",
+            ],
+        );
+
+        assert_eq!(
+            project_languages(temp_dir.path()),
+            vec![Language::Rust, Language::JavaScript]
+        );
+    }
+
+    #[test]
+    fn test_project_languages_count_each_language_once() {
+        let temp_dir = tempdir().unwrap();
+        touch(temp_dir.path(), &["pyproject.toml", "requirements.txt"]);
+
+        assert_eq!(project_languages(temp_dir.path()), vec![Language::Python]);
+    }
+
+    #[test]
+    fn test_dir_languages() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("nested.go")).unwrap();
+        touch(
+            temp_dir.path(),
+            &["package.json", "b.ts", "a.py", "notes.txt"],
+        );
+
+        // Project files first, then the files by name; directories are not files
+        assert_eq!(
+            dir_languages(temp_dir.path()),
+            vec![
+                Language::JavaScript,
+                Language::Python,
+                Language::TypeScript,
+                Language::Json
+            ]
+        );
+    }
+}
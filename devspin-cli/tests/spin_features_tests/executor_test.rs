@@ -0,0 +1,306 @@
+use devspin_cli::parser::devspin_generator::SpinFeature;
+use devspin_cli::spin_features::executor::*;
+use devspin_cli::spin_features::Language;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    fn feature(enabled: bool, modes: &[&str]) -> SpinFeature {
+        SpinFeature {
+            enabled,
+            modes: modes.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn command_line(
+        operation: Operation,
+        language: Language,
+        target: &Target,
+        check: bool,
+    ) -> Option<String> {
+        tool_command(operation, language, target, check).map(|c| c.to_string())
+    }
+
+    fn file(path: &str) -> Target {
+        Target::File(PathBuf::from(path))
+    }
+
+    #[test]
+    fn test_lint_commands() {
+        let cases = [
+            (Language::V, file("main.v"), "v vet main.v"),
+            (
+                Language::Rust,
+                file("src/lib.rs"),
+                "cargo clippy -- -D warnings",
+            ),
+            (Language::Go, file("main.go"), "go vet main.go"),
+            (Language::Go, Target::Project, "go vet ./..."),
+            (
+                Language::Go,
+                Target::Dir(PathBuf::from("cmd/api")),
+                "go vet ./cmd/api/...",
+            ),
+            (
+                Language::JavaScript,
+                file("src/app.js"),
+                "eslint src/app.js",
+            ),
+            (Language::TypeScript, Target::Project, "eslint ."),
+            (Language::Python, file("app.py"), "pylint app.py"),
+            (Language::Python, Target::Project, "pylint --recursive=y ."),
+        ];
+        for (language, target, expected) in cases {
+            assert_eq!(
+                command_line(Operation::Lint, language, &target, false).as_deref(),
+                Some(expected)
+            );
+        }
+        for language in [Language::Json, Language::Toml, Language::Yaml] {
+            assert_eq!(
+                command_line(Operation::Lint, language, &Target::Project, false),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_commands() {
+        let cases = [
+            (
+                Language::V,
+                Target::Project,
+                "v fmt -verify .",
+                "v fmt -w .",
+            ),
+            (
+                Language::Rust,
+                file("src/lib.rs"),
+                "rustfmt --check src/lib.rs",
+                "rustfmt src/lib.rs",
+            ),
+            (
+                Language::Rust,
+                Target::Project,
+                "cargo fmt -- --check",
+                "cargo fmt",
+            ),
+            (
+                Language::TypeScript,
+                file("a.ts"),
+                "prettier --check a.ts",
+                "prettier --write a.ts",
+            ),
+            (
+                Language::Yaml,
+                file("ci.yml"),
+                "prettier --check ci.yml",
+                "prettier --write ci.yml",
+            ),
+            (
+                Language::Python,
+                Target::Project,
+                "black --check --diff .",
+                "black .",
+            ),
+            (
+                Language::Go,
+                file("main.go"),
+                "gofmt -l main.go",
+                "gofmt -w main.go",
+            ),
+        ];
+        for (language, target, check, write) in cases {
+            assert_eq!(
+                command_line(Operation::Format, language, &target, true).as_deref(),
+                Some(check)
+            );
+            assert_eq!(
+                command_line(Operation::Format, language, &target, false).as_deref(),
+                Some(write)
+            );
+        }
+        assert_eq!(
+            command_line(Operation::Format, Language::Toml, &file("Cargo.toml"), true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_gofmt_check_lists_files() {
+        let check = tool_command(Operation::Format, Language::Go, &Target::Project, true).unwrap();
+        let write = tool_command(Operation::Format, Language::Go, &Target::Project, false).unwrap();
+        assert!(check.lists_files);
+        assert!(!write.lists_files);
+    }
+
+    #[test]
+    fn test_target_modes() {
+        assert_eq!(file("a.rs").mode(), "currFile");
+        assert_eq!(Target::Dir(PathBuf::from("src")).mode(), "currDir");
+        assert_eq!(Target::Project.mode(), "all");
+
+        let all_only = feature(true, &["all"]);
+        assert!(Target::Project.allowed_by(&all_only));
+        assert!(!file("a.rs").allowed_by(&all_only));
+        // No modes allow every target, a disabled feature none
+        assert!(file("a.rs").allowed_by(&feature(true, &[])));
+        assert!(!Target::Project.allowed_by(&feature(false, &["all"])));
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+
+        assert_eq!(Target::resolve(None, root).unwrap(), Target::Project);
+        assert_eq!(
+            Target::resolve(Some(&root.join("src/main.rs")), root).unwrap(),
+            file("src/main.rs")
+        );
+        assert_eq!(
+            Target::resolve(Some(&root.join("src")), root).unwrap(),
+            Target::Dir(PathBuf::from("src"))
+        );
+        assert_eq!(
+            Target::resolve(Some(root), root).unwrap(),
+            Target::Dir(PathBuf::from("."))
+        );
+    }
+
+    #[test]
+    fn test_resolve_target_errors() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().join("project");
+        fs::create_dir(&root).unwrap();
+        fs::write(temp_dir.path().join("outside.rs"), "").unwrap();
+
+        let missing = Target::resolve(Some(&root.join("missing.rs")), &root).unwrap_err();
+        assert!(missing.to_string().contains("Cannot read"));
+        let outside =
+            Target::resolve(Some(&temp_dir.path().join("outside.rs")), &root).unwrap_err();
+        assert!(outside.to_string().contains("outside the project root"));
+    }
+
+    #[test]
+    fn test_plan_project() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        for name in ["Cargo.toml", "package.json", "tsconfig.json", "devspin.yml"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+
+        let commands: Vec<String> = plan(Operation::Format, &Target::Project, root, true)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        // JavaScript and TypeScript share one prettier run
+        assert_eq!(commands, vec!["cargo fmt -- --check", "prettier --check ."]);
+    }
+
+    #[test]
+    fn test_plan_rust_dir_formats_its_files_only() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        fs::create_dir_all(root.join("src/parser")).unwrap();
+        fs::create_dir_all(root.join("src/target")).unwrap();
+        fs::create_dir_all(root.join("tests")).unwrap();
+        for file in [
+            "src/lib.rs",
+            "src/parser/mod.rs",
+            "src/target/generated.rs",
+            "tests/a.rs",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let src = Target::Dir(PathBuf::from("src"));
+        assert_eq!(
+            command_line(Operation::Format, Language::Rust, &src, true),
+            None
+        );
+        let commands: Vec<String> = plan(Operation::Format, &src, root, true)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(commands, ["rustfmt --check src/lib.rs src/parser/mod.rs"]);
+
+        let commands: Vec<String> = plan(
+            Operation::Format,
+            &Target::Dir(PathBuf::from(".")),
+            root,
+            false,
+        )
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+        assert_eq!(
+            commands,
+            ["rustfmt src/lib.rs src/parser/mod.rs tests/a.rs"]
+        );
+    }
+
+    #[test]
+    fn test_plan_without_tool() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+
+        let unknown = plan(Operation::Lint, &file("README.md"), root, false).unwrap_err();
+        assert!(unknown
+            .to_string()
+            .contains("No linter for README.md (no supported language found)"));
+
+        let no_linter = plan(Operation::Lint, &file("data.json"), root, false).unwrap_err();
+        assert!(no_linter
+            .to_string()
+            .contains("No linter for data.json (found JSON)"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_exit_codes() {
+        let root = Path::new(".");
+        let command = |program: &str, args: &[&str]| ToolCommand {
+            language: Language::Go,
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            lists_files: false,
+        };
+
+        assert_eq!(run(&command("true", &[]), root).await.unwrap(), 0);
+        assert_eq!(
+            run(&command("sh", &["-c", "exit 3"]), root).await.unwrap(),
+            3
+        );
+
+        // A listing tool fails when it lists something
+        let listing = ToolCommand {
+            lists_files: true,
+            ..command("echo", &["main.go"])
+        };
+        assert_eq!(run(&listing, root).await.unwrap(), 1);
+        let empty = ToolCommand {
+            lists_files: true,
+            ..command("true", &[])
+        };
+        assert_eq!(run(&empty, root).await.unwrap(), 0);
+
+        let missing = run(&command("devspin-missing-tool", &[]), root)
+            .await
+            .unwrap_err();
+        assert_eq!(missing.exit_code(), 127);
+        assert!(missing
+            .to_string()
+            .contains("is not installed or not in PATH"));
+    }
+}
@@ -0,0 +1,116 @@
+use devspin_cli::parser::devspin_generator::Task;
+use devspin_cli::tasks::fingerprint::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn task(command: &str, inputs: &[&str], outputs: &[&str]) -> Task {
+        Task {
+            name: "build".to_string(),
+            command: command.to_string(),
+            depends_on: Vec::new(),
+            inputs: inputs.iter().map(|input| input.to_string()).collect(),
+            outputs: outputs.iter().map(|output| output.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("src/*.rs", "src/main.rs"));
+        assert!(!glob_match("src/*.rs", "src/cli/run.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/cli/run.rs"));
+        assert!(glob_match("**/Cargo.toml", "Cargo.toml"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file10.txt"));
+    }
+
+    #[test]
+    fn test_matching_files_skips_junk_directories() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/cli")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/cli/run.rs"), "").unwrap();
+        fs::write(root.join("src/notes.md"), "").unwrap();
+        fs::write(root.join("target/debug/build.rs"), "").unwrap();
+
+        let files = matching_files(root, &["**/*.rs".to_string()]);
+
+        assert_eq!(files, ["src/cli/run.rs", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_inputs_command_and_env() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("input.txt"), "one").unwrap();
+        let build = task("cat input.txt", &["*.txt"], &[]);
+        let env = IndexMap::new();
+
+        let first = fingerprint(&build, root, &env).unwrap();
+        assert!(matches!(first, Fingerprint::Hash(_)), "{:?}", first);
+        assert_eq!(fingerprint(&build, root, &env).unwrap(), first);
+
+        let other_command = task("wc input.txt", &["*.txt"], &[]);
+        assert_ne!(fingerprint(&other_command, root, &env).unwrap(), first);
+
+        let other_env = IndexMap::from([("MODE".to_string(), "release".to_string())]);
+        assert_ne!(fingerprint(&build, root, &other_env).unwrap(), first);
+
+        fs::write(root.join("input.txt"), "two").unwrap();
+        assert_ne!(fingerprint(&build, root, &env).unwrap(), first);
+    }
+
+    #[test]
+    fn test_task_without_inputs_has_no_fingerprint() {
+        let temp_dir = tempdir().unwrap();
+        let fingerprint = fingerprint(&task("echo", &[], &[]), temp_dir.path(), &IndexMap::new());
+
+        assert_eq!(fingerprint.unwrap(), Fingerprint::NoInputs);
+    }
+
+    #[test]
+    fn test_inputs_matching_no_file_have_no_fingerprint() {
+        let temp_dir = tempdir().unwrap();
+        let build = task("make", &["src/**/*.rs"], &[]);
+
+        let fingerprint = fingerprint(&build, temp_dir.path(), &IndexMap::new());
+
+        assert_eq!(fingerprint.unwrap(), Fingerprint::NoMatch);
+    }
+
+    #[test]
+    fn test_outputs_exist() {
+        let temp_dir = tempdir().unwrap();
+        let build = task("echo", &["*.txt"], &["out/app"]);
+        assert!(!outputs_exist(&build, temp_dir.path()));
+
+        fs::create_dir(temp_dir.path().join("out")).unwrap();
+        fs::write(temp_dir.path().join("out/app"), "").unwrap();
+        assert!(outputs_exist(&build, temp_dir.path()));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let mut cache = FingerprintCache::load(temp_dir.path());
+        assert_eq!(cache.get("build"), None);
+
+        cache.insert("build", "abc123".to_string());
+        cache.save().unwrap();
+
+        let reloaded = FingerprintCache::load(temp_dir.path());
+        assert_eq!(reloaded.get("build"), Some("abc123"));
+        assert!(temp_dir
+            .path()
+            .join(CACHE_DIR)
+            .join("fingerprints")
+            .exists());
+    }
+}
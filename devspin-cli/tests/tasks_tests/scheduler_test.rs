@@ -0,0 +1,189 @@
+use devspin_cli::parser::devspin_generator::Task;
+use devspin_cli::tasks::scheduler::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn task(name: &str, command: &str, depends_on: &[&str]) -> Task {
+        Task {
+            name: name.to_string(),
+            command: command.to_string(),
+            depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    fn targets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn log_lines(root: &Path) -> Vec<String> {
+        fs::read_to_string(root.join("log.txt"))
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_runs_dependencies_in_order() {
+        let temp_dir = tempdir().unwrap();
+        let tasks = vec![
+            task("test", "echo test>> log.txt", &["build"]),
+            task("build", "echo build>> log.txt", &["fetch"]),
+            task("fetch", "echo fetch>> log.txt", &[]),
+        ];
+        let scheduler = Scheduler::new(tasks, temp_dir.path().to_path_buf(), IndexMap::new());
+
+        let summary = scheduler.run(&targets(&["test"])).await.unwrap();
+
+        assert!(summary.success());
+        assert_eq!(log_lines(temp_dir.path()), ["fetch", "build", "test"]);
+        let names: Vec<&str> = summary.reports.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["fetch", "build", "test"]);
+    }
+
+    #[tokio::test]
+    async fn test_failure_cancels_dependents_only() {
+        let temp_dir = tempdir().unwrap();
+        let tasks = vec![
+            task("broken", "exit 4", &[]),
+            task("after-broken", "echo after>> log.txt", &["broken"]),
+            task("independent", "echo independent>> log.txt", &[]),
+        ];
+        let scheduler =
+            Scheduler::new(tasks, temp_dir.path().to_path_buf(), IndexMap::new()).with_jobs(1);
+
+        let summary = scheduler
+            .run(&targets(&["after-broken", "independent"]))
+            .await
+            .unwrap();
+
+        let statuses: Vec<TaskStatus> = summary.reports.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                TaskStatus::Failed(4),
+                TaskStatus::Cancelled,
+                TaskStatus::Succeeded
+            ]
+        );
+        assert_eq!(summary.first_failure(), Some(("broken", 4)));
+        assert_eq!(log_lines(temp_dir.path()), ["independent"]);
+        assert!(summary.reports[1].duration.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_task_is_skipped() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(root.join("input.txt"), "one").unwrap();
+        let mut build = task("build", "echo build>> log.txt && echo built> out.txt", &[]);
+        build.inputs = vec!["input.txt".to_string()];
+        build.outputs = vec!["out.txt".to_string()];
+        let tasks = vec![build, task("test", "echo test>> log.txt", &["build"])];
+        let scheduler = Scheduler::new(tasks, root.clone(), IndexMap::new());
+
+        scheduler.run(&targets(&["test"])).await.unwrap();
+        let summary = scheduler.run(&targets(&["test"])).await.unwrap();
+
+        assert!(summary.success());
+        assert_eq!(summary.reports[0].status, TaskStatus::UpToDate);
+        assert_eq!(summary.reports[1].status, TaskStatus::Succeeded);
+        assert_eq!(log_lines(&root), ["build", "test", "test"]);
+
+        // Removed outputs or changed inputs make the task run again.
+        fs::remove_file(root.join("out.txt")).unwrap();
+        scheduler.run(&targets(&["build"])).await.unwrap();
+        fs::write(root.join("input.txt"), "two").unwrap();
+        scheduler.run(&targets(&["build"])).await.unwrap();
+        let forced = scheduler.with_force(true);
+        forced.run(&targets(&["build"])).await.unwrap();
+        assert_eq!(
+            log_lines(&root),
+            ["build", "test", "test", "build", "build", "build"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_whose_inputs_match_nothing_always_runs() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        let mut build = task("build", "echo build>> log.txt", &[]);
+        build.inputs = vec!["missing/*.txt".to_string()];
+        let scheduler = Scheduler::new(vec![build], root.clone(), IndexMap::new());
+
+        scheduler.run(&targets(&["build"])).await.unwrap();
+        let summary = scheduler.run(&targets(&["build"])).await.unwrap();
+
+        assert_eq!(summary.reports[0].status, TaskStatus::Succeeded);
+        assert_eq!(log_lines(&root), ["build", "build"]);
+    }
+
+    #[tokio::test]
+    async fn test_cycle_is_rejected_before_running() {
+        let temp_dir = tempdir().unwrap();
+        let tasks = vec![
+            task("a", "echo a>> log.txt", &["b"]),
+            task("b", "echo b>> log.txt", &["a"]),
+        ];
+        let scheduler = Scheduler::new(tasks, temp_dir.path().to_path_buf(), IndexMap::new());
+
+        let err = scheduler.run(&targets(&["a"])).await.unwrap_err();
+
+        assert!(err.to_string().contains("dependency cycle: a -> b -> a"));
+        assert!(log_lines(temp_dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_independent_tasks_run_concurrently() {
+        let temp_dir = tempdir().unwrap();
+        let tasks = vec![
+            task("one", "sleep 0.4", &[]),
+            task("two", "sleep 0.4", &[]),
+            task("both", "true", &["one", "two"]),
+        ];
+        let root = temp_dir.path().to_path_buf();
+
+        let parallel = Scheduler::new(tasks.clone(), root.clone(), IndexMap::new()).with_jobs(2);
+        let started = std::time::Instant::now();
+        assert!(parallel.run(&targets(&["both"])).await.unwrap().success());
+        assert!(started.elapsed().as_secs_f64() < 0.75);
+
+        let serial = Scheduler::new(tasks, root, IndexMap::new()).with_jobs(1);
+        let started = std::time::Instant::now();
+        assert!(serial.run(&targets(&["both"])).await.unwrap().success());
+        assert!(started.elapsed().as_secs_f64() >= 0.8);
+    }
+
+    #[test]
+    fn test_summary_table_lists_every_task() {
+        let summary = RunSummary {
+            reports: vec![
+                TaskReport {
+                    name: "build".to_string(),
+                    status: TaskStatus::Succeeded,
+                    duration: Some(std::time::Duration::from_millis(1500)),
+                },
+                TaskReport {
+                    name: "deploy".to_string(),
+                    status: TaskStatus::Cancelled,
+                    duration: None,
+                },
+            ],
+        };
+
+        colored::control::set_override(false);
+        let table = summary.table();
+        assert!(table.starts_with("TASK    STATUS       DURATION\n"));
+        assert!(table.contains("build   ok           1.50s\n"));
+        assert!(table.contains("deploy  cancelled    -\n"));
+    }
+}
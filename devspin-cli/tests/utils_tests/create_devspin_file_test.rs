@@ -79,4 +79,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_write_cfg_file_content() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let temp_path = temp_dir.path().to_str().unwrap();
+
+        let created_path = write_cfg_file(temp_path, "version: 1.0\n")?;
+
+        assert_eq!(created_path, temp_dir.path().join("devspin.yml"));
+        assert_eq!(fs::read_to_string(&created_path)?, "version: 1.0\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_cfg_file() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("devspin.yml");
+        fs::write(&path, "name: old\n")?;
+
+        let backup = backup_cfg_file(&path)?;
+
+        assert_eq!(backup, temp_dir.path().join("devspin.yml.bak"));
+        assert_eq!(fs::read_to_string(&backup)?, "name: old\n");
+        assert!(path.exists());
+
+        Ok(())
+    }
 }
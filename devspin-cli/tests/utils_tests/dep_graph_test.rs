@@ -0,0 +1,89 @@
+use devspin_cli::utils::dep_graph::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn graph(edges: &[(&str, &[&str])]) -> IndexMap<String, Vec<String>> {
+        edges
+            .iter()
+            .map(|(node, deps)| {
+                (
+                    node.to_string(),
+                    deps.iter().map(|dep| dep.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn names(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_dependencies_come_first() {
+        let graph = graph(&[("test", &["build"]), ("build", &["fetch"]), ("fetch", &[])]);
+
+        let order = dependency_order(&graph, &names(&["test"])).unwrap();
+        assert_eq!(order, names(&["fetch", "build", "test"]));
+    }
+
+    #[test]
+    fn test_only_required_nodes_are_planned() {
+        let graph = graph(&[("build", &[]), ("lint", &[]), ("test", &["build"])]);
+
+        let order = dependency_order(&graph, &names(&["test"])).unwrap();
+        assert_eq!(order, names(&["build", "test"]));
+    }
+
+    #[test]
+    fn test_empty_targets_orders_whole_graph() {
+        let graph = graph(&[("test", &["build"]), ("build", &[]), ("lint", &[])]);
+
+        let order = dependency_order(&graph, &[]).unwrap();
+        assert_eq!(order, names(&["build", "test", "lint"]));
+    }
+
+    #[test]
+    fn test_shared_dependency_is_planned_once() {
+        let graph = graph(&[("build", &[]), ("test", &["build"]), ("bench", &["build"])]);
+
+        let order = dependency_order(&graph, &names(&["test", "bench"])).unwrap();
+        assert_eq!(order, names(&["build", "test", "bench"]));
+    }
+
+    #[test]
+    fn test_cycle_reports_path() {
+        let graph = graph(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+
+        let err = dependency_order(&graph, &names(&["a"])).unwrap_err();
+        assert_eq!(err, GraphError::Cycle(names(&["a", "b", "c", "a"])));
+        assert_eq!(err.to_string(), "dependency cycle: a -> b -> c -> a");
+    }
+
+    #[test]
+    fn test_self_dependency_is_a_cycle() {
+        let graph = graph(&[("a", &["a"])]);
+
+        let err = dependency_order(&graph, &[]).unwrap_err();
+        assert_eq!(err, GraphError::Cycle(names(&["a", "a"])));
+    }
+
+    #[test]
+    fn test_unknown_dependency_and_target() {
+        let graph = graph(&[("a", &["missing"])]);
+
+        assert_eq!(
+            dependency_order(&graph, &[]).unwrap_err(),
+            GraphError::UnknownDependency {
+                node: "a".to_string(),
+                dependency: "missing".to_string(),
+            }
+        );
+        assert_eq!(
+            dependency_order(&graph, &names(&["nope"])).unwrap_err(),
+            GraphError::UnknownNode("nope".to_string())
+        );
+    }
+}
@@ -2,7 +2,7 @@ use devspin_cli::utils::devspin_finder::*;
 
 #[cfg(test)]
 /// Tests for devspin_finder utilities that search for devspin config files
-/// Files tested: devspin.yml, devspin.yaml, .devspin.yml
+/// Files tested: devspin.yml, devspin.yaml, .devspin.yml, devspin.json, devspin.toml
 mod tests {
     use super::*;
     use std::fs::{self, File};
@@ -35,9 +35,7 @@ mod tests {
 
     #[test]
     fn test_finds_all_file_variants() {
-        let file_variants = ["devspin.yml", "devspin.yaml", ".devspin.yml"];
-
-        for variant in file_variants.iter() {
+        for variant in CONFIG_FILE_NAMES.iter() {
             let temp_dir = tempdir().unwrap();
             let config_path = temp_dir.path().join(variant);
             File::create(&config_path).unwrap();
@@ -48,6 +46,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prefers_yaml_in_root() {
+        let temp_dir = tempdir().unwrap();
+        File::create(temp_dir.path().join("devspin.json")).unwrap();
+        File::create(temp_dir.path().join("devspin.yml")).unwrap();
+
+        let result = find_devspin_yml_parallel(temp_dir.path().to_str().unwrap());
+        assert_eq!(result.unwrap(), temp_dir.path().join("devspin.yml"));
+    }
+
     #[test]
     fn test_skips_common_directories() {
         let skip_dirs = ["node_modules", "target", ".git", ".vscode"];
@@ -100,4 +108,25 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), config_path);
     }
+
+    #[test]
+    fn test_finds_config_upward() {
+        let temp_dir = tempdir().unwrap();
+        let nested = temp_dir.path().join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        let config_path = temp_dir.path().join("devspin.toml");
+        File::create(&config_path).unwrap();
+
+        assert_eq!(find_devspin_yml_upward(&nested), Some(config_path.clone()));
+        assert_eq!(find_devspin_yml_upward(temp_dir.path()), Some(config_path));
+
+        // The closest config wins, the tree below is never searched
+        let inner = temp_dir.path().join("src/devspin.yml");
+        File::create(&inner).unwrap();
+        assert_eq!(find_devspin_yml_upward(&nested), Some(inner));
+        let other = tempdir().unwrap();
+        fs::create_dir(other.path().join("child")).unwrap();
+        File::create(other.path().join("child/devspin.yml")).unwrap();
+        assert_eq!(find_devspin_yml_upward(other.path()), None);
+    }
 }
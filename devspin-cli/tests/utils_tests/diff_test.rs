@@ -0,0 +1,57 @@
+use devspin_cli::utils::diff::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines_keeps_common_lines() {
+        let lines = diff_lines("a\nb\nc\n", "a\nc\nd\n");
+        assert_eq!(
+            lines,
+            [
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unified_identical_is_empty() {
+        assert_eq!(unified("a\nb\n", "a\nb", "devspin.yml"), "");
+    }
+
+    #[test]
+    fn test_unified_hunk_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let new = "1\n2\n3\n4\n5\nsix\n7\n8\n9\n";
+
+        assert_eq!(
+            unified(old, new, "devspin.yml"),
+            "--- a/devspin.yml\n+++ b/devspin.yml\n@@ -3,7 +3,7 @@\n 3\n 4\n 5\n-6\n+six\n 7\n 8\n 9\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_splits_distant_changes() {
+        let old: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        let new = old
+            .replace("line 2\n", "two\n")
+            .replace("line 19\n", "nineteen\n");
+
+        let diff = unified(&old, &new, "f");
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n line 1\n-line 2\n+two\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n line 16\n"));
+    }
+
+    #[test]
+    fn test_unified_from_empty() {
+        assert_eq!(
+            unified("", "a\nb\n", "new.yml"),
+            "--- a/new.yml\n+++ b/new.yml\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use devspin_cli::error::DevSpinError;
+use devspin_cli::utils::dotenv::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse() {
+        let vars = parse(
+            "# database\nDB_HOST=localhost\nexport DB_PORT = 5432\n\nURL=http://x # trailing comment\nHASH=a#b\nSINGLE='no \\n escapes ${HERE}'\nDOUBLE=\"line\\nnext \\\"quoted\\\"\"\nEMPTY=\n",
+            ".env",
+        )
+        .unwrap();
+        let pairs: Vec<(&str, &str)> = vars
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("DB_HOST", "localhost"),
+                ("DB_PORT", "5432"),
+                ("URL", "http://x"),
+                ("HASH", "a#b"),
+                ("SINGLE", "no \\n escapes ${HERE}"),
+                ("DOUBLE", "line\nnext \"quoted\""),
+                ("EMPTY", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("A=1\nnot an assignment\n", ".env").unwrap_err();
+        assert!(matches!(
+            err,
+            DevSpinError::Parse {
+                line: 2,
+                column: 1,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("expected `KEY=VALUE`"));
+
+        let err = parse("export 1A=x\n", ".env").unwrap_err();
+        assert!(matches!(err, DevSpinError::Parse { column: 8, .. }));
+
+        let err = parse("A=\"open\n", ".env").unwrap_err();
+        assert!(matches!(err, DevSpinError::Parse { column: 3, .. }));
+        assert!(err.to_string().contains("unterminated \" quote"));
+    }
+
+    #[test]
+    fn test_load() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join(".env");
+        assert!(load(&path).unwrap().is_empty());
+
+        fs::write(&path, "TOKEN=abc\n").unwrap();
+        assert_eq!(load(&path).unwrap()["TOKEN"], "abc");
+    }
+}
@@ -0,0 +1,82 @@
+use devspin_cli::utils::dotenv;
+use devspin_cli::utils::export::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn vars() -> IndexMap<String, String> {
+        IndexMap::from([
+            ("PLAIN".to_string(), "postgres://db:5432/app".to_string()),
+            ("SPACED".to_string(), " it's # not a comment ".to_string()),
+            ("ESCAPED".to_string(), "a\\b \"c\"\nd\te".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ])
+    }
+
+    #[test]
+    fn test_render_sh() {
+        let rendered = render(&vars(), ExportFormat::Sh).unwrap();
+
+        assert!(rendered.starts_with("export PLAIN='postgres://db:5432/app'\n"));
+        assert!(rendered.contains("export SPACED=' it'\\''s # not a comment '\n"));
+        assert!(rendered.ends_with("export EMPTY=''\n"));
+    }
+
+    #[test]
+    fn test_render_dotenv_reads_back() {
+        let rendered = render(&vars(), ExportFormat::Dotenv).unwrap();
+
+        assert!(rendered.starts_with("PLAIN=postgres://db:5432/app\n"));
+        assert_eq!(dotenv::parse(&rendered, ".env").unwrap(), vars());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let rendered = render(&vars(), ExportFormat::Json).unwrap();
+
+        let parsed: IndexMap<String, String> = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed, vars());
+    }
+
+    #[test]
+    fn test_render_fish_and_powershell() {
+        let vars = IndexMap::from([("NAME".to_string(), "it's".to_string())]);
+
+        assert_eq!(
+            render(&vars, ExportFormat::Fish).unwrap(),
+            "set -gx NAME 'it\\'s'\n"
+        );
+        assert_eq!(
+            render(&vars, ExportFormat::Powershell).unwrap(),
+            "$env:NAME = 'it''s'\n"
+        );
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_names() {
+        let vars = IndexMap::from([("X=1; touch /tmp/pwned; Y".to_string(), "value".to_string())]);
+
+        for format in [
+            ExportFormat::Sh,
+            ExportFormat::Fish,
+            ExportFormat::Powershell,
+            ExportFormat::Dotenv,
+            ExportFormat::Json,
+        ] {
+            let error = render(&vars, format).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("invalid environment variable name `X=1; touch /tmp/pwned; Y`"));
+        }
+    }
+
+    #[test]
+    fn test_render_unset_skips_invalid_names() {
+        assert_eq!(
+            render_unset(["OLD", "A; rm -rf ~"], ExportFormat::Sh),
+            "unset OLD\n"
+        );
+    }
+}
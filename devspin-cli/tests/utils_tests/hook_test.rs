@@ -0,0 +1,212 @@
+use devspin_cli::utils::export::ExportFormat;
+use devspin_cli::utils::hook::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    fn entry(config: &str, vars: &[(&str, &str)]) -> CacheEntry {
+        CacheEntry {
+            config: PathBuf::from(config),
+            sources: IndexMap::from([(PathBuf::from(config), Some(1))]),
+            vars: vars
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            withheld: Vec::new(),
+            invalid: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_state_from_vars() {
+        let vars = IndexMap::from([
+            (ROOT_VAR, "/work/shop"),
+            (STAMP_VAR, "abc"),
+            (BACKUP_VAR, r#"{"PORT":"80","DB_HOST":null}"#),
+        ]);
+        let state = HookState::from_vars(|key| vars.get(key).map(|v| v.to_string()));
+
+        assert_eq!(state.root.as_deref(), Some(Path::new("/work/shop")));
+        assert_eq!(state.stamp, "abc");
+        assert_eq!(
+            state.backup,
+            IndexMap::from([
+                ("PORT".to_string(), Some("80".to_string())),
+                ("DB_HOST".to_string(), None),
+            ])
+        );
+
+        let damaged = HookState::from_vars(|key| (key == BACKUP_VAR).then(|| "{".to_string()));
+        assert_eq!(damaged, HookState::default());
+
+        // The backup is restored by the shell, names that are not variables are dropped
+        let injected = HookState::from_vars(|key| {
+            (key == BACKUP_VAR).then(|| r#"{"PORT":"80","A; touch pwned":null}"#.to_string())
+        });
+        assert_eq!(
+            injected.backup,
+            IndexMap::from([("PORT".to_string(), Some("80".to_string()))])
+        );
+    }
+
+    #[test]
+    fn test_load_backs_up_the_shell_values() {
+        let state = HookState::default();
+        let entry = entry("/work/shop/devspin.yml", &[("PORT", "3000"), ("NEW", "x")]);
+
+        let script = state
+            .load(&entry, ExportFormat::Sh, |key| {
+                (key == "PORT").then(|| "80".to_string())
+            })
+            .unwrap();
+
+        assert!(script.starts_with("export PORT='3000'\nexport NEW='x'\n"));
+        assert!(script.contains("export DEVSPIN_HOOK_ROOT='/work/shop'\n"));
+        assert!(script.contains(&format!("export DEVSPIN_HOOK_STAMP='{}'\n", entry.stamp())));
+        assert!(script.contains(r#"export DEVSPIN_HOOK_BACKUP='{"PORT":"80","NEW":null}'"#));
+    }
+
+    #[test]
+    fn test_switching_projects_restores_the_previous_one() {
+        let state = HookState {
+            root: Some(PathBuf::from("/work/shop")),
+            stamp: "abc".to_string(),
+            backup: IndexMap::from([
+                ("PORT".to_string(), Some("80".to_string())),
+                ("SHOP_ONLY".to_string(), None),
+            ]),
+        };
+        let blog = entry("/work/blog/devspin.yml", &[("PORT", "4000")]);
+
+        // The shell still has the values of the previous project
+        let script = state
+            .load(&blog, ExportFormat::Fish, |key| {
+                (key == "PORT").then(|| "3000".to_string())
+            })
+            .unwrap();
+
+        assert!(script.starts_with("set -e SHOP_ONLY\nset -gx PORT '4000'\n"));
+        assert!(script.contains(r#"set -gx DEVSPIN_HOOK_BACKUP '{"PORT":"80"}'"#));
+    }
+
+    #[test]
+    fn test_unload() {
+        let state = HookState {
+            root: Some(PathBuf::from("/work/shop")),
+            stamp: "abc".to_string(),
+            backup: IndexMap::from([
+                ("PORT".to_string(), Some("80".to_string())),
+                ("NEW".to_string(), None),
+            ]),
+        };
+
+        assert_eq!(
+            state.unload(ExportFormat::Sh).unwrap(),
+            "unset NEW\nexport PORT='80'\nunset DEVSPIN_HOOK_ROOT\nunset DEVSPIN_HOOK_STAMP\nunset DEVSPIN_HOOK_BACKUP\n"
+        );
+    }
+
+    #[test]
+    fn test_cache_entry_follows_modification_times() {
+        let temp_dir = tempdir().unwrap();
+        let config = temp_dir.path().join("devspin.yml");
+        fs::write(&config, "name: shop\n").unwrap();
+        let dotenv = temp_dir.path().join(".env");
+
+        let entry = CacheEntry::new(&config, [config.clone(), dotenv.clone()]);
+        assert!(entry.is_fresh());
+        assert_eq!(entry.sources[&dotenv], None);
+        let stamp = entry.stamp();
+        assert_eq!(stamp.len(), 16);
+
+        // A source appearing makes the entry stale
+        fs::write(&dotenv, "PORT=1\n").unwrap();
+        assert!(!entry.is_fresh());
+        assert_ne!(
+            CacheEntry::new(&config, [config.clone(), dotenv]).stamp(),
+            stamp
+        );
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let path = cache_path(
+            &temp_dir.path().join("hook"),
+            Path::new("/work/shop/devspin.yml"),
+        );
+        assert_eq!(
+            path,
+            cache_path(
+                &temp_dir.path().join("hook"),
+                Path::new("/work/shop/devspin.yml")
+            )
+        );
+        assert_ne!(
+            path,
+            cache_path(
+                &temp_dir.path().join("hook"),
+                Path::new("/work/blog/devspin.yml")
+            )
+        );
+
+        let entry = CacheEntry {
+            withheld: vec!["PG_PASSWORD".to_string()],
+            ..entry("/work/shop/devspin.yml", &[("PORT", "3000")])
+        };
+        write_cache(&path, &entry).unwrap();
+        assert_eq!(read_cache(&path), Some(entry.clone()));
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(read_cache(&path), None);
+
+        let mut injected = entry.clone();
+        injected
+            .vars
+            .insert("A; touch pwned".to_string(), "x".to_string());
+        write_cache(&path, &injected).unwrap();
+        assert_eq!(read_cache(&path), None);
+    }
+
+    #[test]
+    fn test_allow_follows_content() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path().join("allow");
+        let config = temp_dir.path().join("devspin.yml");
+        fs::write(&config, "name: shop\n").unwrap();
+
+        assert!(!is_allowed(&dir, &config));
+        allow(&dir, &config).unwrap();
+        assert!(is_allowed(&dir, &config));
+
+        // Any change to the config needs a new `devspin allow`
+        fs::write(&config, "name: shop\nenv:\n  A: b\n").unwrap();
+        assert!(!is_allowed(&dir, &config));
+        allow(&dir, &config).unwrap();
+        assert!(is_allowed(&dir, &config));
+
+        revoke(&dir, &config).unwrap();
+        assert!(!is_allowed(&dir, &config));
+        revoke(&dir, &config).unwrap();
+    }
+
+    #[test]
+    fn test_scripts_call_apply() {
+        let devspin = Path::new("/opt/dev spin/devspin");
+
+        let bash = HookShell::Bash.script(devspin);
+        assert!(bash.contains(r#"eval "$('/opt/dev spin/devspin' hook bash --apply)""#));
+        assert!(bash.contains("PROMPT_COMMAND=\"_devspin_hook"));
+        let zsh = HookShell::Zsh.script(devspin);
+        assert!(zsh.contains("precmd_functions=(_devspin_hook $precmd_functions)"));
+        assert!(zsh.contains("chpwd_functions=(_devspin_hook $chpwd_functions)"));
+        let fish = HookShell::Fish.script(devspin);
+        assert!(fish.contains("--on-variable PWD"));
+        assert!(fish.contains("'/opt/dev spin/devspin' hook fish --apply | source"));
+    }
+}
@@ -0,0 +1,95 @@
+use devspin_cli::utils::shell::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(ShellKind::detect("/bin/bash"), ShellKind::Bash);
+        assert_eq!(ShellKind::detect("/usr/local/bin/zsh"), ShellKind::Zsh);
+        assert_eq!(ShellKind::detect("fish"), ShellKind::Fish);
+        assert_eq!(
+            ShellKind::detect("C:\\Program Files\\PowerShell\\7\\pwsh.exe"),
+            ShellKind::PowerShell
+        );
+        assert_eq!(ShellKind::detect("cmd.exe"), ShellKind::Cmd);
+        assert_eq!(ShellKind::detect("/bin/dash"), ShellKind::Posix);
+    }
+
+    #[test]
+    fn test_bash_rcfile_keeps_user_prompt() {
+        let launch = ShellKind::Bash.launch("shop", Path::new("/tmp/devspin-shell-1"));
+
+        assert_eq!(
+            launch.args,
+            ["--rcfile", "/tmp/devspin-shell-1/bashrc", "-i"]
+        );
+        let (path, content) = &launch.files[0];
+        assert_eq!(path, Path::new("/tmp/devspin-shell-1/bashrc"));
+        assert!(content.contains(". ~/.bashrc"));
+        assert!(content.ends_with("PS1='(shop) '\"$PS1\"\n"));
+    }
+
+    #[test]
+    fn test_zsh_reads_user_files_through_zdotdir() {
+        let launch = ShellKind::Zsh.launch("shop", Path::new("/tmp/devspin-shell-1"));
+
+        assert_eq!(launch.env["ZDOTDIR"], "/tmp/devspin-shell-1");
+        let names: Vec<&Path> = launch
+            .files
+            .iter()
+            .map(|(path, _)| path.strip_prefix("/tmp/devspin-shell-1").unwrap())
+            .collect();
+        assert_eq!(names, [Path::new(".zshenv"), Path::new(".zshrc")]);
+        assert!(launch.files[1].1.contains(". \"$ZDOTDIR/.zshrc\""));
+        assert!(launch.files[1].1.contains("PROMPT='(shop) '\"$PROMPT\""));
+    }
+
+    #[test]
+    fn test_other_shells_need_no_files() {
+        let dir = Path::new("/tmp/devspin-shell-1");
+
+        let fish = ShellKind::Fish.launch("it's", dir);
+        assert!(fish.args[1].contains("printf '%s' '(it\\'s) '"));
+        let powershell = ShellKind::PowerShell.launch("it's", dir);
+        assert_eq!(powershell.args[..2], ["-NoExit", "-Command"]);
+        assert!(powershell.args[2].contains("'(it''s) '"));
+        assert_eq!(
+            ShellKind::Cmd.launch("a$b", dir).env["PROMPT"],
+            "(a$$b) $P$G"
+        );
+        assert_eq!(ShellKind::Posix.launch("shop", dir).env["PS1"], "(shop) $ ");
+        for kind in [
+            ShellKind::Fish,
+            ShellKind::PowerShell,
+            ShellKind::Cmd,
+            ShellKind::Posix,
+        ] {
+            assert!(kind.launch("shop", dir).files.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_write_files() {
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        let launch = ShellKind::Zsh.launch("shop", dir);
+
+        launch.write_files().unwrap();
+
+        assert!(dir.join(".zshenv").is_file());
+        assert!(dir.join(".zshrc").is_file());
+        // Files someone else put there are not replaced
+        assert!(launch.write_files().is_err());
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(sh_quote("it's"), "'it'\\''s'");
+        assert_eq!(fish_quote("a\\b"), "'a\\\\b'");
+        assert_eq!(powershell_quote("it's"), "'it''s'");
+    }
+}
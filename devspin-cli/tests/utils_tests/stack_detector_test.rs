@@ -0,0 +1,146 @@
+use devspin_cli::parser::devspin_generator::{DevSpinGenerator, DEFAULT_DEVSPIN_CONFIG};
+use devspin_cli::parser::migrate::CURRENT_VERSION;
+use devspin_cli::utils::stack_detector::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn task_commands(detection: &Detection) -> Vec<(&str, &str)> {
+        detection
+            .tasks
+            .iter()
+            .map(|t| (t.name.as_str(), t.command.as_str()))
+            .collect()
+    }
+
+    fn touch(root: &Path, files: &[&str]) {
+        for file in files {
+            fs::write(root.join(file), "").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_detect_rust() {
+        let temp_dir = tempdir().unwrap();
+        touch(temp_dir.path(), &["Cargo.toml"]);
+
+        let detection = detect(temp_dir.path());
+        assert_eq!(detection.stacks, [Stack::Rust]);
+        assert_eq!(detection.base.as_deref(), Some("rust:1"));
+        assert_eq!(detection.system, ["pkg-config", "libssl-dev"]);
+        assert_eq!(
+            task_commands(&detection),
+            [
+                ("build", "cargo build"),
+                ("test", "cargo test"),
+                ("run", "cargo run")
+            ]
+        );
+        assert!(detection.spin_features["lint"].enabled);
+        assert_eq!(detection.spin_features["format"].modes, ["all"]);
+    }
+
+    #[test]
+    fn test_detect_node_package_manager() {
+        let temp_dir = tempdir().unwrap();
+        touch(temp_dir.path(), &["package.json", "pnpm-lock.yaml"]);
+
+        let detection = detect(temp_dir.path());
+        assert_eq!(detection.base.as_deref(), Some("node:20"));
+        assert_eq!(
+            task_commands(&detection),
+            [
+                ("build", "pnpm build"),
+                ("test", "pnpm test"),
+                ("run", "pnpm start")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_poetry_project() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[tool.poetry]\nname = \"app\"\n",
+        )
+        .unwrap();
+        touch(temp_dir.path(), &["main.py"]);
+
+        let detection = detect(temp_dir.path());
+        assert_eq!(detection.stacks, [Stack::Python]);
+        assert_eq!(
+            task_commands(&detection),
+            [
+                ("build", "poetry install"),
+                ("test", "poetry run python -m pytest"),
+                ("run", "poetry run python main.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_secondary_stack_is_prefixed() {
+        let temp_dir = tempdir().unwrap();
+        touch(temp_dir.path(), &["go.mod", "Gemfile"]);
+
+        let detection = detect(temp_dir.path());
+        assert_eq!(detection.stacks, [Stack::Ruby, Stack::Go]);
+        assert_eq!(detection.base.as_deref(), Some("ruby:3.3"));
+        assert_eq!(detection.system, ["build-essential", "golang"]);
+        assert!(detection.tasks.iter().any(|t| t.name == "go-test"));
+        assert!(!detection.tasks.iter().any(|t| t.name == "run"));
+    }
+
+    #[test]
+    fn test_detect_nothing() {
+        let temp_dir = tempdir().unwrap();
+        assert_eq!(detect(temp_dir.path()), Detection::default());
+    }
+
+    #[test]
+    fn test_merge_keeps_user_values() {
+        let temp_dir = tempdir().unwrap();
+        touch(temp_dir.path(), &["Cargo.toml"]);
+        let detection = detect(temp_dir.path());
+
+        let config = DevSpinGenerator::generate(DEFAULT_DEVSPIN_CONFIG).unwrap();
+        let merged = detection.merge_into(&config);
+        assert_eq!(merged.header.base, "rust:1");
+        // The template's build task and lint feature are kept as written
+        assert_eq!(merged.content.tasks[0], config.content.tasks[0]);
+        assert_eq!(merged.content.tasks.len(), 3);
+        assert_eq!(
+            merged.content.spin_features["lint"],
+            config.content.spin_features["lint"]
+        );
+
+        let mut custom = config.clone();
+        custom.header.base = "rust:1.80-alpine".to_string();
+        assert_eq!(
+            detection.merge_into(&custom).header.base,
+            "rust:1.80-alpine"
+        );
+        assert_eq!(detection.merge_into(&merged), merged);
+    }
+
+    #[test]
+    fn test_to_config() {
+        let temp_dir = tempdir().unwrap();
+        touch(temp_dir.path(), &["go.mod"]);
+
+        let config = detect(temp_dir.path()).to_config("api");
+        assert_eq!(config.header.version, CURRENT_VERSION);
+        assert_eq!(config.header.name, "api");
+        assert_eq!(config.header.base, "golang:1.22");
+        let written = config.write();
+        assert_eq!(
+            DevSpinGenerator::parse_str(&written, "devspin.yml").unwrap(),
+            config
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use devspin_cli::parser::devspin_generator::{DevSpinGenerator, Probe};
+use devspin_cli::parser::migrate::CURRENT_VERSION;
+use devspin_cli::parser::validator::{validate_schema, validate_str};
+use devspin_cli::utils::templates::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_every_template_parses() {
+        for template in Template::value_variants() {
+            let content = render(*template, "demo", None, &[]).unwrap();
+            let config = DevSpinGenerator::parse_str(&content, "devspin.yml")
+                .unwrap_or_else(|e| panic!("{:?} template: {}", template, e));
+            assert_eq!(config.header.version, CURRENT_VERSION);
+            assert_eq!(config.header.name, "demo");
+            assert_eq!(config.header.base, template.base());
+            assert!(!config.content.tasks.is_empty());
+            assert_eq!(validate_str(&content, "devspin.yml").unwrap(), []);
+            assert_eq!(validate_schema(&content, "devspin.yml").unwrap(), []);
+        }
+    }
+
+    #[test]
+    fn test_render_keeps_template_comments() {
+        let content = render(Template::Node, "shop", Some("node:22"), &[]).unwrap();
+        assert!(content.starts_with(&format!(
+            "version: {}\nname: shop\nbase: node:22\n",
+            CURRENT_VERSION
+        )));
+        assert!(
+            content.contains("  # Skipped while package.json and the lock file are unchanged\n")
+        );
+    }
+
+    #[test]
+    fn test_render_adds_presets_and_their_env() {
+        let presets = [
+            ServicePreset::find("postgres").unwrap(),
+            ServicePreset::find("redis").unwrap(),
+        ];
+        let content = render(Template::Rust, "shop", None, &presets).unwrap();
+        assert_eq!(validate_str(&content, "devspin.yml").unwrap(), []);
+        assert_eq!(validate_schema(&content, "devspin.yml").unwrap(), []);
+        let config = DevSpinGenerator::parse_str(&content, "devspin.yml").unwrap();
+
+        let names: Vec<&str> = config
+            .content
+            .services
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, ["postgres", "redis"]);
+        assert_eq!(config.content.env["RUST_LOG"], "info");
+        assert!(config.content.env["DATABASE_URL"].starts_with("postgres://"));
+        assert_eq!(config.content.env["REDIS_URL"], "redis://localhost:6379");
+
+        let redis = &config.content.services[1];
+        assert_eq!(redis.ports, ["6379:6379"]);
+        assert_eq!(redis.healthcheck.as_ref().unwrap().probe, Probe::Tcp(6379));
+    }
+
+    #[test]
+    fn test_fullstack_template_services() {
+        assert_eq!(Template::Fullstack.services(), ["postgres", "redis"]);
+        assert!(Template::Go.services().is_empty());
+        assert!(ServicePreset::find("kafka").is_none());
+    }
+}